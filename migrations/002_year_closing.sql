@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS year_closing (
+  year INTEGER PRIMARY KEY,
+  is_closed INTEGER NOT NULL DEFAULT 0,
+  closed_at TEXT,
+  closed_by TEXT
+);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS sync_log (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  ts TEXT NOT NULL,
+  event TEXT NOT NULL,
+  direction TEXT,
+  peer_device_id TEXT,
+  peer_device_name TEXT,
+  bytes INTEGER NOT NULL DEFAULT 0,
+  outcome TEXT NOT NULL,
+  message TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_sync_log_ts ON sync_log(ts);
@@ -0,0 +1,2 @@
+ALTER TABLE transactions ADD COLUMN created_by TEXT;
+ALTER TABLE transactions ADD COLUMN updated_by TEXT;
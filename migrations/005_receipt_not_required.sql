@@ -0,0 +1 @@
+ALTER TABLE transactions ADD COLUMN receipt_not_required INTEGER NOT NULL DEFAULT 0;
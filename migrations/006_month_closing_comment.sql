@@ -0,0 +1 @@
+ALTER TABLE month_closing ADD COLUMN comment TEXT;
@@ -0,0 +1,2 @@
+ALTER TABLE categories ADD COLUMN default_description TEXT;
+ALTER TABLE categories ADD COLUMN default_payment_method TEXT;
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS export_log (
+  year INTEGER NOT NULL,
+  month INTEGER NOT NULL,
+  kind TEXT NOT NULL,
+  output_path TEXT NOT NULL,
+  exported_at TEXT NOT NULL,
+  exported_by TEXT,
+  PRIMARY KEY (year, month)
+);
@@ -2,6 +2,7 @@
 use rusqlite::{params, Connection};
 
 use crate::error::AppError;
+use crate::models::AuditLogEntry;
 
 pub fn append_audit(
   conn: &Connection,
@@ -29,3 +30,49 @@ pub fn append_audit(
   )?;
   Ok(())
 }
+
+pub const AUDIT_PURGE_KEEP_RECENT: i64 = 500;
+
+// Never removes the newest entries so MAX(ts) (sync last_change) stays stable.
+pub fn purge_audit_log(conn: &Connection, older_than_days: i64, actor: Option<String>) -> Result<i64, AppError> {
+  let cutoff = (Utc::now() - chrono::Duration::days(older_than_days)).to_rfc3339();
+  let deleted = conn.execute(
+    "DELETE FROM audit_log
+     WHERE ts < ?1
+       AND id NOT IN (SELECT id FROM audit_log ORDER BY ts DESC, id DESC LIMIT ?2)",
+    params![cutoff, AUDIT_PURGE_KEEP_RECENT],
+  )? as i64;
+  if deleted > 0 {
+    append_audit(
+      conn,
+      actor,
+      "PURGE_AUDIT",
+      "AUDIT_LOG",
+      None,
+      None,
+      serde_json::json!({
+        "older_than_days": older_than_days,
+        "cutoff": cutoff,
+        "deleted": deleted,
+        "kept_recent": AUDIT_PURGE_KEEP_RECENT
+      })
+      .to_string(),
+      None,
+    )?;
+  }
+  Ok(deleted)
+}
+
+pub fn map_audit_row(row: &rusqlite::Row) -> Result<AuditLogEntry, rusqlite::Error> {
+  Ok(AuditLogEntry {
+    id: row.get(0)?,
+    ts: row.get(1)?,
+    actor: row.get(2)?,
+    action: row.get(3)?,
+    entity_type: row.get(4)?,
+    entity_id: row.get(5)?,
+    ref_id: row.get(6)?,
+    payload_json: row.get(7)?,
+    details: row.get(8)?,
+  })
+}
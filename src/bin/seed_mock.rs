@@ -74,12 +74,12 @@ fn seed_mock_data(conn: &mut Connection, receipt_base: &PathBuf, count: usize) -
   let tx = conn.transaction()?;
 
   let mut income_stmt = tx.prepare(
-    "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at)
-     VALUES (?1, ?2, ?3, ?4, 'INCOME', ?5, NULL, NULL, ?6, ?7, NULL, ?8, NULL, ?9, ?10)",
+    "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at, is_demo)
+     VALUES (?1, ?2, ?3, ?4, 'INCOME', ?5, NULL, NULL, ?6, ?7, NULL, ?8, NULL, ?9, ?10, 1)",
   )?;
   let mut expense_stmt = tx.prepare(
-    "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at)
-     VALUES (?1, ?2, ?3, ?4, 'EXPENSE', NULL, ?5, ?6, ?7, ?8, ?9, ?10, NULL, ?11, ?12)",
+    "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at, is_demo)
+     VALUES (?1, ?2, ?3, ?4, 'EXPENSE', NULL, ?5, ?6, ?7, ?8, ?9, ?10, NULL, ?11, ?12, 1)",
   )?;
 
   for _ in 0..count {
@@ -1,21 +1,25 @@
 use std::collections::HashSet;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use base64::Engine;
 use chrono::{Datelike, Duration, NaiveDate, Utc};
-use rusqlite::{params, Connection};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
 use serde::Serialize;
-use tauri::State;
+use tauri::{AppHandle, State};
+use walkdir::WalkDir;
 
-use crate::audit::log::append_audit;
+use crate::audit::log::{append_audit, map_audit_row};
 use crate::db;
-use crate::domain::{closing, mwst, validation};
+use crate::domain::validation::PaymentMethod;
+use crate::domain::{closing, mwst, time, validation};
 use crate::error::AppError;
-use crate::export::{csv, excel};
-use crate::files::{backup, receipts};
+use crate::events;
+use crate::export::{bank_csv, csv, excel, excel_import, receipts as receipt_export};
+use crate::files::{backup, receipts, reveal};
 use crate::models::*;
-use crate::reports;
+use crate::reports::{self, map_transaction_row, TRANSACTION_SELECT};
 use crate::settings;
 use crate::sync;
 use crate::AppState;
@@ -34,13 +38,97 @@ pub fn get_settings(state: State<AppState>) -> Result<Settings, AppError> {
 }
 
 #[tauri::command]
-pub fn update_settings(state: State<AppState>, settings_input: Settings, actor: Option<String>) -> Result<Settings, AppError> {
-  let receipt_path = PathBuf::from(&settings_input.receipt_base_folder);
+pub fn format_timestamps(state: State<AppState>, timestamps: Vec<String>) -> Result<Vec<String>, AppError> {
+  let timezone = db::with_conn(&state.db, |conn| settings::get_timezone(conn))?;
+  Ok(timestamps
+    .iter()
+    .map(|ts| time::format_local_ts(ts, timezone))
+    .collect())
+}
+
+#[tauri::command]
+pub fn update_settings(app: AppHandle, state: State<AppState>, settings_input: Settings, actor: Option<String>) -> Result<Settings, AppError> {
+  if settings_input.fiscal_year_start_month != 0 {
+    ensure_month(settings_input.fiscal_year_start_month)?;
+  }
+  if settings_input.default_page_size != 0
+    && !(settings::PAGE_SIZE_MIN..=settings::PAGE_SIZE_MAX).contains(&settings_input.default_page_size)
+  {
+    return Err(AppError::new_field(
+      "INVALID_PAGE_SIZE",
+      "default_page_size",
+      "Seitengroesse muss zwischen 10 und 500 liegen",
+    ));
+  }
+  if settings_input.receipt_max_bytes != 0 && !(RECEIPT_MIN_BYTES..=RECEIPT_MAX_BYTES_LIMIT).contains(&settings_input.receipt_max_bytes) {
+    return Err(AppError::new_field(
+      "INVALID_RECEIPT_SIZE",
+      "receipt_max_bytes",
+      format!(
+        "Maximale Belegdateigroesse muss zwischen {} und {} liegen",
+        receipts::format_megabytes(RECEIPT_MIN_BYTES),
+        receipts::format_megabytes(RECEIPT_MAX_BYTES_LIMIT)
+      ),
+    ));
+  }
+  if mwst::MwstMode::parse(&settings_input.mwst_mode).is_none() {
+    return Err(AppError::new_field(
+      "INVALID_MWST_MODE",
+      "mwst_mode",
+      "MWST-Modus muss EFFEKTIV oder SALDO sein",
+    )
+    .with_details(serde_json::json!({ "allowed": mwst::MwstMode::ALL })));
+  }
+  if !settings_input.vat_rounding_mode.trim().is_empty()
+    && mwst::VatRoundingMode::parse(&settings_input.vat_rounding_mode).is_none()
+  {
+    return Err(AppError::new_field(
+      "INVALID_VAT_ROUNDING",
+      "vat_rounding_mode",
+      "MWST-Rundung muss PER_LINE oder AGGREGATE sein",
+    )
+    .with_details(serde_json::json!({ "allowed": mwst::VatRoundingMode::ALL })));
+  }
+  let currency_code = settings_input.currency_code.trim();
+  if !currency_code.is_empty() && (currency_code.len() != 3 || !currency_code.chars().all(|ch| ch.is_ascii_alphabetic())) {
+    return Err(AppError::new_field(
+      "INVALID_CURRENCY",
+      "currency_code",
+      "Waehrung muss ein dreistelliger Code sein (z.B. CHF, EUR)",
+    ));
+  }
+  if time::Timezone::parse(&settings_input.timezone).is_none() {
+    return Err(AppError::new_field(
+      "INVALID_TIMEZONE",
+      "timezone",
+      "Zeitzone muss LOCAL, UTC oder ein Offset wie +01:00 sein",
+    ));
+  }
+  if !(0..=36500).contains(&settings_input.audit_retention_days) {
+    return Err(AppError::new_field(
+      "INVALID_AUDIT_RETENTION",
+      "audit_retention_days",
+      "Aufbewahrung des Protokolls muss zwischen 0 und 36500 Tagen liegen",
+    ));
+  }
+  if let Some(invalid) = settings_input.receipt_allowed_extensions.iter().find(|ext| {
+    let value = ext.trim().trim_start_matches('.');
+    !value.is_empty() && (value.len() > 8 || !value.chars().all(|ch| ch.is_ascii_alphanumeric()))
+  }) {
+    return Err(AppError::new_field(
+      "INVALID_RECEIPT_EXTENSION",
+      "receipt_allowed_extensions",
+      format!("Ungueltige Dateiendung: {invalid}"),
+    ));
+  }
   if !settings_input.receipt_base_folder.trim().is_empty() {
-    fs::create_dir_all(&receipt_path)?;
+    ensure_receipt_folder_writable(Path::new(&settings_input.receipt_base_folder))?;
+  }
+  if !settings_input.export_base_folder.trim().is_empty() {
+    fs::create_dir_all(PathBuf::from(&settings_input.export_base_folder))?;
   }
 
-  db::with_conn(&state.db, |conn| {
+  let result = db::with_conn(&state.db, |conn| {
     settings::update_settings(conn, &settings_input)?;
     append_audit(
       conn,
@@ -53,14 +141,25 @@ pub fn update_settings(state: State<AppState>, settings_input: Settings, actor:
       None,
     )?;
     Ok(settings_input)
-  })
+  })?;
+  events::emit_data_changed(&app, "SETTINGS", None, None);
+  Ok(result)
+}
+
+#[tauri::command]
+pub fn set_last_viewed_year(state: State<AppState>, year: i32) -> Result<(), AppError> {
+  if !(1900..=9999).contains(&year) {
+    return Err(AppError::new("INVALID_YEAR", "Jahr ungueltig"));
+  }
+  db::with_conn(&state.db, |conn| settings::set_last_viewed_year(conn, year))
 }
 
 #[tauri::command]
 pub fn list_categories(state: State<AppState>) -> Result<Vec<Category>, AppError> {
   db::with_conn(&state.db, |conn| {
     let mut stmt = conn.prepare(
-      "SELECT id, name, description, default_mwst_rate, is_active FROM categories ORDER BY name",
+      "SELECT id, name, description, default_mwst_rate, is_active, default_description, default_payment_method
+       FROM categories ORDER BY name",
     )?;
     let rows = stmt.query_map([], |row| {
       Ok(Category {
@@ -69,6 +168,8 @@ pub fn list_categories(state: State<AppState>) -> Result<Vec<Category>, AppError
         description: row.get(2)?,
         default_mwst_rate: row.get(3)?,
         is_active: row.get::<_, i64>(4)? == 1,
+        default_description: row.get(5)?,
+        default_payment_method: row.get(6)?,
       })
     })?;
 
@@ -77,17 +178,53 @@ pub fn list_categories(state: State<AppState>) -> Result<Vec<Category>, AppError
 }
 
 #[tauri::command]
-pub fn create_category(state: State<AppState>, input: CategoryInput, actor: Option<String>) -> Result<Category, AppError> {
+pub fn list_categories_with_stats(state: State<AppState>) -> Result<Vec<CategoryStat>, AppError> {
   db::with_conn(&state.db, |conn| {
+    let mut stmt = conn.prepare(
+      "SELECT c.id, c.name, c.description, c.default_mwst_rate, c.is_active,
+              COUNT(t.id), COALESCE(SUM(t.amount_chf), 0.0)
+       FROM categories c
+       LEFT JOIN transactions t ON t.category_id = c.id
+       GROUP BY c.id
+       ORDER BY COUNT(t.id) DESC, c.name",
+    )?;
+    let rows = stmt.query_map([], |row| {
+      Ok(CategoryStat {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        default_mwst_rate: row.get(3)?,
+        is_active: row.get::<_, i64>(4)? == 1,
+        tx_count: row.get(5)?,
+        total_amount: row.get(6)?,
+      })
+    })?;
+
+    let mut data = Vec::new();
+    for row in rows {
+      data.push(row?);
+    }
+    Ok(data)
+  })
+}
+
+#[tauri::command]
+pub fn create_category(app: AppHandle, state: State<AppState>, input: CategoryInput, actor: Option<String>) -> Result<Category, AppError> {
+  let result = db::with_conn(&state.db, |conn| {
     let payload_json = serde_json::to_string(&input).unwrap_or_else(|_| "{}".to_string());
     let CategoryInput {
       name,
       description,
       default_mwst_rate,
+      default_description,
+      default_payment_method,
     } = input;
+    let (default_description, default_payment_method) =
+      normalize_category_defaults(default_description, default_payment_method)?;
     conn.execute(
-      "INSERT INTO categories (name, description, default_mwst_rate, is_active) VALUES (?1, ?2, ?3, 1)",
-      params![name, description, default_mwst_rate],
+      "INSERT INTO categories (name, description, default_mwst_rate, is_active, default_description, default_payment_method)
+       VALUES (?1, ?2, ?3, 1, ?4, ?5)",
+      params![name, description, default_mwst_rate, default_description, default_payment_method],
     )?;
     let id = conn.last_insert_rowid();
     append_audit(
@@ -106,13 +243,17 @@ pub fn create_category(state: State<AppState>, input: CategoryInput, actor: Opti
       description,
       default_mwst_rate,
       is_active: true,
+      default_description,
+      default_payment_method,
     })
-  })
+  })?;
+  events::emit_data_changed(&app, "CATEGORY", None, None);
+  Ok(result)
 }
 
 #[tauri::command]
-pub fn update_category(state: State<AppState>, input: CategoryUpdateInput, actor: Option<String>) -> Result<Category, AppError> {
-  db::with_conn(&state.db, |conn| {
+pub fn update_category(app: AppHandle, state: State<AppState>, input: CategoryUpdateInput, actor: Option<String>) -> Result<Category, AppError> {
+  let result = db::with_conn(&state.db, |conn| {
     let payload_json = serde_json::to_string(&input).unwrap_or_else(|_| "{}".to_string());
     let CategoryUpdateInput {
       id,
@@ -120,10 +261,24 @@ pub fn update_category(state: State<AppState>, input: CategoryUpdateInput, actor
       description,
       default_mwst_rate,
       is_active,
+      default_description,
+      default_payment_method,
     } = input;
+    let (default_description, default_payment_method) =
+      normalize_category_defaults(default_description, default_payment_method)?;
     conn.execute(
-      "UPDATE categories SET name = ?1, description = ?2, default_mwst_rate = ?3, is_active = ?4 WHERE id = ?5",
-      params![name, description, default_mwst_rate, if is_active {1} else {0}, id],
+      "UPDATE categories SET name = ?1, description = ?2, default_mwst_rate = ?3, is_active = ?4,
+         default_description = ?5, default_payment_method = ?6
+       WHERE id = ?7",
+      params![
+        name,
+        description,
+        default_mwst_rate,
+        if is_active {1} else {0},
+        default_description,
+        default_payment_method,
+        id
+      ],
     )?;
     append_audit(
       conn,
@@ -141,12 +296,47 @@ pub fn update_category(state: State<AppState>, input: CategoryUpdateInput, actor
       description,
       default_mwst_rate,
       is_active,
+      default_description,
+      default_payment_method,
     })
-  })
+  })?;
+  events::emit_data_changed(&app, "CATEGORY", None, None);
+  Ok(result)
+}
+
+#[tauri::command]
+pub fn import_categories_from_backup(
+  app: AppHandle,
+  state: State<AppState>,
+  archive_path: String,
+  actor: Option<String>,
+) -> Result<i64, AppError> {
+  let imported = sync::import_categories_from_backup(&state, &archive_path)?;
+  db::with_conn(&state.db, |conn| {
+    let payload_json = serde_json::to_string(&serde_json::json!({
+      "archive_path": archive_path,
+      "imported": imported,
+    }))
+    .unwrap_or_else(|_| "{}".to_string());
+    append_audit(
+      conn,
+      actor,
+      "IMPORT",
+      "CATEGORY",
+      None,
+      None,
+      payload_json,
+      Some(format!("{} Kategorien aus Backup importiert", imported)),
+    )
+  })?;
+  if imported > 0 {
+    events::emit_data_changed(&app, "CATEGORY", None, None);
+  }
+  Ok(imported)
 }
 
 #[tauri::command]
-pub fn deactivate_category(state: State<AppState>, id: i64, actor: Option<String>) -> Result<(), AppError> {
+pub fn deactivate_category(app: AppHandle, state: State<AppState>, id: i64, actor: Option<String>) -> Result<(), AppError> {
   db::with_conn(&state.db, |conn| {
     conn.execute("UPDATE categories SET is_active = 0 WHERE id = ?1", params![id])?;
     append_audit(
@@ -160,32 +350,112 @@ pub fn deactivate_category(state: State<AppState>, id: i64, actor: Option<String
       None,
     )?;
     Ok(())
+  })?;
+  events::emit_data_changed(&app, "CATEGORY", None, None);
+  Ok(())
+}
+
+#[tauri::command]
+pub fn get_category_default_mwst(state: State<AppState>, category_id: i64) -> Result<f64, AppError> {
+  db::with_conn(&state.db, |conn| {
+    let (default_mwst, _) = load_category_default_mwst(conn, category_id)?;
+    Ok(default_mwst)
   })
 }
 
 #[tauri::command]
-pub fn create_income(state: State<AppState>, input: NewIncomeInput, actor: Option<String>) -> Result<TransactionListItem, AppError> {
+pub fn reassign_category(
+  app: AppHandle,
+  state: State<AppState>,
+  public_ids: Vec<String>,
+  new_category_id: i64,
+  actor: Option<String>,
+) -> Result<i64, AppError> {
+  if public_ids.is_empty() {
+    return Err(AppError::new("INVALID_ID", "Keine Eintraege ausgewaehlt"));
+  }
+
+  let result = db::with_conn(&state.db, |conn| {
+    let is_active: i64 = conn
+      .query_row(
+        "SELECT is_active FROM categories WHERE id = ?1",
+        params![new_category_id],
+        |row| row.get(0),
+      )
+      .optional()?
+      .ok_or_else(|| AppError::new("CATEGORY_NOT_FOUND", "Kategorie nicht gefunden"))?;
+    if is_active == 0 {
+      return Err(AppError::new("CATEGORY_INACTIVE", "Kategorie ist deaktiviert"));
+    }
+
+    let tx = conn.transaction()?;
+    let now = Utc::now().to_rfc3339();
+    let mut moved = 0_i64;
+
+    for public_id in &public_ids {
+      let (tx_type, year, month): (String, i32, i32) = tx
+        .query_row(
+          "SELECT type, year, month FROM transactions WHERE public_id = ?1",
+          params![public_id],
+          |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::new("NOT_FOUND", format!("Eintrag {public_id} nicht gefunden")))?;
+      if tx_type != "EXPENSE" {
+        return Err(AppError::new(
+          "INVALID_TYPE",
+          format!("Eintrag {public_id} ist keine Ausgabe und hat keine Kategorie"),
+        ));
+      }
+      closing::ensure_period_open(&tx, year, month)?;
+
+      moved += tx.execute(
+        "UPDATE transactions SET category_id = ?1, updated_at = ?2, updated_by = ?3 WHERE public_id = ?4",
+        params![new_category_id, now, actor.as_deref(), public_id],
+      )? as i64;
+    }
+
+    let payload_json = serde_json::to_string(&serde_json::json!({
+      "public_ids": public_ids,
+      "new_category_id": new_category_id,
+      "moved": moved,
+    }))
+    .unwrap_or_else(|_| "{}".to_string());
+    append_audit(
+      &tx,
+      actor,
+      "CATEGORY_REASSIGN",
+      "TRANSACTION",
+      None,
+      Some(new_category_id.to_string()),
+      payload_json,
+      Some("Kategorie neu zugeordnet".to_string()),
+    )?;
+
+    tx.commit()?;
+    Ok(moved)
+  })?;
+  events::emit_data_changed(&app, "TRANSACTION", None, None);
+  Ok(result)
+}
+
+#[tauri::command]
+pub fn create_income(app: AppHandle, state: State<AppState>, input: NewIncomeInput, actor: Option<String>) -> Result<TransactionListItem, AppError> {
   let payload_json = serde_json::to_string(&input).unwrap_or_else(|_| "{}".to_string());
   let date = validation::parse_date(&input.date)?;
   validation::ensure_amount_positive(input.amount_chf)?;
   validation::ensure_mwst_rate(input.mwst_rate)?;
-  if input.payment_method != "BAR" && input.payment_method != "TWINT" {
-    return Err(AppError::new("INVALID_PAYMENT", "Zahlungsart muss BAR oder TWINT sein"));
-  }
+  let payment_method = PaymentMethod::parse(&input.payment_method)?;
 
   let (year, month) = (date.year(), date.month() as i32);
 
-  db::with_conn(&state.db, |conn| {
-    if closing::is_month_closed(conn, year, month)? {
-      return Err(AppError::new("MONTH_CLOSED", "Monat abgeschlossen"));
-    }
+  // Retried when a sync merge holds the write lock; the booking is one transaction, so a replay is safe.
+  let result = db::with_conn_retry(&state.db, |conn| {
+    closing::ensure_period_open(conn, year, month)?;
 
     if !input.allow_duplicate.unwrap_or(false) {
-      if let Some(dup) = check_duplicate_income(conn, date, input.amount_chf, &input.payment_method, input.note.as_deref())? {
-        return Err(AppError::new(
-          "DUPLICATE_WARNING",
-          format!("Moeglicher Doppel-Eintrag: {dup}"),
-        ));
+      if let Some(dup) = check_duplicate_income(conn, date, input.amount_chf, payment_method.as_str(), input.note.as_deref())? {
+        return Err(duplicate_warning(dup));
       }
     }
 
@@ -194,69 +464,85 @@ pub fn create_income(state: State<AppState>, input: NewIncomeInput, actor: Optio
     let now = Utc::now().to_rfc3339();
 
     tx.execute(
-      "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at)
-       VALUES (?1, ?2, ?3, ?4, 'INCOME', ?5, NULL, NULL, ?6, ?7, NULL, ?8, NULL, ?9, ?10)",
+      "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at, created_by, updated_by)
+       VALUES (?1, ?2, ?3, ?4, 'INCOME', ?5, NULL, NULL, ?6, ?7, NULL, ?8, NULL, ?9, ?10, ?11, ?11)",
       params![
         public_id,
         input.date,
         year,
         month,
-        input.payment_method,
+        payment_method.as_str(),
         input.amount_chf,
         input.mwst_rate,
         input.note.clone(),
         now,
-        now
+        now,
+        actor.as_deref()
       ],
     )?;
 
     append_audit(
       &tx,
-      actor,
+      actor.clone(),
       "CREATE_TX",
       "TRANSACTION",
       Some(public_id.clone()),
       None,
-      payload_json,
+      payload_json.clone(),
       None,
     )?;
 
     tx.commit()?;
     fetch_transaction_by_public_id(conn, &public_id)
-  })
+  })?;
+  events::emit_data_changed(&app, "TRANSACTION", Some(year), Some(month));
+  Ok(result)
 }
 
 #[tauri::command]
-pub fn create_expense(state: State<AppState>, input: NewExpenseInput, actor: Option<String>) -> Result<TransactionListItem, AppError> {
+pub fn create_expense(app: AppHandle, state: State<AppState>, input: NewExpenseInput, actor: Option<String>) -> Result<TransactionListItem, AppError> {
   let payload_json = serde_json::to_string(&input).unwrap_or_else(|_| "{}".to_string());
   let date = validation::parse_date(&input.date)?;
   validation::ensure_amount_positive(input.amount_chf)?;
 
   let (year, month) = (date.year(), date.month() as i32);
 
-  db::with_conn(&state.db, |conn| {
-    if closing::is_month_closed(conn, year, month)? {
-      return Err(AppError::new("MONTH_CLOSED", "Monat abgeschlossen"));
-    }
+  let result = db::with_conn(&state.db, |conn| {
+    closing::ensure_period_open(conn, year, month)?;
 
-    let (default_mwst, is_active): (f64, i64) = conn.query_row(
-      "SELECT default_mwst_rate, is_active FROM categories WHERE id = ?1",
-      params![input.category_id],
-      |row| Ok((row.get(0)?, row.get(1)?)),
-    )?;
-    if is_active == 0 {
+    let (default_mwst, is_active) = load_category_default_mwst(conn, input.category_id)?;
+    if !is_active {
       return Err(AppError::new("CATEGORY_INACTIVE", "Kategorie ist deaktiviert"));
     }
 
     let mwst_rate = input.mwst_rate.unwrap_or(default_mwst);
     validation::ensure_mwst_rate(mwst_rate)?;
 
+    let (category_description, category_payment_method): (Option<String>, Option<String>) = conn
+      .query_row(
+        "SELECT default_description, default_payment_method FROM categories WHERE id = ?1",
+        params![input.category_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+      )
+      .optional()?
+      .unwrap_or_default();
+    let description = input
+      .description
+      .as_deref()
+      .map(str::trim)
+      .filter(|value| !value.is_empty())
+      .map(str::to_string)
+      .or(category_description);
+    let payment_method = input
+      .payment_method
+      .as_deref()
+      .or(category_payment_method.as_deref())
+      .map(PaymentMethod::parse)
+      .transpose()?;
+
     if !input.allow_duplicate.unwrap_or(false) {
-      if let Some(dup) = check_duplicate_expense(conn, date, input.amount_chf, input.category_id, input.description.as_deref())? {
-        return Err(AppError::new(
-          "DUPLICATE_WARNING",
-          format!("Moeglicher Doppel-Eintrag: {dup}"),
-        ));
+      if let Some(dup) = check_duplicate_expense(conn, date, input.amount_chf, input.category_id, description.as_deref())? {
+        return Err(duplicate_warning(dup));
       }
     }
 
@@ -266,28 +552,54 @@ pub fn create_expense(state: State<AppState>, input: NewExpenseInput, actor: Opt
 
     let final_receipt = if let Some(source) = input.receipt_source_path.as_deref() {
       let settings = settings::get_settings(&tx)?;
-      let base_folder = resolve_receipt_base(&settings, &state);
-      Some(receipts::copy_receipt(source, &base_folder, year, month, &public_id)?)
+      let base_folder = writable_receipt_base(&settings, &state)?;
+      let max_edge = settings.compress_receipts.then_some(receipts::RECEIPT_MAX_EDGE);
+      let category_name: Option<String> = tx
+        .query_row(
+          "SELECT name FROM categories WHERE id = ?1",
+          params![input.category_id],
+          |row| row.get(0),
+        )
+        .optional()?;
+      let date_str = date.format("%Y-%m-%d").to_string();
+      let target = receipts::ReceiptTarget {
+        year,
+        month,
+        public_id: &public_id,
+        date: &date_str,
+        category: category_name.as_deref(),
+      };
+      Some(receipts::copy_receipt(
+        source,
+        &base_folder,
+        &target,
+        &settings.receipt_naming_pattern,
+        max_edge,
+        settings.receipt_max_bytes,
+        &settings.receipt_allowed_extensions,
+      )?)
     } else {
       None
     };
 
     tx.execute(
-      "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at)
-       VALUES (?1, ?2, ?3, ?4, 'EXPENSE', NULL, ?5, ?6, ?7, ?8, ?9, ?10, NULL, ?11, ?12)",
+      "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at, created_by, updated_by)
+       VALUES (?1, ?2, ?3, ?4, 'EXPENSE', ?14, ?5, ?6, ?7, ?8, ?9, ?10, NULL, ?11, ?12, ?13, ?13)",
       params![
         public_id,
         input.date,
         year,
         month,
         input.category_id,
-        input.description.clone(),
+        description,
         input.amount_chf,
         mwst_rate,
         final_receipt,
         input.note.clone(),
         now,
-        now
+        now,
+        actor.as_deref(),
+        payment_method.map(|method| method.as_str())
       ],
     )?;
 
@@ -304,23 +616,23 @@ pub fn create_expense(state: State<AppState>, input: NewExpenseInput, actor: Opt
 
     tx.commit()?;
     fetch_transaction_by_public_id(conn, &public_id)
-  })
+  })?;
+  events::emit_data_changed(&app, "TRANSACTION", Some(year), Some(month));
+  Ok(result)
 }
 
 #[tauri::command]
-pub fn create_storno(state: State<AppState>, input: StornoInput, actor: Option<String>) -> Result<TransactionListItem, AppError> {
+pub fn create_storno(app: AppHandle, state: State<AppState>, input: StornoInput, actor: Option<String>) -> Result<TransactionListItem, AppError> {
   let payload_json = serde_json::to_string(&input).unwrap_or_else(|_| "{}".to_string());
   let date = validation::parse_date(&input.date)?;
   let (year, month) = (date.year(), date.month() as i32);
 
-  db::with_conn(&state.db, |conn| {
-    if closing::is_month_closed(conn, year, month)? {
-      return Err(AppError::new("MONTH_CLOSED", "Monat abgeschlossen"));
-    }
+  let result = db::with_conn(&state.db, |conn| {
+    closing::ensure_period_open(conn, year, month)?;
 
     let original = {
       let mut stmt = conn.prepare(
-        "SELECT public_id, type, payment_method, category_id, description, amount_chf, mwst_rate, note
+        "SELECT public_id, type, payment_method, category_id, description, amount_chf, mwst_rate, note, date
        FROM transactions WHERE public_id = ?1",
       )?;
       stmt.query_row(params![input.public_id], |row| {
@@ -333,6 +645,7 @@ pub fn create_storno(state: State<AppState>, input: StornoInput, actor: Option<S
           row.get::<_, f64>(5)?,
           row.get::<_, f64>(6)?,
           row.get::<_, Option<String>>(7)?,
+          row.get::<_, String>(8)?,
         ))
       })?
     };
@@ -341,7 +654,43 @@ pub fn create_storno(state: State<AppState>, input: StornoInput, actor: Option<S
       return Err(AppError::new("STORNO_INVALID", "Storno auf Storno nicht erlaubt"));
     }
 
+    let original_date = validation::parse_date(&original.8)?;
+    if date < original_date {
+      return Err(AppError::new_field(
+        "STORNO_DATE_BEFORE_ORIGINAL",
+        "date",
+        format!("Storno-Datum liegt vor dem Original ({})", original.8),
+      ));
+    }
+    if !input.allow_cross_period.unwrap_or(false)
+      && (date.year(), date.month()) != (original_date.year(), original_date.month())
+    {
+      return Err(AppError::new(
+        "STORNO_PERIOD_WARNING",
+        format!(
+          "Storno liegt in einem anderen Monat als das Original ({}/{})",
+          original_date.month(),
+          original_date.year()
+        ),
+      ));
+    }
+
     let amount = input.amount_chf.unwrap_or(original.5).abs();
+    let already_stornoed: f64 = conn.query_row(
+      "SELECT COALESCE(SUM(-amount_chf), 0) FROM transactions WHERE ref_public_id = ?1 AND amount_chf < 0",
+      params![original.0],
+      |row| row.get(0),
+    )?;
+    if amount + already_stornoed > original.5 + 0.005 {
+      return Err(AppError::new(
+        "STORNO_EXCEEDS",
+        format!(
+          "Storno uebersteigt den Originalbetrag ({:.2} bereits storniert, {:.2} offen)",
+          already_stornoed,
+          (original.5 - already_stornoed).max(0.0)
+        ),
+      ));
+    }
     let storno_amount = -amount;
 
     let tx = conn.transaction()?;
@@ -351,8 +700,8 @@ pub fn create_storno(state: State<AppState>, input: StornoInput, actor: Option<S
     let note = format!("Storno {}: {}", original.0, input.reason);
 
     tx.execute(
-      "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at)
-       VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, NULL, ?11, ?12, ?13, ?14)",
+      "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at, created_by, updated_by)
+       VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, NULL, ?11, ?12, ?13, ?14, ?15, ?15)",
       params![
         public_id,
         input.date,
@@ -367,7 +716,8 @@ pub fn create_storno(state: State<AppState>, input: StornoInput, actor: Option<S
         note,
         original.0,
         now,
-        now
+        now,
+        actor.as_deref()
       ],
     )?;
 
@@ -384,26 +734,26 @@ pub fn create_storno(state: State<AppState>, input: StornoInput, actor: Option<S
 
     tx.commit()?;
     fetch_transaction_by_public_id(conn, &public_id)
-  })
+  })?;
+  events::emit_data_changed(&app, "TRANSACTION", Some(year), Some(month));
+  Ok(result)
 }
 
 #[tauri::command]
-pub fn delete_transaction(state: State<AppState>, public_id: String, actor: Option<String>) -> Result<i64, AppError> {
+pub fn delete_transaction(app: AppHandle, state: State<AppState>, public_id: String, actor: Option<String>) -> Result<i64, AppError> {
   let public_id = public_id.trim().to_string();
   if public_id.is_empty() {
     return Err(AppError::new("INVALID_ID", "ID fehlt"));
   }
 
-  db::with_conn(&state.db, |conn| {
+  let result = db::with_conn(&state.db, |conn| {
     let (year, month) = conn.query_row(
       "SELECT year, month FROM transactions WHERE public_id = ?1",
       params![public_id],
       |row| Ok((row.get::<_, i32>(0)?, row.get::<_, i32>(1)?)),
     ).map_err(|_| AppError::new("NOT_FOUND", "Eintrag nicht gefunden"))?;
 
-    if closing::is_month_closed(conn, year, month)? {
-      return Err(AppError::new("MONTH_CLOSED", "Monat abgeschlossen"));
-    }
+    closing::ensure_period_open(conn, year, month)?;
 
     let tx = conn.transaction()?;
     let mut deleted = 0_i64;
@@ -428,119 +778,461 @@ pub fn delete_transaction(state: State<AppState>, public_id: String, actor: Opti
 
     tx.commit()?;
     Ok(deleted)
-  })
-}
-
-#[tauri::command]
-pub fn list_transactions(state: State<AppState>, filter: TransactionFilter) -> Result<Paginated<TransactionListItem>, AppError> {
-  let search = filter.search.clone().unwrap_or_default();
-  let search_trimmed = search.trim();
-  let has_search = !search_trimmed.is_empty();
-  let page = if filter.page < 1 { 1 } else { filter.page };
-  let page_size = if filter.page_size < 1 { 50 } else { filter.page_size };
-  let offset = (page - 1) * page_size;
+  })?;
+  events::emit_data_changed(&app, "TRANSACTION", None, None);
+  Ok(result)
+}
 
-  db::with_conn(&state.db, |conn| {
-    let total: i64 = if has_search {
-      let like = format!("%{}%", search_trimmed);
-      conn.query_row(
-        "SELECT COUNT(*) FROM transactions t
-         LEFT JOIN categories c ON c.id = t.category_id
-         WHERE t.year = ?1 AND t.month = ?2 AND t.type = ?3
-           AND (t.public_id LIKE ?4 OR t.description LIKE ?4 OR t.note LIKE ?4 OR c.name LIKE ?4
-                OR t.date LIKE ?4 OR t.payment_method LIKE ?4 OR t.ref_public_id LIKE ?4
-                OR CAST(t.amount_chf AS TEXT) LIKE ?4)",
-        params![filter.year, filter.month, filter.tx_type, like],
-        |row| row.get(0),
-      )?
-    } else {
-      conn.query_row(
-        "SELECT COUNT(*) FROM transactions WHERE year = ?1 AND month = ?2 AND type = ?3",
-        params![filter.year, filter.month, filter.tx_type],
-        |row| row.get(0),
-      )?
-    };
+#[tauri::command]
+pub fn update_note(
+  app: AppHandle,
+  state: State<AppState>,
+  public_id: String,
+  note: Option<String>,
+  actor: Option<String>,
+) -> Result<TransactionListItem, AppError> {
+  let public_id = public_id.trim().to_string();
+  if public_id.is_empty() {
+    return Err(AppError::new("INVALID_ID", "ID fehlt"));
+  }
+  let note = note.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+
+  let (year, month, result) = db::with_conn(&state.db, |conn| {
+    let (year, month, old_note): (i32, i32, Option<String>) = conn
+      .query_row(
+        "SELECT year, month, note FROM transactions WHERE public_id = ?1",
+        params![public_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+      )
+      .optional()?
+      .ok_or_else(|| AppError::new("NOT_FOUND", "Eintrag nicht gefunden"))?;
+
+    // The note is not a financial field, so closed periods may allow it.
+    if !settings::get_allow_note_edit_when_closed(conn)? {
+      closing::ensure_period_open(conn, year, month)?;
+    }
 
-    let mut items = Vec::new();
-    if has_search {
-      let like = format!("%{}%", search_trimmed);
-      let mut stmt = conn.prepare(
-        "SELECT t.id, t.public_id, t.date, t.year, t.month, t.type, t.payment_method, t.category_id,
-                c.name, t.description, t.amount_chf, t.mwst_rate, t.receipt_path, t.note, t.ref_public_id,
-                t.created_at, t.updated_at,
-                EXISTS (SELECT 1 FROM transactions x WHERE x.ref_public_id = t.public_id) as is_stornoed
-         FROM transactions t
-         LEFT JOIN categories c ON c.id = t.category_id
-         WHERE t.year = ?1 AND t.month = ?2 AND t.type = ?3
-           AND (t.public_id LIKE ?4 OR t.description LIKE ?4 OR t.note LIKE ?4 OR c.name LIKE ?4
-                OR t.date LIKE ?4 OR t.payment_method LIKE ?4 OR t.ref_public_id LIKE ?4
-                OR CAST(t.amount_chf AS TEXT) LIKE ?4)
-         ORDER BY t.date DESC, t.public_id DESC
-         LIMIT ?5 OFFSET ?6",
-      )?;
-      let rows = stmt.query_map(
-        params![filter.year, filter.month, filter.tx_type, like, page_size, offset],
-        |row| map_transaction_row(row),
-      )?;
-      for row in rows {
-        items.push(row?);
-      }
-    } else {
-      let mut stmt = conn.prepare(
-        "SELECT t.id, t.public_id, t.date, t.year, t.month, t.type, t.payment_method, t.category_id,
-                c.name, t.description, t.amount_chf, t.mwst_rate, t.receipt_path, t.note, t.ref_public_id,
-                t.created_at, t.updated_at,
-                EXISTS (SELECT 1 FROM transactions x WHERE x.ref_public_id = t.public_id) as is_stornoed
-         FROM transactions t
-         LEFT JOIN categories c ON c.id = t.category_id
-         WHERE t.year = ?1 AND t.month = ?2 AND t.type = ?3
-         ORDER BY t.date DESC, t.public_id DESC
-         LIMIT ?4 OFFSET ?5",
-      )?;
-      let rows = stmt.query_map(
-        params![filter.year, filter.month, filter.tx_type, page_size, offset],
-        |row| map_transaction_row(row),
-      )?;
-      for row in rows {
-        items.push(row?);
-      }
+    let tx = conn.transaction()?;
+    let now = Utc::now().to_rfc3339();
+    tx.execute(
+      "UPDATE transactions SET note = ?1, updated_at = ?2, updated_by = ?3 WHERE public_id = ?4",
+      params![note, now, actor.as_deref(), public_id],
+    )?;
+
+    let payload_json = serde_json::to_string(&serde_json::json!({
+      "public_id": public_id,
+      "old_note": old_note,
+      "new_note": note,
+    }))
+    .unwrap_or_else(|_| "{}".to_string());
+    append_audit(
+      &tx,
+      actor,
+      "UPDATE_NOTE",
+      "TRANSACTION",
+      Some(public_id.clone()),
+      None,
+      payload_json,
+      None,
+    )?;
+
+    tx.commit()?;
+    Ok((year, month, fetch_transaction_by_public_id(conn, &public_id)?))
+  })?;
+  events::emit_data_changed(&app, "TRANSACTION", Some(year), Some(month));
+  Ok(result)
+}
+
+#[tauri::command]
+pub fn update_payment_method(
+  app: AppHandle,
+  state: State<AppState>,
+  public_id: String,
+  payment_method: String,
+  actor: Option<String>,
+) -> Result<TransactionListItem, AppError> {
+  let public_id = public_id.trim().to_string();
+  if public_id.is_empty() {
+    return Err(AppError::new("INVALID_ID", "ID fehlt"));
+  }
+  let payment_method = PaymentMethod::parse(&payment_method)?;
+
+  let (year, month, result) = db::with_conn(&state.db, |conn| {
+    let (year, month, tx_type, old_method, ref_public_id): (i32, i32, String, Option<String>, Option<String>) = conn
+      .query_row(
+        "SELECT year, month, type, payment_method, ref_public_id FROM transactions WHERE public_id = ?1",
+        params![public_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+      )
+      .optional()?
+      .ok_or_else(|| AppError::new("NOT_FOUND", "Eintrag nicht gefunden"))?;
+    if tx_type != "INCOME" {
+      return Err(AppError::new("INVALID_TYPE", "Zahlungsart gibt es nur bei Einnahmen"));
+    }
+    if ref_public_id.is_some() {
+      return Err(AppError::new(
+        "STORNO_INVALID",
+        "Zahlungsart eines Stornos folgt der Originalbuchung und kann nicht einzeln geaendert werden",
+      ));
+    }
+    closing::ensure_period_open(conn, year, month)?;
+
+    // Stornos must keep the method of their original, otherwise the payment split no longer nets to zero.
+    let stornos = {
+      let mut stmt = conn.prepare("SELECT public_id, year, month FROM transactions WHERE ref_public_id = ?1")?;
+      let rows = stmt.query_map(params![public_id], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)?, row.get::<_, i32>(2)?))
+      })?;
+      rows.collect::<Result<Vec<_>, _>>()?
+    };
+    for (_, storno_year, storno_month) in &stornos {
+      closing::ensure_period_open(conn, *storno_year, *storno_month)?;
     }
+    let storno_ids: Vec<String> = stornos.into_iter().map(|(id, _, _)| id).collect();
 
-    Ok(Paginated { total, items })
-  })
+    let tx = conn.transaction()?;
+    let now = Utc::now().to_rfc3339();
+    tx.execute(
+      "UPDATE transactions SET payment_method = ?1, updated_at = ?2, updated_by = ?3
+       WHERE public_id = ?4 OR ref_public_id = ?4",
+      params![payment_method.as_str(), now, actor.as_deref(), public_id],
+    )?;
+
+    let payload_json = serde_json::to_string(&serde_json::json!({
+      "public_id": public_id,
+      "old_payment_method": old_method,
+      "new_payment_method": payment_method.as_str(),
+      "stornos": storno_ids,
+    }))
+    .unwrap_or_else(|_| "{}".to_string());
+    append_audit(
+      &tx,
+      actor,
+      "UPDATE_PAYMENT_METHOD",
+      "TRANSACTION",
+      Some(public_id.clone()),
+      None,
+      payload_json,
+      None,
+    )?;
+
+    tx.commit()?;
+    Ok((year, month, fetch_transaction_by_public_id(conn, &public_id)?))
+  })?;
+  events::emit_data_changed(&app, "TRANSACTION", Some(year), Some(month));
+  Ok(result)
 }
 
 #[tauri::command]
-pub fn search_transactions(state: State<AppState>, query: String, limit: i64) -> Result<Vec<TransactionListItem>, AppError> {
-  let search_trimmed = query.trim();
-  if search_trimmed.is_empty() {
-    return Ok(Vec::new());
-  }
-  let limit = if limit < 1 { 20 } else { limit.min(100) };
-  let like = format!("%{}%", search_trimmed);
+pub fn relink_receipts(
+  app: AppHandle,
+  state: State<AppState>,
+  old_base: String,
+  new_base: String,
+  move_files: Option<bool>,
+  actor: Option<String>,
+) -> Result<ReceiptRelinkSummary, AppError> {
+  let move_files = move_files.unwrap_or(false);
+  let old_path = PathBuf::from(old_base.trim());
+  let new_path = PathBuf::from(new_base.trim());
+  if old_base.trim().is_empty() || new_base.trim().is_empty() {
+    return Err(AppError::new("INVALID_PATH", "Alter und neuer Belegordner muessen gesetzt sein"));
+  }
+  ensure_receipt_folder_writable(&new_path)?;
 
-  db::with_conn(&state.db, |conn| {
-    let mut stmt = conn.prepare(
-      "SELECT t.id, t.public_id, t.date, t.year, t.month, t.type, t.payment_method, t.category_id,
-              c.name, t.description, t.amount_chf, t.mwst_rate, t.receipt_path, t.note, t.ref_public_id,
-              t.created_at, t.updated_at,
-              EXISTS (SELECT 1 FROM transactions x WHERE x.ref_public_id = t.public_id) as is_stornoed
-       FROM transactions t
-       LEFT JOIN categories c ON c.id = t.category_id
-       WHERE (t.public_id LIKE ?1 OR t.description LIKE ?1 OR t.note LIKE ?1 OR c.name LIKE ?1
-          OR t.date LIKE ?1 OR t.payment_method LIKE ?1 OR t.ref_public_id LIKE ?1
-          OR CAST(t.amount_chf AS TEXT) LIKE ?1 OR t.type LIKE ?1)
-       ORDER BY t.date DESC, t.public_id DESC
-       LIMIT ?2",
+  let summary = db::with_conn(&state.db, |conn| {
+    let tx = conn.transaction()?;
+    let summary = sync::relink_receipt_paths(&tx, &old_path, &new_path, move_files)?;
+    let payload_json = serde_json::to_string(&serde_json::json!({
+      "old_base": old_base,
+      "new_base": new_base,
+      "move_files": move_files,
+      "relinked": summary.relinked,
+      "moved": summary.moved,
+      "broken": summary.broken,
+    }))
+    .unwrap_or_else(|_| "{}".to_string());
+    append_audit(
+      &tx,
+      actor,
+      "RELINK_RECEIPTS",
+      "TRANSACTION",
+      None,
+      None,
+      payload_json,
+      Some(format!("{} Belege neu verknuepft", summary.relinked)),
     )?;
-    let rows = stmt.query_map(params![like, limit], |row| map_transaction_row(row))?;
-    let mut items = Vec::new();
-    for row in rows {
-      items.push(row?);
-    }
-    Ok(items)
-  })
-}
+    tx.commit()?;
+    Ok(summary)
+  })?;
+  events::emit_data_changed(&app, "TRANSACTION", None, None);
+  Ok(summary)
+}
+
+#[tauri::command]
+pub fn set_receipt_not_required(
+  app: AppHandle,
+  state: State<AppState>,
+  public_id: String,
+  not_required: bool,
+  actor: Option<String>,
+) -> Result<TransactionListItem, AppError> {
+  let public_id = public_id.trim().to_string();
+  if public_id.is_empty() {
+    return Err(AppError::new("INVALID_ID", "ID fehlt"));
+  }
+
+  let (year, month, result) = db::with_conn(&state.db, |conn| {
+    let (year, month, tx_type): (i32, i32, String) = conn
+      .query_row(
+        "SELECT year, month, type FROM transactions WHERE public_id = ?1",
+        params![public_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+      )
+      .optional()?
+      .ok_or_else(|| AppError::new("NOT_FOUND", "Eintrag nicht gefunden"))?;
+    if tx_type != "EXPENSE" {
+      return Err(AppError::new("INVALID_TYPE", "Nur Ausgaben benoetigen einen Beleg"));
+    }
+    closing::ensure_period_open(conn, year, month)?;
+
+    let tx = conn.transaction()?;
+    let now = Utc::now().to_rfc3339();
+    tx.execute(
+      "UPDATE transactions SET receipt_not_required = ?1, updated_at = ?2, updated_by = ?3 WHERE public_id = ?4",
+      params![not_required as i64, now, actor.as_deref(), public_id],
+    )?;
+
+    let payload_json = serde_json::to_string(&serde_json::json!({
+      "public_id": public_id,
+      "receipt_not_required": not_required,
+    }))
+    .unwrap_or_else(|_| "{}".to_string());
+    append_audit(
+      &tx,
+      actor,
+      "SET_RECEIPT_NOT_REQUIRED",
+      "TRANSACTION",
+      Some(public_id.clone()),
+      None,
+      payload_json,
+      None,
+    )?;
+
+    tx.commit()?;
+    Ok((year, month, fetch_transaction_by_public_id(conn, &public_id)?))
+  })?;
+  events::emit_data_changed(&app, "TRANSACTION", Some(year), Some(month));
+  Ok(result)
+}
+
+#[tauri::command]
+pub fn list_transactions(state: State<AppState>, filter: TransactionFilter) -> Result<Paginated<TransactionListItem>, AppError> {
+  let search = filter.search.clone().unwrap_or_default();
+  let search_trimmed = search.trim();
+  let has_search = !search_trimmed.is_empty();
+  let ignore_period = has_search && filter.ignore_period.unwrap_or(false);
+  let page = if filter.page < 1 { 1 } else { filter.page };
+
+  let date_from = filter.date_from.as_deref().map(validation::parse_date).transpose()?;
+  let date_to = filter.date_to.as_deref().map(validation::parse_date).transpose()?;
+  if let (Some(from), Some(to)) = (date_from, date_to) {
+    if from > to {
+      return Err(AppError::new("INVALID_DATE_RANGE", "Datum von muss vor Datum bis liegen"));
+    }
+  }
+  if let (Some(min), Some(max)) = (filter.min_amount, filter.max_amount) {
+    if min > max {
+      return Err(AppError::new("INVALID_AMOUNT_RANGE", "Mindestbetrag muss kleiner als Hoechstbetrag sein"));
+    }
+  }
+
+  let mut clauses: Vec<String> = Vec::new();
+  let mut values: Vec<Value> = Vec::new();
+
+  let tx_type = filter.tx_type.trim();
+  if !tx_type.is_empty() && !tx_type.eq_ignore_ascii_case("ALL") {
+    values.push(Value::Text(tx_type.to_string()));
+    clauses.push(format!("t.type = ?{}", values.len()));
+  }
+  if !ignore_period {
+    values.push(Value::Integer(filter.year as i64));
+    clauses.push(format!("t.year = ?{}", values.len()));
+    values.push(Value::Integer(filter.month as i64));
+    clauses.push(format!("t.month = ?{}", values.len()));
+  }
+  if has_search {
+    values.push(Value::Text(format!("%{}%", search_trimmed)));
+    let n = values.len();
+    clauses.push(format!(
+      "(t.public_id LIKE ?{n} OR t.description LIKE ?{n} OR t.note LIKE ?{n} OR c.name LIKE ?{n}
+        OR t.date LIKE ?{n} OR t.payment_method LIKE ?{n} OR t.ref_public_id LIKE ?{n}
+        OR CAST(t.amount_chf AS TEXT) LIKE ?{n})"
+    ));
+  }
+  if let Some(min) = filter.min_amount {
+    values.push(Value::Real(min));
+    clauses.push(format!("t.amount_chf >= ?{}", values.len()));
+  }
+  if let Some(max) = filter.max_amount {
+    values.push(Value::Real(max));
+    clauses.push(format!("t.amount_chf <= ?{}", values.len()));
+  }
+  if let Some(from) = date_from {
+    values.push(Value::Text(from.format("%Y-%m-%d").to_string()));
+    clauses.push(format!("t.date >= ?{}", values.len()));
+  }
+  if let Some(to) = date_to {
+    values.push(Value::Text(to.format("%Y-%m-%d").to_string()));
+    clauses.push(format!("t.date <= ?{}", values.len()));
+  }
+  let order_sql = transaction_order_clause(filter.sort_by.as_deref(), filter.sort_dir.as_deref())?;
+  let where_sql = if clauses.is_empty() {
+    "1 = 1".to_string()
+  } else {
+    clauses.join(" AND ")
+  };
+
+  db::with_conn(&state.db, |conn| {
+    let page_size = if filter.page_size < 1 {
+      settings::get_settings(conn)?.default_page_size
+    } else {
+      filter.page_size
+    };
+    let offset = (page - 1) * page_size;
+
+    let total: i64 = conn.query_row(
+      &format!(
+        "SELECT COUNT(*) FROM transactions t
+         LEFT JOIN categories c ON c.id = t.category_id
+         WHERE {where_sql}"
+      ),
+      params_from_iter(values.iter()),
+      |row| row.get(0),
+    )?;
+
+    let limit_index = values.len() + 1;
+    let offset_index = values.len() + 2;
+    let mut page_values = values.clone();
+    page_values.push(Value::Integer(page_size));
+    page_values.push(Value::Integer(offset));
+
+    let mut stmt = conn.prepare(&format!(
+      "{TRANSACTION_SELECT}
+       WHERE {where_sql}
+       ORDER BY {order_sql}
+       LIMIT ?{limit_index} OFFSET ?{offset_index}"
+    ))?;
+    let rows = stmt.query_map(params_from_iter(page_values.iter()), |row| map_transaction_row(row))?;
+    let mut items = Vec::new();
+    for row in rows {
+      items.push(row?);
+    }
+
+    Ok(Paginated { total, items })
+  })
+}
+
+#[tauri::command]
+pub fn search_transactions(
+  state: State<AppState>,
+  query: String,
+  limit: i64,
+  field: Option<String>,
+  exact: Option<bool>,
+) -> Result<Vec<TransactionListItem>, AppError> {
+  let search_trimmed = query.trim();
+  if search_trimmed.is_empty() {
+    return Ok(Vec::new());
+  }
+  let limit = if limit < 1 { 20 } else { limit.min(100) };
+  let (search_sql, search_value) = build_search_clause(search_trimmed, field.as_deref(), exact.unwrap_or(false))?;
+
+  db::with_conn(&state.db, |conn| {
+    let mut stmt = conn.prepare(&format!(
+      "{TRANSACTION_SELECT}
+       WHERE {search_sql}
+       ORDER BY t.date DESC, t.public_id DESC
+       LIMIT ?2"
+    ))?;
+    let rows = stmt.query_map(params![search_value, limit], |row| map_transaction_row(row))?;
+    let mut items = Vec::new();
+    for row in rows {
+      items.push(row?);
+    }
+    Ok(items)
+  })
+}
+
+#[tauri::command]
+pub fn get_transaction(state: State<AppState>, public_id: String) -> Result<TransactionListItem, AppError> {
+  let public_id = public_id.trim().to_string();
+  if public_id.is_empty() {
+    return Err(AppError::new("INVALID_ID", "ID fehlt"));
+  }
+  db::with_conn(&state.db, |conn| {
+    let exists: Option<i64> = conn
+      .query_row(
+        "SELECT 1 FROM transactions WHERE public_id = ?1",
+        params![public_id],
+        |row| row.get(0),
+      )
+      .optional()?;
+    if exists.is_none() {
+      return Err(AppError::new("NOT_FOUND", format!("Eintrag {public_id} nicht gefunden")));
+    }
+    fetch_transaction_by_public_id(conn, &public_id)
+  })
+}
+
+#[tauri::command]
+pub fn get_transaction_relations(state: State<AppState>, public_id: String) -> Result<TxRelations, AppError> {
+  let public_id = public_id.trim().to_string();
+  if public_id.is_empty() {
+    return Err(AppError::new("INVALID_ID", "ID fehlt"));
+  }
+  db::with_conn(&state.db, |conn| {
+    let ref_public_id: Option<String> = conn
+      .query_row(
+        "SELECT ref_public_id FROM transactions WHERE public_id = ?1",
+        params![public_id],
+        |row| row.get(0),
+      )
+      .optional()?
+      .ok_or_else(|| AppError::new("NOT_FOUND", format!("Eintrag {public_id} nicht gefunden")))?;
+
+    let original = match ref_public_id.as_deref() {
+      Some(ref_id) => {
+        let exists: Option<i64> = conn
+          .query_row("SELECT 1 FROM transactions WHERE public_id = ?1", params![ref_id], |row| row.get(0))
+          .optional()?;
+        match exists {
+          Some(_) => Some(fetch_transaction_by_public_id(conn, ref_id)?),
+          None => None,
+        }
+      }
+      None => None,
+    };
+
+    let mut stmt = conn.prepare(&format!(
+      "{TRANSACTION_SELECT}
+       WHERE t.ref_public_id = ?1
+       ORDER BY t.date, t.public_id"
+    ))?;
+    let rows = stmt.query_map(params![public_id], |row| map_transaction_row(row))?;
+    let mut stornos = Vec::new();
+    for row in rows {
+      stornos.push(row?);
+    }
+
+    Ok(TxRelations {
+      public_id: public_id.clone(),
+      ref_public_id,
+      original,
+      stornos,
+    })
+  })
+}
 
 #[tauri::command]
 pub fn search_transactions_paginated(
@@ -554,11 +1246,16 @@ pub fn search_transactions_paginated(
     return Ok(Paginated { total: 0, items: Vec::new() });
   }
   let page = if page < 1 { 1 } else { page };
-  let page_size = if page_size < 1 { 50 } else { page_size.min(200) };
-  let offset = (page - 1) * page_size;
   let like = format!("%{}%", search_trimmed);
 
   db::with_conn(&state.db, |conn| {
+    let page_size = if page_size < 1 {
+      settings::get_settings(conn)?.default_page_size
+    } else {
+      page_size.min(200)
+    };
+    let offset = (page - 1) * page_size;
+
     let total: i64 = conn.query_row(
       "SELECT COUNT(*)
        FROM transactions t
@@ -570,19 +1267,14 @@ pub fn search_transactions_paginated(
       |row| row.get(0),
     )?;
 
-    let mut stmt = conn.prepare(
-      "SELECT t.id, t.public_id, t.date, t.year, t.month, t.type, t.payment_method, t.category_id,
-              c.name, t.description, t.amount_chf, t.mwst_rate, t.receipt_path, t.note, t.ref_public_id,
-              t.created_at, t.updated_at,
-              EXISTS (SELECT 1 FROM transactions x WHERE x.ref_public_id = t.public_id) as is_stornoed
-       FROM transactions t
-       LEFT JOIN categories c ON c.id = t.category_id
+    let mut stmt = conn.prepare(&format!(
+      "{TRANSACTION_SELECT}
        WHERE (t.public_id LIKE ?1 OR t.description LIKE ?1 OR t.note LIKE ?1 OR c.name LIKE ?1
           OR t.date LIKE ?1 OR t.payment_method LIKE ?1 OR t.ref_public_id LIKE ?1
           OR CAST(t.amount_chf AS TEXT) LIKE ?1 OR t.type LIKE ?1)
        ORDER BY t.date DESC, t.public_id DESC
-       LIMIT ?2 OFFSET ?3",
-    )?;
+       LIMIT ?2 OFFSET ?3"
+    ))?;
     let rows = stmt.query_map(params![like, page_size, offset], |row| map_transaction_row(row))?;
     let mut items = Vec::new();
     for row in rows {
@@ -593,12 +1285,20 @@ pub fn search_transactions_paginated(
 }
 
 #[tauri::command]
-pub fn seed_mock_data(state: State<AppState>, count: i64, actor: Option<String>) -> Result<i64, AppError> {
+pub fn seed_mock_data(
+  app: AppHandle,
+  state: State<AppState>,
+  count: i64,
+  seed: Option<u64>,
+  dry_run: Option<bool>,
+  actor: Option<String>,
+) -> Result<MockSeedSummary, AppError> {
   let count = count.clamp(1, 200_000) as usize;
-  let seed = Utc::now().timestamp_millis() as u64;
+  let seed = seed.unwrap_or_else(|| Utc::now().timestamp_millis() as u64);
+  let dry_run = dry_run.unwrap_or(false);
   let mut rng = MockRng::new(seed);
 
-  db::with_conn(&state.db, |conn| {
+  let result = db::with_conn(&state.db, |conn| {
     let tx = conn.transaction()?;
     let settings = settings::get_settings(&tx)?;
     let year = settings.current_year;
@@ -608,11 +1308,13 @@ pub fn seed_mock_data(state: State<AppState>, count: i64, actor: Option<String>)
       return Err(AppError::new("CATEGORIES", "Keine Kategorien vorhanden"));
     }
 
-    let base_folder = resolve_receipt_base(&settings, &state);
-    std::fs::create_dir_all(&base_folder)?;
+    let base_folder = writable_receipt_base(&settings, &state)?;
     let demo_receipt = base_folder.join("demo_receipt.png");
-    if !demo_receipt.exists() {
-      std::fs::write(&demo_receipt, DEMO_PNG_BYTES)?;
+    if !dry_run {
+      std::fs::create_dir_all(&base_folder)?;
+      if !demo_receipt.exists() {
+        std::fs::write(&demo_receipt, DEMO_PNG_BYTES)?;
+      }
     }
     let demo_receipt_path = demo_receipt.to_string_lossy().to_string();
 
@@ -642,16 +1344,32 @@ pub fn seed_mock_data(state: State<AppState>, count: i64, actor: Option<String>)
     ];
 
     let mut income_stmt = tx.prepare(
-      "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at)
-       VALUES (?1, ?2, ?3, ?4, 'INCOME', ?5, NULL, NULL, ?6, ?7, NULL, ?8, NULL, ?9, ?10)",
+      "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at, created_by, updated_by, is_demo)
+       VALUES (?1, ?2, ?3, ?4, 'INCOME', ?5, NULL, NULL, ?6, ?7, NULL, ?8, NULL, ?9, ?10, ?11, ?11, 1)",
     )?;
     let mut expense_stmt = tx.prepare(
-      "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at)
-       VALUES (?1, ?2, ?3, ?4, 'EXPENSE', NULL, ?5, ?6, ?7, ?8, ?9, ?10, NULL, ?11, ?12)",
+      "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at, created_by, updated_by, is_demo)
+       VALUES (?1, ?2, ?3, ?4, 'EXPENSE', NULL, ?5, ?6, ?7, ?8, ?9, ?10, NULL, ?11, ?12, ?13, ?13, 1)",
     )?;
 
+    let mut closed_months = HashSet::new();
+    for month in 1..=12u32 {
+      if !closing::period_permission(&tx, year, month as i32)?.allowed {
+        closed_months.insert(month);
+      }
+    }
+
+    let mut income_count = 0;
+    let mut expense_count = 0;
+    let mut receipt_count = 0;
+    let mut skipped_closed = 0;
+
     for _ in 0..count {
       let month = (rng.next_u32() % 12 + 1) as u32;
+      if closed_months.contains(&month) {
+        skipped_closed += 1;
+        continue;
+      }
       let day = (rng.next_u32() % days_in_month(year, month) + 1) as u32;
       let date = chrono::NaiveDate::from_ymd_opt(year, month, day)
         .unwrap_or_else(|| chrono::NaiveDate::from_ymd_opt(year, month, 1).unwrap());
@@ -663,11 +1381,15 @@ pub fn seed_mock_data(state: State<AppState>, count: i64, actor: Option<String>)
 
       let is_income = (rng.next_u32() % 100) < 65;
       if is_income {
-        let payment_method = if (rng.next_u32() % 2) == 0 { "BAR" } else { "TWINT" };
+        let payment_method = PaymentMethod::ALL[(rng.next_u32() as usize) % PaymentMethod::ALL.len()].as_str();
         let amount = random_amount(&mut rng, 20.0, 700.0);
         let mwst_rate = mwst_options[(rng.next_u32() as usize) % mwst_options.len()];
         let note = income_notes[(rng.next_u32() as usize) % income_notes.len()];
 
+        income_count += 1;
+        if dry_run {
+          continue;
+        }
         income_stmt.execute(params![
           public_id,
           date_str,
@@ -678,7 +1400,8 @@ pub fn seed_mock_data(state: State<AppState>, count: i64, actor: Option<String>)
           mwst_rate,
           format!("Demo: {note}"),
           now,
-          now
+          now,
+          actor.as_deref()
         ])?;
       } else {
         let idx = (rng.next_u32() as usize) % categories.len();
@@ -691,6 +1414,13 @@ pub fn seed_mock_data(state: State<AppState>, count: i64, actor: Option<String>)
           None
         };
 
+        expense_count += 1;
+        if receipt_path.is_some() {
+          receipt_count += 1;
+        }
+        if dry_run {
+          continue;
+        }
         expense_stmt.execute(params![
           public_id,
           date_str,
@@ -703,7 +1433,8 @@ pub fn seed_mock_data(state: State<AppState>, count: i64, actor: Option<String>)
           receipt_path,
           Some(format!("Demo: {description}")),
           now,
-          now
+          now,
+          actor.as_deref()
         ])?;
       }
     }
@@ -711,9 +1442,27 @@ pub fn seed_mock_data(state: State<AppState>, count: i64, actor: Option<String>)
     drop(income_stmt);
     drop(expense_stmt);
 
+    let summary = MockSeedSummary {
+      count: count as i64,
+      income_count,
+      expense_count,
+      receipt_count,
+      skipped_closed,
+      seed,
+      dry_run,
+    };
+    if dry_run {
+      return Ok(summary);
+    }
+
     let payload_json = serde_json::to_string(&serde_json::json!({
       "count": count,
       "year": year,
+      "seed": seed,
+      "income_count": income_count,
+      "expense_count": expense_count,
+      "receipt_count": receipt_count,
+      "skipped_closed": skipped_closed,
     }))
     .unwrap_or_else(|_| "{}".to_string());
 
@@ -729,12 +1478,105 @@ pub fn seed_mock_data(state: State<AppState>, count: i64, actor: Option<String>)
     )?;
 
     tx.commit()?;
-    Ok(count as i64)
-  })
+    Ok(summary)
+  })?;
+  if !result.dry_run {
+    events::emit_data_changed(&app, "TRANSACTION", None, None);
+  }
+  Ok(result)
+}
+
+#[tauri::command]
+pub fn factory_reset(
+  app: AppHandle,
+  state: State<AppState>,
+  confirm: String,
+  wipe_receipts: Option<bool>,
+  actor: Option<String>,
+) -> Result<(), AppError> {
+  if confirm != "RESET" {
+    return Err(AppError::new("RESET_NOT_CONFIRMED", "Zuruecksetzen muss mit RESET bestaetigt werden"));
+  }
+  let wipe_receipts = wipe_receipts.unwrap_or(false);
+
+  let (receipt_base, receipt_paths) = db::with_conn(&state.db, |conn| {
+    let settings = settings::get_settings(conn)?;
+    let receipt_base = resolve_receipt_base(&settings, &state);
+
+    let tx = conn.transaction()?;
+    let tx_count: i64 = tx.query_row("SELECT COUNT(*) FROM transactions", [], |row| row.get(0))?;
+    // Only files booked in this database are wiped; anything else in the receipt folder stays.
+    let mut receipt_paths: Vec<String> = Vec::new();
+    if wipe_receipts {
+      let mut stmt = tx.prepare(
+        "SELECT DISTINCT receipt_path FROM transactions WHERE receipt_path IS NOT NULL AND receipt_path <> ''",
+      )?;
+      for path in stmt.query_map([], |row| row.get(0))? {
+        receipt_paths.push(path?);
+      }
+    }
+    // Settings are reset on purpose (receipt folder, MWST mode, sync pairing); ensure_defaults re-seeds them.
+    tx.execute_batch(
+      "DELETE FROM transactions;
+       DELETE FROM month_closing;
+       DELETE FROM year_closing;
+       DELETE FROM settings;
+       DELETE FROM audit_log;
+       DELETE FROM sync_log;
+       DELETE FROM export_log;",
+    )?;
+    db::reset_default_categories(&tx)?;
+    db::seed_defaults(&tx, &state.receipt_base)?;
+
+    let payload_json = serde_json::to_string(&serde_json::json!({
+      "deleted_transactions": tx_count,
+      "wipe_receipts": wipe_receipts,
+    }))
+    .unwrap_or_else(|_| "{}".to_string());
+    append_audit(
+      &tx,
+      actor,
+      "FACTORY_RESET",
+      "SYSTEM",
+      None,
+      None,
+      payload_json,
+      Some("Alle Daten zurueckgesetzt".to_string()),
+    )?;
+    tx.commit()?;
+    Ok((receipt_base, receipt_paths))
+  })?;
+
+  for path in receipt_paths.iter().map(PathBuf::from) {
+    if !path.starts_with(&receipt_base) {
+      continue;
+    }
+    match fs::remove_file(&path) {
+      Ok(()) => {}
+      Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+      Err(err) => return Err(err.into()),
+    }
+    // Drop month/year folders that became empty; remove_dir fails on the first non-empty one.
+    for dir in path.ancestors().skip(1).take_while(|dir| *dir != receipt_base.as_path()) {
+      if fs::remove_dir(dir).is_err() {
+        break;
+      }
+    }
+  }
+
+  events::emit_data_changed(&app, "ALL", None, None);
+  Ok(())
 }
 
 #[tauri::command]
-pub fn clear_demo_data(state: State<AppState>, actor: Option<String>) -> Result<i64, AppError> {
+pub fn clear_demo_data(
+  app: AppHandle,
+  state: State<AppState>,
+  include_legacy: Option<bool>,
+  actor: Option<String>,
+) -> Result<i64, AppError> {
+  let include_legacy = include_legacy.unwrap_or(false);
+  // Legacy heuristics only for data seeded before the is_demo marker existed.
   let income_notes = [
     "Mittagsverkauf",
     "Abendverkauf",
@@ -743,44 +1585,45 @@ pub fn clear_demo_data(state: State<AppState>, actor: Option<String>) -> Result<
     "Wochenmarkt",
   ];
 
-  db::with_conn(&state.db, |conn| {
+  let result = db::with_conn(&state.db, |conn| {
     let tx = conn.transaction()?;
-    let mut deleted = 0_i64;
-    deleted += tx.execute(
-      "DELETE FROM transactions
-       WHERE note LIKE 'Demo%' OR note LIKE '[DEMO]%' OR note LIKE 'DEMO%'
-          OR receipt_path LIKE '%demo_receipt.png'",
-      [],
-    )? as i64;
-
-    deleted += tx.execute(
-      "DELETE FROM transactions
-       WHERE type = 'INCOME' AND note IN (?1, ?2, ?3, ?4, ?5)",
-      params![
-        income_notes[0],
-        income_notes[1],
-        income_notes[2],
-        income_notes[3],
-        income_notes[4],
-      ],
-    )? as i64;
+    let mut deleted = tx.execute("DELETE FROM transactions WHERE is_demo = 1", [])? as i64;
+
+    if include_legacy {
+      deleted += tx.execute(
+        "DELETE FROM transactions
+         WHERE note LIKE 'Demo%' OR note LIKE '[DEMO]%' OR note LIKE 'DEMO%'
+            OR receipt_path LIKE '%demo_receipt.png'",
+        [],
+      )? as i64;
+
+      deleted += tx.execute(
+        "DELETE FROM transactions
+         WHERE type = 'INCOME' AND note IN (?1, ?2, ?3, ?4, ?5)",
+        params![
+          income_notes[0],
+          income_notes[1],
+          income_notes[2],
+          income_notes[3],
+          income_notes[4],
+        ],
+      )? as i64;
+    }
 
     let settings = settings::get_settings(&tx)?;
     let base_folder = resolve_receipt_base(&settings, &state);
     let demo_receipt = base_folder.join("demo_receipt.png");
-    if demo_receipt.exists() {
-      let remaining: i64 = tx.query_row(
-        "SELECT COUNT(*) FROM transactions WHERE receipt_path LIKE '%demo_receipt.png'",
-        [],
-        |row| row.get(0),
-      )?;
-      if remaining == 0 {
-        let _ = fs::remove_file(&demo_receipt);
-      }
-    }
+    let remaining: i64 = tx.query_row(
+      "SELECT COUNT(*) FROM transactions WHERE receipt_path LIKE '%demo_receipt.png'",
+      [],
+      |row| row.get(0),
+    )?;
+    // The demo file is shared by all seeded rows; it goes only once the deletion is committed.
+    let remove_demo_receipt = remaining == 0 && demo_receipt.exists();
 
     let payload_json = serde_json::to_string(&serde_json::json!({
       "deleted": deleted,
+      "include_legacy": include_legacy,
     }))
     .unwrap_or_else(|_| "{}".to_string());
     append_audit(
@@ -795,8 +1638,13 @@ pub fn clear_demo_data(state: State<AppState>, actor: Option<String>) -> Result<
     )?;
 
     tx.commit()?;
+    if remove_demo_receipt {
+      let _ = fs::remove_file(&demo_receipt);
+    }
     Ok(deleted)
-  })
+  })?;
+  events::emit_data_changed(&app, "TRANSACTION", None, None);
+  Ok(result)
 }
 
 #[tauri::command]
@@ -804,70 +1652,134 @@ pub fn get_month_kpis(state: State<AppState>, year: i32, month: i32) -> Result<M
   db::with_conn(&state.db, |conn| {
     let base = reports::get_month_base_kpis(conn, year, month)?;
     let settings = settings::get_settings(conn)?;
-    let result = base.income_total - base.expense_total;
-    let margin = mwst::safe_margin(result, base.income_total);
-    let mwst_due = if settings.mwst_mode == "SALDO" {
-      mwst::saldo_due(base.income_total, settings.mwst_saldo_rate)
-    } else {
-      mwst::effective_due(base.mwst_income, base.mwst_expense)
-    };
+    Ok(reports::kpis_from_base(base, &settings))
+  })
+}
+
+#[tauri::command]
+pub fn get_year_kpis(state: State<AppState>, year: i32) -> Result<YearKpis, AppError> {
+  db::with_conn(&state.db, |conn| {
+    let base = reports::get_year_base_kpis(conn, year)?;
+    let settings = settings::get_settings(conn)?;
+    Ok(reports::kpis_from_base(base, &settings))
+  })
+}
+
+#[tauri::command]
+pub fn get_fiscal_year_kpis(state: State<AppState>, fiscal_year: i32) -> Result<YearKpis, AppError> {
+  db::with_conn(&state.db, |conn| {
+    let settings = settings::get_settings(conn)?;
+    let base = reports::get_fiscal_year_base_kpis(conn, fiscal_year, settings.fiscal_year_start_month)?;
+    Ok(reports::kpis_from_base(base, &settings))
+  })
+}
+
+#[tauri::command]
+pub fn get_daterange_kpis(state: State<AppState>, date_from: String, date_to: String) -> Result<YearKpis, AppError> {
+  let from = validation::parse_date(&date_from)?;
+  let to = validation::parse_date(&date_to)?;
+  if from > to {
+    return Err(AppError::new("INVALID_DATE_RANGE", "Datumsbereich ungueltig"));
+  }
+  db::with_conn(&state.db, |conn| {
+    let settings = settings::get_settings(conn)?;
+    let base = reports::get_daterange_base_kpis(conn, &from.to_string(), &to.to_string())?;
+    Ok(reports::kpis_from_base(base, &settings))
+  })
+}
+
+#[tauri::command]
+pub fn get_fiscal_month_series(state: State<AppState>, fiscal_year: i32) -> Result<Vec<MonthSeriesPoint>, AppError> {
+  db::with_conn(&state.db, |conn| {
+    let settings = settings::get_settings(conn)?;
+    reports::get_fiscal_month_series(conn, fiscal_year, settings.fiscal_year_start_month)
+  })
+}
+
+#[tauri::command]
+pub fn get_category_trend(state: State<AppState>, year: i32, category_id: i64) -> Result<Vec<MonthAmountPoint>, AppError> {
+  db::with_conn(&state.db, |conn| {
+    ensure_category_exists(conn, category_id)?;
+    reports::get_category_trend(conn, year, category_id)
+  })
+}
 
-    Ok(MonthKpis {
-      income_total: base.income_total,
-      income_bar: base.income_bar,
-      income_twint: base.income_twint,
-      expense_total: base.expense_total,
-      result,
-      margin,
-      mwst_income: base.mwst_income,
-      mwst_expense: base.mwst_expense,
-      mwst_due,
-      missing_receipts_count: base.missing_receipts_count,
-      missing_receipts_sum: base.missing_receipts_sum,
+#[tauri::command]
+pub fn get_mwst_comparison(state: State<AppState>, year: i32) -> Result<MwstComparison, AppError> {
+  db::with_conn(&state.db, |conn| {
+    let base = reports::get_year_base_kpis(conn, year)?;
+    let settings = settings::get_settings(conn)?;
+    let effective_due = mwst::round2(mwst::effective_due(base.mwst_income, base.mwst_expense));
+    let saldo_due = mwst::round2(mwst::saldo_due(base.income_total, settings.mwst_saldo_rate));
+    let difference = mwst::round2(effective_due - saldo_due);
+    let cheaper_mode = if difference > 0.0 { "SALDO" } else { "EFFEKTIV" };
+
+    Ok(MwstComparison {
+      year,
+      active_mode: settings.mwst_mode,
+      saldo_rate: settings.mwst_saldo_rate,
+      effective_due,
+      saldo_due,
+      difference,
+      cheaper_mode: cheaper_mode.to_string(),
     })
   })
 }
 
 #[tauri::command]
-pub fn get_year_kpis(state: State<AppState>, year: i32) -> Result<YearKpis, AppError> {
+pub fn preview_mwst_mode(state: State<AppState>, year: i32, mode: String) -> Result<MwstModePreview, AppError> {
+  let proposed = mwst::MwstMode::parse(&mode).ok_or_else(|| {
+    AppError::new_field("INVALID_MWST_MODE", "mode", "MWST-Modus muss EFFEKTIV oder SALDO sein")
+      .with_details(serde_json::json!({ "allowed": mwst::MwstMode::ALL }))
+  })?;
   db::with_conn(&state.db, |conn| {
     let base = reports::get_year_base_kpis(conn, year)?;
     let settings = settings::get_settings(conn)?;
-    let result = base.income_total - base.expense_total;
-    let margin = mwst::safe_margin(result, base.income_total);
-    let mwst_due = if settings.mwst_mode == "SALDO" {
-      mwst::saldo_due(base.income_total, settings.mwst_saldo_rate)
-    } else {
-      mwst::effective_due(base.mwst_income, base.mwst_expense)
+    let current = reports::kpis_from_base(base.clone(), &settings);
+    // Only the in-memory copy carries the proposed mode; nothing is persisted.
+    let proposed_settings = Settings {
+      mwst_mode: proposed.as_str().to_string(),
+      ..settings.clone()
     };
-
-    Ok(YearKpis {
-      income_total: base.income_total,
-      income_bar: base.income_bar,
-      income_twint: base.income_twint,
-      expense_total: base.expense_total,
-      result,
-      margin,
-      mwst_income: base.mwst_income,
-      mwst_expense: base.mwst_expense,
-      mwst_due,
-      missing_receipts_count: base.missing_receipts_count,
-      missing_receipts_sum: base.missing_receipts_sum,
+    let preview = reports::kpis_from_base(base, &proposed_settings);
+    Ok(MwstModePreview {
+      year,
+      current_mode: settings.mwst_mode,
+      current_mwst_due: current.mwst_due,
+      proposed_mode: proposed.as_str().to_string(),
+      proposed_mwst_due: preview.mwst_due,
+      difference: mwst::round2(preview.mwst_due - current.mwst_due),
     })
   })
 }
 
 #[tauri::command]
 pub fn get_month_charts(state: State<AppState>, year: i32, month: i32) -> Result<MonthCharts, AppError> {
+  db::with_conn(&state.db, |conn| month_charts(conn, year, month))
+}
+
+#[tauri::command]
+pub fn get_month_dashboard(state: State<AppState>, year: i32, month: i32) -> Result<MonthDashboard, AppError> {
+  ensure_month(month)?;
   db::with_conn(&state.db, |conn| {
-    Ok(MonthCharts {
-      daily: reports::get_daily_series(conn, year, month)?,
-      payments: reports::get_payment_split(conn, year, Some(month))?,
-      categories: reports::get_top_categories(conn, year, Some(month), 8)?,
+    let base = reports::get_month_base_kpis(conn, year, month)?;
+    let settings = settings::get_settings(conn)?;
+    Ok(MonthDashboard {
+      kpis: reports::kpis_from_base(base, &settings),
+      charts: month_charts(conn, year, month)?,
+      status: closing::get_month_status(conn, year, month)?,
     })
   })
 }
 
+fn month_charts(conn: &Connection, year: i32, month: i32) -> Result<MonthCharts, AppError> {
+  Ok(MonthCharts {
+    daily: reports::get_daily_series(conn, year, month)?,
+    payments: reports::get_payment_split(conn, year, Some(month))?,
+    categories: reports::get_top_categories(conn, year, Some(month), 8)?,
+  })
+}
+
 #[tauri::command]
 pub fn get_year_charts(state: State<AppState>, year: i32) -> Result<YearCharts, AppError> {
   db::with_conn(&state.db, |conn| {
@@ -879,60 +1791,401 @@ pub fn get_year_charts(state: State<AppState>, year: i32) -> Result<YearCharts,
   })
 }
 
+#[tauri::command]
+pub fn get_storno_report(state: State<AppState>, year: i32, month: Option<i32>) -> Result<Vec<StornoPair>, AppError> {
+  if let Some(month) = month {
+    ensure_month(month)?;
+  }
+  db::with_conn(&state.db, |conn| reports::get_storno_report(conn, year, month))
+}
+
+#[tauri::command]
+pub fn get_largest_transactions(
+  state: State<AppState>,
+  year: i32,
+  month: Option<i32>,
+  tx_type: Option<String>,
+  limit: Option<i64>,
+) -> Result<Vec<TransactionListItem>, AppError> {
+  if let Some(month) = month {
+    ensure_month(month)?;
+  }
+  let tx_type = tx_type
+    .map(|value| value.trim().to_ascii_uppercase())
+    .filter(|value| !value.is_empty() && value != "ALL");
+  if let Some(value) = tx_type.as_deref() {
+    if value != "INCOME" && value != "EXPENSE" {
+      return Err(AppError::new_field("INVALID_TYPE", "tx_type", "Typ muss INCOME oder EXPENSE sein"));
+    }
+  }
+  let limit = limit.unwrap_or(10).clamp(1, 100);
+  db::with_conn(&state.db, |conn| {
+    reports::get_largest_transactions(conn, year, month, tx_type.as_deref(), limit)
+  })
+}
+
+#[tauri::command]
+pub fn get_ticket_stats(state: State<AppState>, year: i32, month: Option<i32>) -> Result<TicketStats, AppError> {
+  if let Some(month) = month {
+    ensure_month(month)?;
+  }
+  db::with_conn(&state.db, |conn| reports::get_ticket_stats(conn, year, month))
+}
+
+#[tauri::command]
+pub fn list_broken_receipts(state: State<AppState>) -> Result<Vec<BrokenReceipt>, AppError> {
+  db::with_conn(&state.db, |conn| reports::find_broken_receipts(conn))
+}
+
 #[tauri::command]
 pub fn get_month_status(state: State<AppState>, year: i32, month: i32) -> Result<MonthStatus, AppError> {
   db::with_conn(&state.db, |conn| closing::get_month_status(conn, year, month))
 }
 
 #[tauri::command]
-pub fn close_month(state: State<AppState>, year: i32, month: i32, actor: Option<String>) -> Result<(), AppError> {
+pub fn can_edit_period(state: State<AppState>, year: i32, month: i32) -> Result<EditPermission, AppError> {
+  ensure_month(month)?;
+  db::with_conn(&state.db, |conn| closing::period_permission(conn, year, month))
+}
+
+#[tauri::command]
+pub fn close_month(
+  app: AppHandle,
+  state: State<AppState>,
+  year: i32,
+  month: i32,
+  force: Option<bool>,
+  comment: Option<String>,
+  actor: Option<String>,
+) -> Result<(), AppError> {
+  ensure_month(month)?;
+  let force = force.unwrap_or(false);
+  let comment = comment.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+
+  db::with_conn(&state.db, |conn| {
+    if closing::is_year_closed(conn, year)? {
+      return Err(AppError::new("YEAR_CLOSED", "Jahr abgeschlossen"));
+    }
+    let base = reports::get_month_base_kpis(conn, year, month)?;
+    let tx_count: i64 = conn.query_row(
+      "SELECT COUNT(*) FROM transactions WHERE year = ?1 AND month = ?2",
+      params![year, month],
+      |row| row.get(0),
+    )?;
+
+    if !force {
+      let mut issues = Vec::new();
+      if base.missing_receipts_count > 0 {
+        issues.push(format!("{} Ausgaben ohne Beleg", base.missing_receipts_count));
+      }
+      if tx_count == 0 {
+        issues.push("keine Buchungen im Monat".to_string());
+      }
+      if !issues.is_empty() {
+        return Err(AppError::new(
+          "MONTH_HAS_ISSUES",
+          format!("Monat hat offene Punkte: {}", issues.join(", ")),
+        ));
+      }
+    }
+
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+      "INSERT OR IGNORE INTO month_closing (year, month, is_closed, closed_at, closed_by) VALUES (?1, ?2, 0, NULL, NULL)",
+      params![year, month],
+    )?;
+    conn.execute(
+      "UPDATE month_closing SET is_closed = 1, closed_at = ?1, closed_by = ?2, comment = ?3 WHERE year = ?4 AND month = ?5",
+      params![now, actor.clone(), comment, year, month],
+    )?;
+    let payload_json = serde_json::to_string(&serde_json::json!({
+      "forced": force,
+      "comment": comment,
+      "missing_receipts_count": base.missing_receipts_count,
+      "tx_count": tx_count,
+    }))
+    .unwrap_or_else(|_| "{}".to_string());
+    append_audit(
+      conn,
+      actor,
+      "CLOSE_MONTH",
+      "MONTH",
+      Some(format!("{year}-{month:02}")),
+      None,
+      payload_json,
+      None,
+    )?;
+    Ok(())
+  })?;
+  events::emit_data_changed(&app, "MONTH", Some(year), Some(month));
+  Ok(())
+}
+
+#[tauri::command]
+pub fn close_months(
+  app: AppHandle,
+  state: State<AppState>,
+  year: i32,
+  months: Vec<i32>,
+  force: Option<bool>,
+  actor: Option<String>,
+) -> Result<Vec<i32>, AppError> {
+  let months = normalize_months(months)?;
+  let force = force.unwrap_or(false);
+
+  let changed = db::with_conn(&state.db, |conn| {
+    if closing::is_year_closed(conn, year)? {
+      return Err(AppError::new("YEAR_CLOSED", "Jahr abgeschlossen"));
+    }
+    let tx = conn.transaction()?;
+    let now = Utc::now().to_rfc3339();
+    let mut changed = Vec::new();
+    let mut issues = Vec::new();
+
+    for &month in &months {
+      if closing::get_month_status(&tx, year, month)?.is_closed {
+        continue;
+      }
+      if !force {
+        let base = reports::get_month_base_kpis(&tx, year, month)?;
+        let tx_count: i64 = tx.query_row(
+          "SELECT COUNT(*) FROM transactions WHERE year = ?1 AND month = ?2",
+          params![year, month],
+          |row| row.get(0),
+        )?;
+        if base.missing_receipts_count > 0 || tx_count == 0 {
+          issues.push(month);
+          continue;
+        }
+      }
+      tx.execute(
+        "INSERT OR IGNORE INTO month_closing (year, month, is_closed, closed_at, closed_by) VALUES (?1, ?2, 0, NULL, NULL)",
+        params![year, month],
+      )?;
+      tx.execute(
+        "UPDATE month_closing SET is_closed = 1, closed_at = ?1, closed_by = ?2, comment = NULL WHERE year = ?3 AND month = ?4",
+        params![now, actor.clone(), year, month],
+      )?;
+      changed.push(month);
+    }
+
+    if !issues.is_empty() {
+      let labels: Vec<String> = issues.iter().map(|month| format!("{year}-{month:02}")).collect();
+      return Err(AppError::new(
+        "MONTH_HAS_ISSUES",
+        format!("Monate mit offenen Punkten: {}", labels.join(", ")),
+      )
+      .with_details(serde_json::json!({ "months": issues })));
+    }
+
+    if !changed.is_empty() {
+      let payload_json = serde_json::to_string(&serde_json::json!({
+        "months": changed,
+        "forced": force,
+      }))
+      .unwrap_or_else(|_| "{}".to_string());
+      append_audit(
+        &tx,
+        actor,
+        "CLOSE_MONTHS",
+        "MONTH",
+        Some(year.to_string()),
+        None,
+        payload_json,
+        None,
+      )?;
+    }
+    tx.commit()?;
+    Ok(changed)
+  })?;
+  if !changed.is_empty() {
+    events::emit_data_changed(&app, "MONTH", Some(year), None);
+  }
+  Ok(changed)
+}
+
+#[tauri::command]
+pub fn open_months(
+  app: AppHandle,
+  state: State<AppState>,
+  year: i32,
+  months: Vec<i32>,
+  actor: Option<String>,
+) -> Result<Vec<i32>, AppError> {
+  let months = normalize_months(months)?;
+
+  let changed = db::with_conn(&state.db, |conn| {
+    if closing::is_year_closed(conn, year)? {
+      return Err(AppError::new("YEAR_CLOSED", "Jahr abgeschlossen"));
+    }
+    let tx = conn.transaction()?;
+    let mut changed = Vec::new();
+
+    for &month in &months {
+      if !closing::get_month_status(&tx, year, month)?.is_closed {
+        continue;
+      }
+      tx.execute(
+        "UPDATE month_closing SET is_closed = 0, closed_at = NULL, closed_by = NULL, comment = NULL WHERE year = ?1 AND month = ?2",
+        params![year, month],
+      )?;
+      changed.push(month);
+    }
+
+    if !changed.is_empty() {
+      let payload_json = serde_json::to_string(&serde_json::json!({ "months": changed }))
+        .unwrap_or_else(|_| "{}".to_string());
+      append_audit(
+        &tx,
+        actor,
+        "OPEN_MONTHS",
+        "MONTH",
+        Some(year.to_string()),
+        None,
+        payload_json,
+        None,
+      )?;
+    }
+    tx.commit()?;
+    Ok(changed)
+  })?;
+  if !changed.is_empty() {
+    events::emit_data_changed(&app, "MONTH", Some(year), None);
+  }
+  Ok(changed)
+}
+
+#[tauri::command]
+pub fn open_month(app: AppHandle, state: State<AppState>, year: i32, month: i32, actor: Option<String>) -> Result<(), AppError> {
+  db::with_conn(&state.db, |conn| {
+    if closing::is_year_closed(conn, year)? {
+      return Err(AppError::new("YEAR_CLOSED", "Jahr abgeschlossen"));
+    }
+    conn.execute(
+      "INSERT OR IGNORE INTO month_closing (year, month, is_closed, closed_at, closed_by) VALUES (?1, ?2, 0, NULL, NULL)",
+      params![year, month],
+    )?;
+    conn.execute(
+      "UPDATE month_closing SET is_closed = 0, closed_at = NULL, closed_by = NULL, comment = NULL WHERE year = ?1 AND month = ?2",
+      params![year, month],
+    )?;
+    append_audit(
+      conn,
+      actor,
+      "OPEN_MONTH",
+      "MONTH",
+      Some(format!("{year}-{month:02}")),
+      None,
+      "{}".to_string(),
+      None,
+    )?;
+    Ok(())
+  })?;
+  events::emit_data_changed(&app, "MONTH", Some(year), Some(month));
+  Ok(())
+}
+
+#[tauri::command]
+pub fn close_year(app: AppHandle, state: State<AppState>, year: i32, actor: Option<String>) -> Result<(), AppError> {
   db::with_conn(&state.db, |conn| {
     let now = Utc::now().to_rfc3339();
     conn.execute(
-      "INSERT OR IGNORE INTO month_closing (year, month, is_closed, closed_at, closed_by) VALUES (?1, ?2, 0, NULL, NULL)",
-      params![year, month],
+      "INSERT OR IGNORE INTO year_closing (year, is_closed, closed_at, closed_by) VALUES (?1, 0, NULL, NULL)",
+      params![year],
     )?;
     conn.execute(
-      "UPDATE month_closing SET is_closed = 1, closed_at = ?1, closed_by = ?2 WHERE year = ?3 AND month = ?4",
-      params![now, actor.clone(), year, month],
+      "UPDATE year_closing SET is_closed = 1, closed_at = ?1, closed_by = ?2 WHERE year = ?3",
+      params![now, actor.clone(), year],
     )?;
     append_audit(
       conn,
       actor,
-      "CLOSE_MONTH",
-      "MONTH",
-      Some(format!("{year}-{month:02}")),
+      "CLOSE_YEAR",
+      "YEAR",
+      Some(year.to_string()),
       None,
       "{}".to_string(),
       None,
     )?;
     Ok(())
-  })
+  })?;
+  events::emit_data_changed(&app, "YEAR", Some(year), None);
+  Ok(())
 }
 
 #[tauri::command]
-pub fn open_month(state: State<AppState>, year: i32, month: i32, actor: Option<String>) -> Result<(), AppError> {
+pub fn open_year(app: AppHandle, state: State<AppState>, year: i32, actor: Option<String>) -> Result<(), AppError> {
   db::with_conn(&state.db, |conn| {
     conn.execute(
-      "INSERT OR IGNORE INTO month_closing (year, month, is_closed, closed_at, closed_by) VALUES (?1, ?2, 0, NULL, NULL)",
-      params![year, month],
+      "INSERT OR IGNORE INTO year_closing (year, is_closed, closed_at, closed_by) VALUES (?1, 0, NULL, NULL)",
+      params![year],
     )?;
     conn.execute(
-      "UPDATE month_closing SET is_closed = 0, closed_at = NULL, closed_by = NULL WHERE year = ?1 AND month = ?2",
-      params![year, month],
+      "UPDATE year_closing SET is_closed = 0, closed_at = NULL, closed_by = NULL WHERE year = ?1",
+      params![year],
     )?;
     append_audit(
       conn,
       actor,
-      "OPEN_MONTH",
-      "MONTH",
-      Some(format!("{year}-{month:02}")),
+      "OPEN_YEAR",
+      "YEAR",
+      Some(year.to_string()),
       None,
       "{}".to_string(),
       None,
     )?;
     Ok(())
-  })
+  })?;
+  events::emit_data_changed(&app, "YEAR", Some(year), None);
+  Ok(())
+}
+
+#[tauri::command]
+pub fn rollover_year(app: AppHandle, state: State<AppState>, actor: Option<String>) -> Result<i32, AppError> {
+  let (year, rolled) = db::with_conn(&state.db, |conn| {
+    let current_year = settings::get_settings(conn)?.current_year;
+    // Only advance up to the calendar year, so repeated runs never skip a year.
+    let rolled = current_year < Utc::now().year();
+    let year = if rolled { current_year + 1 } else { current_year };
+
+    let tx = conn.transaction()?;
+    if rolled {
+      settings::set_current_year(&tx, year)?;
+    }
+    tx.execute(
+      "INSERT OR IGNORE INTO year_closing (year, is_closed, closed_at, closed_by) VALUES (?1, 0, NULL, NULL)",
+      params![year],
+    )?;
+    for month in 1..=12 {
+      tx.execute(
+        "INSERT OR IGNORE INTO month_closing (year, month, is_closed, closed_at, closed_by) VALUES (?1, ?2, 0, NULL, NULL)",
+        params![year, month],
+      )?;
+    }
+    if rolled {
+      let payload_json = serde_json::to_string(&serde_json::json!({
+        "from_year": current_year,
+        "to_year": year,
+      }))
+      .unwrap_or_else(|_| "{}".to_string());
+      append_audit(
+        &tx,
+        actor,
+        "ROLLOVER_YEAR",
+        "YEAR",
+        Some(year.to_string()),
+        None,
+        payload_json,
+        Some(format!("Jahreswechsel {} -> {}", current_year, year)),
+      )?;
+    }
+    tx.commit()?;
+    Ok((year, rolled))
+  })?;
+  if rolled {
+    events::emit_data_changed(&app, "SETTINGS", Some(year), None);
+  }
+  Ok(year)
 }
 
 #[tauri::command]
@@ -949,19 +2202,7 @@ pub fn list_audit_log(state: State<AppState>, page: i64, page_size: i64) -> Resu
        ORDER BY ts DESC
        LIMIT ?1 OFFSET ?2",
     )?;
-    let rows = stmt.query_map(params![page_size, offset], |row| {
-      Ok(AuditLogEntry {
-        id: row.get(0)?,
-        ts: row.get(1)?,
-        actor: row.get(2)?,
-        action: row.get(3)?,
-        entity_type: row.get(4)?,
-        entity_id: row.get(5)?,
-        ref_id: row.get(6)?,
-        payload_json: row.get(7)?,
-        details: row.get(8)?,
-      })
-    })?;
+    let rows = stmt.query_map(params![page_size, offset], |row| map_audit_row(row))?;
 
     let mut items = Vec::new();
     for row in rows {
@@ -972,13 +2213,69 @@ pub fn list_audit_log(state: State<AppState>, page: i64, page_size: i64) -> Resu
   })
 }
 
+#[tauri::command]
+pub fn purge_audit_log(state: State<AppState>, older_than_days: i64, actor: Option<String>) -> Result<i64, AppError> {
+  if older_than_days < 1 {
+    return Err(AppError::new_field(
+      "INVALID_AUDIT_RETENTION",
+      "older_than_days",
+      "Anzahl Tage muss mindestens 1 sein",
+    ));
+  }
+  db::with_conn(&state.db, |conn| {
+    let tx = conn.transaction()?;
+    let deleted = crate::audit::log::purge_audit_log(&tx, older_than_days, actor)?;
+    tx.commit()?;
+    Ok(deleted)
+  })
+}
+
+#[tauri::command]
+pub fn get_transaction_audit(state: State<AppState>, public_id: String) -> Result<Vec<AuditLogEntry>, AppError> {
+  let public_id = public_id.trim().to_string();
+  if public_id.is_empty() {
+    return Err(AppError::new("INVALID_ID", "ID fehlt"));
+  }
+
+  db::with_conn(&state.db, |conn| {
+    let mut stmt = conn.prepare(
+      "SELECT id, ts, actor, action, entity_type, entity_id, ref_id, payload_json, details
+       FROM audit_log
+       WHERE entity_id = ?1 OR ref_id = ?1
+       ORDER BY ts, id",
+    )?;
+    let rows = stmt.query_map(params![public_id], |row| map_audit_row(row))?;
+
+    let mut items = Vec::new();
+    for row in rows {
+      items.push(row?);
+    }
+    Ok(items)
+  })
+}
+
 #[tauri::command]
 pub fn export_excel(state: State<AppState>, request: ExportRequest) -> Result<String, AppError> {
-  let app_dir = state.app_dir.clone();
+  // A half-specified range is a caller bug; don't widen it to the whole year.
+  if request.month_from.is_some() != request.month_to.is_some() {
+    return Err(AppError::new(
+      "INVALID_MONTH_RANGE",
+      "Monatsbereich unvollstaendig: von und bis muessen beide gesetzt sein",
+    )
+    .with_details(serde_json::json!({
+      "month_from": request.month_from,
+      "month_to": request.month_to,
+    })));
+  }
+  let years = normalize_export_years(request.years.as_deref())?;
   db::with_conn(&state.db, |conn| {
-    let export_dir = app_dir.join("Exports");
+    let settings = settings::get_settings(conn)?;
+    let export_dir = resolve_export_base(&settings, &state);
     fs::create_dir_all(&export_dir)?;
-    let filename = if let Some(month) = request.month {
+    let filename = if let Some(years) = years.as_deref() {
+      let labels: Vec<String> = years.iter().map(|year| year.to_string()).collect();
+      format!("export_{}.xlsx", labels.join("_"))
+    } else if let Some(month) = request.month {
       format!("export_{}_{}.xlsx", request.year, format!("{:02}", month))
     } else if let (Some(month_from), Some(month_to)) = (request.month_from, request.month_to) {
       format!(
@@ -1016,7 +2313,9 @@ pub fn export_excel(state: State<AppState>, request: ExportRequest) -> Result<St
         .unwrap_or(&filename),
     );
 
-    if let Some(month) = request.month {
+    if let Some(years) = years.as_deref() {
+      excel::export_years(conn, years, excel_path.as_path(), Some(&receipts_dir))?;
+    } else if let Some(month) = request.month {
       ensure_month(month)?;
       excel::export_month(conn, request.year, month, excel_path.as_path(), Some(&receipts_dir))?;
     } else if let (Some(month_from), Some(month_to)) = (request.month_from, request.month_to) {
@@ -1026,6 +2325,17 @@ pub fn export_excel(state: State<AppState>, request: ExportRequest) -> Result<St
       excel::export_year(conn, request.year, excel_path.as_path(), Some(&receipts_dir))?;
     }
 
+    let periods: Vec<(i32, i32)> = if let Some(years) = years.as_deref() {
+      years.iter().flat_map(|year| (1..=12).map(move |month| (*year, month))).collect()
+    } else if let Some(month) = request.month {
+      vec![(request.year, month)]
+    } else if let (Some(month_from), Some(month_to)) = (request.month_from, request.month_to) {
+      (month_from..=month_to).map(|month| (request.year, month)).collect()
+    } else {
+      (1..=12).map(|month| (request.year, month)).collect()
+    };
+    record_export(conn, &periods, "EXCEL", &excel_path.to_string_lossy(), request.actor.as_deref())?;
+
     let payload_json = serde_json::to_string(&request).unwrap_or_else(|_| "{}".to_string());
     append_audit(
       conn,
@@ -1047,11 +2357,13 @@ pub fn export_csv(
   state: State<AppState>,
   year: i32,
   output_path: Option<String>,
+  with_subtotals: Option<bool>,
   actor: Option<String>,
 ) -> Result<String, AppError> {
-  let app_dir = state.app_dir.clone();
+  let with_subtotals = with_subtotals.unwrap_or(false);
   db::with_conn(&state.db, |conn| {
-    let export_dir = app_dir.join("Exports");
+    let settings = settings::get_settings(conn)?;
+    let export_dir = resolve_export_base(&settings, &state);
     fs::create_dir_all(&export_dir)?;
     let default_path = export_dir.join(format!("export_{}.csv", year));
     let output_path = output_path
@@ -1062,11 +2374,14 @@ pub fn export_csv(
       fs::create_dir_all(parent)?;
     }
 
-    csv::export_year_csv(conn, year, PathBuf::from(&output_path).as_path())?;
+    csv::export_year_csv(conn, year, PathBuf::from(&output_path).as_path(), with_subtotals)?;
+    let periods: Vec<(i32, i32)> = (1..=12).map(|month| (year, month)).collect();
+    record_export(conn, &periods, "CSV", &output_path, actor.as_deref())?;
 
     let payload_json = serde_json::to_string(&serde_json::json!({
       "year": year,
       "output_path": output_path,
+      "with_subtotals": with_subtotals,
     }))
     .unwrap_or_else(|_| "{}".to_string());
 
@@ -1085,11 +2400,137 @@ pub fn export_csv(
   })
 }
 
+#[tauri::command]
+pub fn get_export_status(state: State<AppState>, year: i32, month: i32) -> Result<Option<ExportInfo>, AppError> {
+  ensure_month(month)?;
+  db::with_conn(&state.db, |conn| {
+    Ok(conn
+      .query_row(
+        "SELECT year, month, kind, output_path, exported_at, exported_by FROM export_log WHERE year = ?1 AND month = ?2",
+        params![year, month],
+        |row| {
+          Ok(ExportInfo {
+            year: row.get(0)?,
+            month: row.get(1)?,
+            kind: row.get(2)?,
+            output_path: row.get(3)?,
+            exported_at: row.get(4)?,
+            exported_by: row.get(5)?,
+          })
+        },
+      )
+      .optional()?)
+  })
+}
+
+fn record_export(
+  conn: &Connection,
+  periods: &[(i32, i32)],
+  kind: &str,
+  output_path: &str,
+  actor: Option<&str>,
+) -> Result<(), AppError> {
+  let now = Utc::now().to_rfc3339();
+  // Empty months are skipped so a year export does not mark months without bookings as exported.
+  let mut stmt = conn.prepare(
+    "INSERT OR REPLACE INTO export_log (year, month, kind, output_path, exported_at, exported_by)
+     SELECT ?1, ?2, ?3, ?4, ?5, ?6
+     WHERE EXISTS (SELECT 1 FROM transactions WHERE year = ?1 AND month = ?2)",
+  )?;
+  for (year, month) in periods {
+    stmt.execute(params![year, month, kind, output_path, now, actor])?;
+  }
+  Ok(())
+}
+
+#[tauri::command]
+pub fn export_audit_log(
+  state: State<AppState>,
+  output_path: Option<String>,
+  actor: Option<String>,
+) -> Result<String, AppError> {
+  db::with_conn(&state.db, |conn| {
+    let settings = settings::get_settings(conn)?;
+    let export_dir = resolve_export_base(&settings, &state);
+    fs::create_dir_all(&export_dir)?;
+    let default_path = export_dir.join(format!("audit_log_{}.csv", Utc::now().format("%Y%m%d")));
+    let output_path = output_path
+      .clone()
+      .unwrap_or_else(|| default_path.to_string_lossy().to_string());
+
+    if let Some(parent) = PathBuf::from(&output_path).parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    let rows = csv::export_audit_log(conn, PathBuf::from(&output_path).as_path())?;
+
+    let payload_json = serde_json::to_string(&serde_json::json!({
+      "output_path": output_path,
+      "rows": rows,
+    }))
+    .unwrap_or_else(|_| "{}".to_string());
+
+    append_audit(
+      conn,
+      actor,
+      "EXPORT",
+      "AUDIT_LOG",
+      Some(output_path.clone()),
+      None,
+      payload_json,
+      None,
+    )?;
+
+    Ok(output_path)
+  })
+}
+
+#[tauri::command]
+pub fn export_receipts(
+  state: State<AppState>,
+  year: i32,
+  month_from: i32,
+  month_to: i32,
+  output_dir: String,
+  actor: Option<String>,
+) -> Result<ReceiptExportSummary, AppError> {
+  ensure_month_range(month_from, month_to)?;
+  if output_dir.trim().is_empty() {
+    return Err(AppError::new_field("INVALID_PATH", "output_dir", "Zielordner fehlt"));
+  }
+  db::with_conn(&state.db, |conn| {
+    let summary = receipt_export::export_period_receipts(conn, year, month_from, month_to, &PathBuf::from(output_dir.trim()))?;
+
+    let payload_json = serde_json::to_string(&serde_json::json!({
+      "year": year,
+      "month_from": month_from,
+      "month_to": month_to,
+      "output_dir": summary.output_dir,
+      "exported": summary.exported,
+      "missing": summary.missing,
+    }))
+    .unwrap_or_else(|_| "{}".to_string());
+
+    append_audit(
+      conn,
+      actor,
+      "EXPORT",
+      "EXPORT",
+      Some(summary.output_dir.clone()),
+      None,
+      payload_json,
+      Some(format!("Belege exportiert: {}", summary.exported)),
+    )?;
+
+    Ok(summary)
+  })
+}
+
 #[tauri::command]
 pub fn create_backup(state: State<AppState>, request: BackupRequest) -> Result<String, AppError> {
   let app_dir = state.app_dir.clone();
   db::with_conn(&state.db, |conn| {
-    db::checkpoint(conn)?;
+    db::ensure_wal_drained(conn)?;
     let settings = settings::get_settings(conn)?;
     let receipt_base = resolve_receipt_base(&settings, &state);
     let path = backup::create_backup(
@@ -1115,16 +2556,23 @@ pub fn create_backup(state: State<AppState>, request: BackupRequest) -> Result<S
 }
 
 #[tauri::command]
-pub fn restore_backup(state: State<AppState>, request: RestoreRequest) -> Result<(), AppError> {
-  let receipt_base = db::with_conn(&state.db, |conn| {
+pub fn restore_backup(app: AppHandle, state: State<AppState>, request: RestoreRequest) -> Result<RestoreResult, AppError> {
+  let (receipt_base, tx_count_before) = db::with_conn(&state.db, |conn| {
     let settings = settings::get_settings(conn)?;
-    Ok(resolve_receipt_base(&settings, &state))
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM transactions", [], |row| row.get(0))?;
+    Ok((resolve_receipt_base(&settings, &state), count))
   })?;
 
-  backup::restore_backup(&request.archive_path, &state.db.db_path, &receipt_base)?;
-  db::reload_connection(&state.db)?;
+  let backup_schema_version = backup::restore_backup(&request.archive_path, &state.db.db_path, &receipt_base)?;
 
-  db::with_conn(&state.db, |conn| {
+  let result = db::reload_connection(&state.db).and_then(|_| db::with_conn(&state.db, |conn| {
+    let check = db::verify_restored_db(conn)?;
+    db::seed_defaults(conn, &state.receipt_base)?;
+    let restored_settings = settings::get_settings(conn)?;
+    if !PathBuf::from(&restored_settings.receipt_base_folder).is_dir() {
+      sync::ensure_receipt_setting(conn, &receipt_base)?;
+      sync::fix_receipt_paths(conn, &receipt_base)?;
+    }
     append_audit(
       conn,
       request.actor.clone(),
@@ -1133,12 +2581,32 @@ pub fn restore_backup(state: State<AppState>, request: RestoreRequest) -> Result
       Some(request.archive_path.clone()),
       None,
       serde_json::to_string(&request).unwrap_or_else(|_| "{}".to_string()),
-      None,
+      Some(restore_check_details(&check)),
     )?;
-    Ok(())
-  })?;
+    let (year_min, year_max): (Option<i32>, Option<i32>) =
+      conn.query_row("SELECT MIN(year), MAX(year) FROM transactions", [], |row| {
+        Ok((row.get(0)?, row.get(1)?))
+      })?;
+    Ok(RestoreResult {
+      tx_count_before,
+      tx_count_after: check.tx_count,
+      year_min,
+      year_max,
+      backup_schema_version,
+      schema_version: check.schema_version,
+    })
+  }));
+  let result = match result {
+    Ok(result) => result,
+    Err(err) => {
+      backup::rollback_restore(&state.db.db_path)?;
+      db::reload_connection(&state.db)?;
+      return Err(err);
+    }
+  };
 
-  Ok(())
+  events::emit_data_changed(&app, "ALL", None, None);
+  Ok(result)
 }
 
 #[tauri::command]
@@ -1162,6 +2630,187 @@ pub fn open_receipt(state: State<AppState>, path: String, actor: Option<String>)
   Ok(())
 }
 
+#[tauri::command]
+pub fn reveal_in_folder(path: String) -> Result<(), AppError> {
+  reveal::reveal_in_folder(&path)
+}
+
+#[tauri::command]
+pub fn attach_receipts_from_folder(
+  app: AppHandle,
+  state: State<AppState>,
+  folder: String,
+  overwrite: Option<bool>,
+  actor: Option<String>,
+) -> Result<ReceiptAttachSummary, AppError> {
+  let folder_path = PathBuf::from(&folder);
+  if !folder_path.is_dir() {
+    return Err(AppError::new("FOLDER_NOT_FOUND", "Ordner nicht gefunden"));
+  }
+  let overwrite = overwrite.unwrap_or(false);
+
+  let mut files: Vec<PathBuf> = fs::read_dir(&folder_path)?
+    .filter_map(Result::ok)
+    .map(|entry| entry.path())
+    .filter(|path| path.is_file())
+    .collect();
+  files.sort();
+
+  // Files copied for this run and attachments replaced by overwrite, handled after the transaction.
+  let mut copied: Vec<String> = Vec::new();
+  let mut replaced: Vec<String> = Vec::new();
+  let result = db::with_conn(&state.db, |conn| {
+    let settings = settings::get_settings(conn)?;
+    let base_folder = writable_receipt_base(&settings, &state)?;
+    let max_edge = settings.compress_receipts.then_some(receipts::RECEIPT_MAX_EDGE);
+    let naming_pattern = settings.receipt_naming_pattern.as_str();
+    let tx = conn.transaction()?;
+    let now = Utc::now().to_rfc3339();
+
+    let mut summary = ReceiptAttachSummary {
+      attached: 0,
+      matched: Vec::new(),
+      unmatched: Vec::new(),
+      skipped_closed: Vec::new(),
+      skipped_existing: Vec::new(),
+    };
+
+    for path in files {
+      let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+      let public_id = match parse_receipt_public_id(&path) {
+        Some(value) => value,
+        None => {
+          summary.unmatched.push(file_name);
+          continue;
+        }
+      };
+
+      let existing: Option<(i32, i32, String, Option<String>, Option<String>)> = tx
+        .query_row(
+          "SELECT t.year, t.month, t.date, t.receipt_path, c.name
+           FROM transactions t
+           LEFT JOIN categories c ON c.id = t.category_id
+           WHERE t.public_id = ?1",
+          params![public_id],
+          |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+        )
+        .optional()?;
+      let (year, month, date, receipt_path, category_name) = match existing {
+        Some(value) => value,
+        None => {
+          summary.unmatched.push(file_name);
+          continue;
+        }
+      };
+
+      if closing::ensure_period_open(&tx, year, month).is_err() {
+        summary.skipped_closed.push(file_name);
+        continue;
+      }
+      if !overwrite && receipt_path.as_deref().map(|value| !value.trim().is_empty()).unwrap_or(false) {
+        summary.skipped_existing.push(file_name);
+        continue;
+      }
+
+      let source = path.to_string_lossy().to_string();
+      let target = receipts::ReceiptTarget {
+        year,
+        month,
+        public_id: &public_id,
+        date: &date,
+        category: category_name.as_deref(),
+      };
+      let stored = receipts::copy_receipt(
+        &source,
+        &base_folder,
+        &target,
+        naming_pattern,
+        max_edge,
+        settings.receipt_max_bytes,
+        &settings.receipt_allowed_extensions,
+      )?;
+      if receipt_reference_count(&tx, &stored)? == 0 {
+        copied.push(stored.clone());
+      }
+      if let Some(old) = receipt_path.filter(|old| !old.trim().is_empty() && *old != stored) {
+        replaced.push(old);
+      }
+      tx.execute(
+        "UPDATE transactions SET receipt_path = ?1, updated_at = ?2, updated_by = ?3 WHERE public_id = ?4",
+        params![stored, now, actor.as_deref(), public_id],
+      )?;
+      summary.attached += 1;
+      summary.matched.push(file_name);
+    }
+
+    let payload_json = serde_json::to_string(&serde_json::json!({
+      "folder": folder,
+      "overwrite": overwrite,
+      "attached": summary.attached,
+      "unmatched": summary.unmatched.len(),
+      "skipped_closed": summary.skipped_closed.len(),
+      "skipped_existing": summary.skipped_existing.len(),
+    }))
+    .unwrap_or_else(|_| "{}".to_string());
+    append_audit(
+      &tx,
+      actor,
+      "ATTACH_RECEIPTS",
+      "TRANSACTION",
+      None,
+      None,
+      payload_json,
+      Some("Belege aus Ordner zugeordnet".to_string()),
+    )?;
+
+    tx.commit()?;
+    Ok(summary)
+  });
+  let result = match result {
+    Ok(summary) => summary,
+    Err(err) => {
+      // The rows were rolled back, so the files copied for them are orphans.
+      for path in &copied {
+        let _ = fs::remove_file(path);
+      }
+      return Err(err);
+    }
+  };
+  let _ = db::with_conn(&state.db, |conn| {
+    for path in &replaced {
+      remove_unreferenced_receipt(conn, path)?;
+    }
+    Ok(())
+  });
+  events::emit_data_changed(&app, "TRANSACTION", None, None);
+  Ok(result)
+}
+
+fn receipt_reference_count(conn: &Connection, path: &str) -> Result<i64, AppError> {
+  let count = conn.query_row(
+    "SELECT COUNT(*) FROM transactions WHERE receipt_path = ?1",
+    params![path.trim()],
+    |row| row.get(0),
+  )?;
+  Ok(count)
+}
+
+// Receipt files can be shared between bookings, so a file is only removed once no booking points to it.
+fn remove_unreferenced_receipt(conn: &Connection, path: &str) -> Result<bool, AppError> {
+  let path = path.trim();
+  if path.is_empty() || receipt_reference_count(conn, path)? > 0 {
+    return Ok(false);
+  }
+  match fs::remove_file(path) {
+    Ok(()) => Ok(true),
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+    Err(err) => Err(err.into()),
+  }
+}
+
 #[derive(Serialize)]
 pub struct ReadFileResponse {
   pub data_base64: String,
@@ -1169,34 +2818,42 @@ pub struct ReadFileResponse {
 }
 
 #[tauri::command]
-pub fn read_receipt_file(path: String) -> Result<ReadFileResponse, AppError> {
+pub fn read_receipt_file(state: State<AppState>, path: String) -> Result<ReadFileResponse, AppError> {
   let file_path = PathBuf::from(&path);
-  if !file_path.exists() {
+  if !file_path.is_file() {
     return Err(AppError::new("RECEIPT_NOT_FOUND", "Belegdatei nicht gefunden"));
   }
-  let ext = file_path
-    .extension()
-    .and_then(|ext| ext.to_str())
-    .unwrap_or("")
-    .to_lowercase();
-  let content_type = match ext.as_str() {
-    "pdf" => "application/pdf",
-    "png" => "image/png",
-    "jpg" | "jpeg" => "image/jpeg",
-    _ => return Err(AppError::new("RECEIPT_TYPE", "Dateiformat nicht unterstuetzt")),
+  let (settings, booked) = db::with_conn(&state.db, |conn| {
+    Ok((settings::get_settings(conn)?, receipt_reference_count(conn, &path)? > 0))
+  })?;
+  // Receipts already attached to a booking stay viewable after the limits are tightened.
+  let ext = if booked {
+    file_path
+      .extension()
+      .and_then(|ext| ext.to_str())
+      .unwrap_or("")
+      .to_lowercase()
+  } else {
+    receipts::ensure_receipt_allowed(&file_path, settings.receipt_max_bytes, &settings.receipt_allowed_extensions)?
   };
-  let metadata = fs::metadata(&file_path)?;
-  if metadata.len() > OCR_FILE_MAX_BYTES {
-    return Err(AppError::new("RECEIPT_SIZE", "Datei ist zu gross fuer OCR"));
-  }
   let bytes = fs::read(&file_path)?;
   let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
   Ok(ReadFileResponse {
     data_base64: encoded,
-    content_type: content_type.to_string(),
+    content_type: receipts::content_type_for(&ext).to_string(),
   })
 }
 
+#[tauri::command]
+pub fn validate_receipt(state: State<AppState>, path: String) -> Result<ReceiptInfo, AppError> {
+  let settings = db::with_conn(&state.db, |conn| settings::get_settings(conn))?;
+  receipts::validate_receipt(
+    &PathBuf::from(path.trim()),
+    settings.receipt_max_bytes,
+    &settings.receipt_allowed_extensions,
+  )
+}
+
 #[tauri::command]
 pub fn read_text_file(path: String) -> Result<String, AppError> {
   let file_path = PathBuf::from(&path);
@@ -1220,26 +2877,184 @@ pub fn read_text_file(path: String) -> Result<String, AppError> {
 }
 
 #[tauri::command]
-pub fn get_sync_status(state: State<AppState>) -> Result<SyncStatus, AppError> {
-  build_sync_status(&state)
+pub fn get_sync_status(state: State<AppState>) -> Result<SyncStatus, AppError> {
+  build_sync_status(&state)
+}
+
+#[tauri::command]
+pub fn restart_sync_server(app: AppHandle, state: State<AppState>, port: u16) -> Result<SyncStatus, AppError> {
+  sync::restart_sync_server(&app, &state, port)?;
+  db::with_conn(&state.db, |conn| settings::set_sync_port(conn, port))?;
+  build_sync_status(&state)
+}
+
+#[tauri::command]
+pub fn get_sync_changes(
+  state: State<AppState>,
+  device_id: String,
+  limit: Option<i64>,
+) -> Result<Vec<AuditLogEntry>, AppError> {
+  let snapshot = state.sync.snapshot()?;
+  let device = snapshot
+    .paired_devices
+    .into_iter()
+    .find(|device| device.device_id == device_id)
+    .ok_or_else(|| AppError::new("NOT_FOUND", "Geraet nicht gefunden"))?;
+  let limit = limit.unwrap_or(200).clamp(1, 1000);
+  db::with_conn(&state.db, |conn| sync::changes_since(conn, device.last_sync_at.as_deref(), limit))
+}
+
+#[tauri::command]
+pub fn list_sync_log(state: State<AppState>, page: i64, page_size: i64) -> Result<Paginated<SyncLogEntry>, AppError> {
+  let page = if page < 1 { 1 } else { page };
+  let page_size = if page_size < 1 { 50 } else { page_size.min(500) };
+  db::with_conn(&state.db, |conn| sync::list_sync_log(conn, page, page_size))
+}
+
+#[tauri::command]
+pub fn sync_pair_with(
+  state: State<AppState>,
+  peer_ip: String,
+  port: Option<u16>,
+  code: String,
+) -> Result<SyncStatus, AppError> {
+  sync::pair_with_peer(&state, peer_ip.trim(), port, &code)?;
+  build_sync_status(&state)
+}
+
+#[tauri::command]
+pub fn sync_push_to(
+  app: AppHandle,
+  state: State<AppState>,
+  peer_ip: String,
+  port: Option<u16>,
+) -> Result<SyncStatus, AppError> {
+  sync::push_to_peer(&app, &state, peer_ip.trim(), port)?;
+  build_sync_status(&state)
+}
+
+#[tauri::command]
+pub fn resolve_sync_conflict(app: AppHandle, state: State<AppState>, action: String) -> Result<SyncStatus, AppError> {
+  sync::resolve_sync_conflict(&app, &state, &action)?;
+  build_sync_status(&state)
+}
+
+#[tauri::command]
+pub fn get_diagnostics(state: State<AppState>) -> Result<Diagnostics, AppError> {
+  let (schema_version, db_writable, settings) = db::with_conn(&state.db, |conn| {
+    Ok((
+      db::current_schema_version(conn)?,
+      db::is_writable(&state.db, conn),
+      settings::get_settings(conn)?,
+    ))
+  })?;
+  let receipt_folder = if settings.receipt_base_folder.trim().is_empty() {
+    state.receipt_base.clone()
+  } else {
+    PathBuf::from(&settings.receipt_base_folder)
+  };
+
+  Ok(Diagnostics {
+    app_version: env!("CARGO_PKG_VERSION").to_string(),
+    data_dir: state.app_dir.to_string_lossy().to_string(),
+    db_path: state.db.db_path.to_string_lossy().to_string(),
+    schema_version,
+    latest_schema_version: db::latest_schema_version().to_string(),
+    db_writable,
+    sync_active: state.sync.is_active(),
+    sync_port: state.sync.port(),
+    sync_last_error: state.sync.last_error(),
+    receipt_folder_exists: receipt_folder.is_dir(),
+    receipt_folder: receipt_folder.to_string_lossy().to_string(),
+  })
+}
+
+#[tauri::command]
+pub fn get_schema_version(state: State<AppState>) -> Result<String, AppError> {
+  db::with_conn(&state.db, |conn| Ok(db::current_schema_version(conn)?.unwrap_or_default()))
 }
 
 #[tauri::command]
-pub fn resolve_sync_conflict(state: State<AppState>, action: String) -> Result<SyncStatus, AppError> {
-  sync::resolve_sync_conflict(&state, &action)?;
-  build_sync_status(&state)
+pub fn run_pending_migrations(app: AppHandle, state: State<AppState>, actor: Option<String>) -> Result<Vec<String>, AppError> {
+  let applied = db::with_conn(&state.db, |conn| {
+    let applied = db::run_migrations(conn)?;
+    if !applied.is_empty() {
+      let payload_json = serde_json::to_string(&serde_json::json!({
+        "applied": applied,
+      }))
+      .unwrap_or_else(|_| "{}".to_string());
+      append_audit(
+        conn,
+        actor,
+        "RUN_MIGRATIONS",
+        "SYSTEM",
+        None,
+        None,
+        payload_json,
+        Some(format!("{} Migrationen angewendet", applied.len())),
+      )?;
+    }
+    Ok(applied)
+  })?;
+  if !applied.is_empty() {
+    events::emit_data_changed(&app, "ALL", None, None);
+  }
+  Ok(applied)
+}
+
+#[tauri::command]
+pub fn get_storage_stats(state: State<AppState>) -> Result<StorageStats, AppError> {
+  let db_bytes = fs::metadata(&state.db.db_path).map(|meta| meta.len()).unwrap_or(0);
+  let (wal_bytes, transaction_count, per_year, receipt_base) = db::with_conn(&state.db, |conn| {
+    let wal_bytes = db::wal_size(conn)?;
+    let transaction_count: i64 = conn.query_row("SELECT COUNT(*) FROM transactions", [], |row| row.get(0))?;
+    let mut stmt = conn.prepare("SELECT year, COUNT(*) FROM transactions GROUP BY year ORDER BY year")?;
+    let rows = stmt.query_map([], |row| {
+      Ok(YearTransactionCount {
+        year: row.get(0)?,
+        count: row.get(1)?,
+      })
+    })?;
+    let mut per_year = Vec::new();
+    for row in rows {
+      per_year.push(row?);
+    }
+    let settings = settings::get_settings(conn)?;
+    Ok((wal_bytes, transaction_count, per_year, resolve_receipt_base(&settings, &state)))
+  })?;
+
+  let mut receipt_bytes = 0_u64;
+  let mut receipt_files = 0_i64;
+  if receipt_base.exists() {
+    for entry in WalkDir::new(&receipt_base).into_iter().filter_map(Result::ok) {
+      if entry.file_type().is_file() {
+        receipt_files += 1;
+        receipt_bytes += entry.metadata().map(|meta| meta.len()).unwrap_or(0);
+      }
+    }
+  }
+
+  Ok(StorageStats {
+    db_bytes,
+    wal_bytes,
+    receipt_bytes,
+    receipt_files,
+    transaction_count,
+    per_year,
+  })
 }
 
 #[tauri::command]
-pub fn import_twint(state: State<AppState>, request: TwintImportRequest) -> Result<TwintImportSummary, AppError> {
+pub fn import_twint(app: AppHandle, state: State<AppState>, request: TwintImportRequest) -> Result<TwintImportSummary, AppError> {
   if request.rows.is_empty() {
     return Err(AppError::new("IMPORT_EMPTY", "Keine Daten fuer den Import"));
   }
   validation::ensure_mwst_rate(request.income_mwst_rate)?;
   validation::ensure_mwst_rate(request.fee_mwst_rate)?;
   let skip_duplicates = request.skip_duplicates.unwrap_or(true);
+  let book_refunds = request.book_refunds.unwrap_or(false);
 
-  db::with_conn(&state.db, |conn| {
+  let result = db::with_conn(&state.db, |conn| {
     let tx = conn.transaction()?;
     let fee_category_id = ensure_fee_category(&tx, request.fee_mwst_rate)?;
 
@@ -1252,37 +3067,45 @@ pub fn import_twint(state: State<AppState>, request: TwintImportRequest) -> Resu
     let now = Utc::now().to_rfc3339();
 
     let mut income_stmt = tx.prepare(
-      "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at)
-       VALUES (?1, ?2, ?3, ?4, 'INCOME', 'TWINT', NULL, NULL, ?5, ?6, NULL, ?7, NULL, ?8, ?9)",
+      "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at, created_by, updated_by)
+       VALUES (?1, ?2, ?3, ?4, 'INCOME', ?10, NULL, NULL, ?5, ?6, NULL, ?7, NULL, ?8, ?9, ?11, ?11)",
     )?;
     let mut expense_stmt = tx.prepare(
-      "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at)
-       VALUES (?1, ?2, ?3, ?4, 'EXPENSE', NULL, ?5, ?6, ?7, ?8, NULL, ?9, NULL, ?10, ?11)",
+      "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at, created_by, updated_by)
+       VALUES (?1, ?2, ?3, ?4, 'EXPENSE', NULL, ?5, ?6, ?7, ?8, NULL, ?9, NULL, ?10, ?11, ?12, ?12)",
     )?;
 
     let mut closed_months: HashSet<(i32, i32)> = HashSet::new();
     let mut income_created = 0;
     let mut fee_created = 0;
     let mut skipped_duplicates = 0;
+    let mut refunds_skipped = 0;
+    let mut refunds_booked = 0;
 
     for row in request.rows {
       let date = validation::parse_date(&row.date)?;
       let year = date.year();
       let month = date.month() as i32;
 
-      if !closed_months.contains(&(year, month)) && closing::is_month_closed(&tx, year, month)? {
-        return Err(AppError::new("MONTH_CLOSED", "Monat abgeschlossen"));
+      if !closed_months.contains(&(year, month)) {
+        closing::ensure_period_open(&tx, year, month)?;
       }
       closed_months.insert((year, month));
 
-      let amount = row.amount_chf.abs();
-      if amount <= 0.0 {
+      if row.amount_chf == 0.0 {
+        continue;
+      }
+      // Refunds arrive as negative amounts; booked as negative income so turnover is reduced.
+      let is_refund = row.amount_chf < 0.0;
+      if is_refund && !book_refunds {
+        refunds_skipped += 1;
         continue;
       }
-      let note = build_twint_note(row.reference.as_deref(), row.description.as_deref());
+      let amount = row.amount_chf;
+      let note = build_twint_note(row.reference.as_deref(), row.description.as_deref(), is_refund);
 
       if skip_duplicates {
-        if check_duplicate_income(&tx, date, amount, "TWINT", note.as_deref())?.is_some() {
+        if check_duplicate_income(&tx, date, amount, PaymentMethod::Twint.as_str(), note.as_deref())?.is_some() {
           skipped_duplicates += 1;
           continue;
         }
@@ -1300,11 +3123,18 @@ pub fn import_twint(state: State<AppState>, request: TwintImportRequest) -> Resu
         request.income_mwst_rate,
         note.clone(),
         now,
-        now
+        now,
+        PaymentMethod::Twint.as_str(),
+        request.actor.as_deref()
       ])?;
-      income_created += 1;
+      if is_refund {
+        refunds_booked += 1;
+      } else {
+        income_created += 1;
+      }
 
-      if let Some(fee) = row.fee_chf {
+      // A refund row's fee column mirrors the original charge; booking it again would double the fee.
+      if let Some(fee) = row.fee_chf.filter(|_| !is_refund) {
         let fee_amount = fee.abs();
         if fee_amount > 0.0 {
           let fee_desc = build_twint_fee_description(row.reference.as_deref());
@@ -1327,7 +3157,8 @@ pub fn import_twint(state: State<AppState>, request: TwintImportRequest) -> Resu
             request.fee_mwst_rate,
             note.clone(),
             now,
-            now
+            now,
+            request.actor.as_deref()
           ])?;
           fee_created += 1;
         }
@@ -1341,6 +3172,8 @@ pub fn import_twint(state: State<AppState>, request: TwintImportRequest) -> Resu
       "income_created": income_created,
       "fee_created": fee_created,
       "skipped_duplicates": skipped_duplicates,
+      "refunds_skipped": refunds_skipped,
+      "refunds_booked": refunds_booked,
     }))
     .unwrap_or_else(|_| "{}".to_string());
 
@@ -1361,31 +3194,466 @@ pub fn import_twint(state: State<AppState>, request: TwintImportRequest) -> Resu
       income_created,
       fee_created,
       skipped_duplicates,
+      refunds_skipped,
+      refunds_booked,
     })
-  })
+  })?;
+  events::emit_data_changed(&app, "TRANSACTION", None, None);
+  Ok(result)
 }
 
-fn map_transaction_row(row: &rusqlite::Row) -> Result<TransactionListItem, rusqlite::Error> {
-  Ok(TransactionListItem {
-    id: row.get(0)?,
-    public_id: row.get(1)?,
-    date: row.get(2)?,
-    year: row.get(3)?,
-    month: row.get(4)?,
-    tx_type: row.get(5)?,
-    payment_method: row.get(6)?,
-    category_id: row.get(7)?,
-    category_name: row.get(8)?,
-    description: row.get(9)?,
-    amount_chf: row.get(10)?,
-    mwst_rate: row.get(11)?,
-    receipt_path: row.get(12)?,
-    note: row.get(13)?,
-    ref_public_id: row.get(14)?,
-    created_at: row.get(15)?,
-    updated_at: row.get(16)?,
-    is_stornoed: row.get::<_, i64>(17)? == 1,
-  })
+#[tauri::command]
+pub fn parse_bank_csv(request: BankCsvParseRequest) -> Result<Vec<BankImportRow>, AppError> {
+  let content = read_text_file(request.path)?;
+  let columns = bank_csv::BankCsvColumns {
+    date: &request.date_column,
+    amount: &request.amount_column,
+    description: request.description_column.as_deref(),
+  };
+  bank_csv::parse_bank_csv(&content, &columns)
+}
+
+#[tauri::command]
+pub fn import_bank_csv(app: AppHandle, state: State<AppState>, request: BankImportRequest) -> Result<BankImportSummary, AppError> {
+  if request.rows.is_empty() {
+    return Err(AppError::new("IMPORT_EMPTY", "Keine Daten fuer den Import"));
+  }
+  let payment_method = match request.income_payment_method.as_deref() {
+    Some(value) => PaymentMethod::parse(value)?,
+    None => PaymentMethod::Bar,
+  };
+  let income_mwst_rate = request.income_mwst_rate.unwrap_or(0.0);
+  validation::ensure_mwst_rate(income_mwst_rate)?;
+  if let Some(rate) = request.expense_mwst_rate {
+    validation::ensure_mwst_rate(rate)?;
+  }
+  let skip_duplicates = request.skip_duplicates.unwrap_or(true);
+
+  let result = db::with_conn(&state.db, |conn| {
+    let (default_mwst, is_active) = load_category_default_mwst(conn, request.category_id)?;
+    if !is_active {
+      return Err(AppError::new("CATEGORY_INACTIVE", "Kategorie ist deaktiviert"));
+    }
+    let expense_mwst_rate = request.expense_mwst_rate.unwrap_or(default_mwst);
+
+    let tx = conn.transaction()?;
+    let max_id: Option<i64> = tx.query_row(
+      "SELECT MAX(CAST(public_id AS INTEGER)) FROM transactions",
+      [],
+      |row| row.get(0),
+    )?;
+    let mut next_id = max_id.unwrap_or(0) + 1;
+    let now = Utc::now().to_rfc3339();
+
+    let mut income_stmt = tx.prepare(
+      "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at, created_by, updated_by)
+       VALUES (?1, ?2, ?3, ?4, 'INCOME', ?5, NULL, NULL, ?6, ?7, NULL, ?8, NULL, ?9, ?10, ?11, ?11)",
+    )?;
+    let mut expense_stmt = tx.prepare(
+      "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at, created_by, updated_by)
+       VALUES (?1, ?2, ?3, ?4, 'EXPENSE', NULL, ?5, ?6, ?7, ?8, NULL, NULL, NULL, ?9, ?10, ?11, ?11)",
+    )?;
+
+    let mut closed_months: HashSet<(i32, i32)> = HashSet::new();
+    let mut income_created = 0;
+    let mut expense_created = 0;
+    let mut skipped_duplicates = 0;
+
+    for row in &request.rows {
+      let date = validation::parse_date(&row.date)?;
+      let year = date.year();
+      let month = date.month() as i32;
+
+      if !closed_months.contains(&(year, month)) {
+        closing::ensure_period_open(&tx, year, month)?;
+      }
+      closed_months.insert((year, month));
+
+      let amount = row.amount_chf.abs();
+      if amount <= 0.0 {
+        continue;
+      }
+      let description = normalize_import_text(row.description.as_deref());
+      let public_id = format!("{:06}", next_id);
+
+      if row.amount_chf > 0.0 {
+        if skip_duplicates
+          && check_duplicate_income(&tx, date, amount, payment_method.as_str(), description.as_deref())?.is_some()
+        {
+          skipped_duplicates += 1;
+          continue;
+        }
+        income_stmt.execute(params![
+          public_id,
+          row.date,
+          year,
+          month,
+          payment_method.as_str(),
+          amount,
+          income_mwst_rate,
+          description,
+          now,
+          now,
+          request.actor.as_deref()
+        ])?;
+        income_created += 1;
+      } else {
+        if skip_duplicates
+          && check_duplicate_expense(&tx, date, amount, request.category_id, description.as_deref())?.is_some()
+        {
+          skipped_duplicates += 1;
+          continue;
+        }
+        expense_stmt.execute(params![
+          public_id,
+          row.date,
+          year,
+          month,
+          request.category_id,
+          description,
+          amount,
+          expense_mwst_rate,
+          now,
+          now,
+          request.actor.as_deref()
+        ])?;
+        expense_created += 1;
+      }
+      next_id += 1;
+    }
+
+    drop(income_stmt);
+    drop(expense_stmt);
+
+    let payload_json = serde_json::to_string(&serde_json::json!({
+      "category_id": request.category_id,
+      "income_created": income_created,
+      "expense_created": expense_created,
+      "skipped_duplicates": skipped_duplicates,
+    }))
+    .unwrap_or_else(|_| "{}".to_string());
+
+    append_audit(
+      &tx,
+      request.actor.clone(),
+      "IMPORT_BANK",
+      "TRANSACTION",
+      None,
+      None,
+      payload_json,
+      Some("Bank Import".to_string()),
+    )?;
+
+    tx.commit()?;
+
+    Ok(BankImportSummary {
+      income_created,
+      expense_created,
+      skipped_duplicates,
+    })
+  })?;
+  events::emit_data_changed(&app, "TRANSACTION", None, None);
+  Ok(result)
+}
+
+#[tauri::command]
+pub fn import_excel(
+  app: AppHandle,
+  state: State<AppState>,
+  path: String,
+  actor: Option<String>,
+) -> Result<ExcelImportSummary, AppError> {
+  let sheets = excel_import::read_month_sheets(Path::new(&path))?;
+
+  let result = db::with_conn(&state.db, |conn| {
+    let tx = conn.transaction()?;
+    let now = Utc::now().to_rfc3339();
+
+    // Original IDs are kept so storno references in the RefID column stay valid.
+    let mut income_stmt = tx.prepare(
+      "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at, created_by, updated_by)
+       VALUES (?1, ?2, ?3, ?4, 'INCOME', ?5, NULL, NULL, ?6, ?7, NULL, ?8, ?9, ?10, ?11, ?12, ?12)",
+    )?;
+    let mut expense_stmt = tx.prepare(
+      "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at, created_by, updated_by)
+       VALUES (?1, ?2, ?3, ?4, 'EXPENSE', NULL, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?14)",
+    )?;
+
+    let settings = settings::get_settings(&tx)?;
+    let receipt_base = writable_receipt_base(&settings, &state)?;
+    let mut summaries = Vec::new();
+    let mut conflicts = Vec::new();
+    for sheet in &sheets {
+      let mut summary = ExcelImportSheetSummary {
+        sheet: sheet.name.clone(),
+        year: sheet.year,
+        month: sheet.month,
+        imported: 0,
+        skipped: sheet.invalid_rows,
+        closed: false,
+      };
+      if !closing::period_permission(&tx, sheet.year, sheet.month)?.allowed {
+        summary.closed = true;
+        summary.skipped += (sheet.income.len() + sheet.expenses.len()) as i64;
+        summaries.push(summary);
+        continue;
+      }
+
+      for row in &sheet.income {
+        let payment_method = match PaymentMethod::parse(&row.payment_method) {
+          Ok(method) => method,
+          Err(_) => {
+            summary.skipped += 1;
+            continue;
+          }
+        };
+        if row.amount_chf == 0.0 || validation::ensure_mwst_rate(row.mwst_rate).is_err() {
+          summary.skipped += 1;
+          continue;
+        }
+        match import_id_matches(&tx, &row.public_id, "INCOME", row.date, row.amount_chf, None)? {
+          Some(true) => {
+            summary.skipped += 1;
+            continue;
+          }
+          Some(false) => {
+            summary.skipped += 1;
+            conflicts.push(row.public_id.clone());
+            continue;
+          }
+          None => {}
+        }
+        if check_duplicate_income(&tx, row.date, row.amount_chf, payment_method.as_str(), row.note.as_deref())?.is_some() {
+          summary.skipped += 1;
+          continue;
+        }
+        income_stmt.execute(params![
+          row.public_id,
+          row.date.format("%Y-%m-%d").to_string(),
+          sheet.year,
+          sheet.month,
+          payment_method.as_str(),
+          row.amount_chf,
+          row.mwst_rate,
+          row.note,
+          row.ref_public_id,
+          now,
+          now,
+          actor.as_deref()
+        ])?;
+        summary.imported += 1;
+      }
+
+      for row in &sheet.expenses {
+        if row.amount_chf == 0.0 || validation::ensure_mwst_rate(row.mwst_rate).is_err() {
+          summary.skipped += 1;
+          continue;
+        }
+        let category_name = row.category.as_deref().unwrap_or("Unbekannt");
+        match import_id_matches(&tx, &row.public_id, "EXPENSE", row.date, row.amount_chf, Some(category_name))? {
+          Some(true) => {
+            summary.skipped += 1;
+            continue;
+          }
+          Some(false) => {
+            summary.skipped += 1;
+            conflicts.push(row.public_id.clone());
+            continue;
+          }
+          None => {}
+        }
+        let category_id = ensure_import_category(&tx, category_name, row.mwst_rate)?;
+        if check_duplicate_expense(&tx, row.date, row.amount_chf, category_id, row.description.as_deref())?.is_some() {
+          summary.skipped += 1;
+          continue;
+        }
+        // The export folder is often removable, so the receipt is copied into the receipt base.
+        // A file the current receipt limits reject is left off; the booking is still imported.
+        let receipt_path = match row
+          .receipt
+          .as_deref()
+          .and_then(|receipt| excel_import::resolve_receipt(Path::new(&path), row.date, receipt))
+        {
+          Some(source) => {
+            let date_str = row.date.format("%Y-%m-%d").to_string();
+            let target = receipts::ReceiptTarget {
+              year: sheet.year,
+              month: sheet.month,
+              public_id: &row.public_id,
+              date: &date_str,
+              category: Some(category_name),
+            };
+            receipts::copy_receipt(
+              &source.to_string_lossy(),
+              &receipt_base,
+              &target,
+              &settings.receipt_naming_pattern,
+              settings.compress_receipts.then_some(receipts::RECEIPT_MAX_EDGE),
+              settings.receipt_max_bytes,
+              &settings.receipt_allowed_extensions,
+            )
+            .ok()
+          }
+          None => None,
+        };
+        expense_stmt.execute(params![
+          row.public_id,
+          row.date.format("%Y-%m-%d").to_string(),
+          sheet.year,
+          sheet.month,
+          category_id,
+          row.description,
+          row.amount_chf,
+          row.mwst_rate,
+          receipt_path,
+          row.note,
+          row.ref_public_id,
+          now,
+          now,
+          actor.as_deref()
+        ])?;
+        summary.imported += 1;
+      }
+      summaries.push(summary);
+    }
+
+    drop(income_stmt);
+    drop(expense_stmt);
+
+    let imported: i64 = summaries.iter().map(|sheet| sheet.imported).sum();
+    let skipped: i64 = summaries.iter().map(|sheet| sheet.skipped).sum();
+    let payload_json = serde_json::to_string(&serde_json::json!({
+      "path": path,
+      "sheets": summaries.len(),
+      "imported": imported,
+      "skipped": skipped,
+      "conflicts": conflicts,
+    }))
+    .unwrap_or_else(|_| "{}".to_string());
+
+    append_audit(
+      &tx,
+      actor.clone(),
+      "IMPORT_EXCEL",
+      "TRANSACTION",
+      Some(path.clone()),
+      None,
+      payload_json,
+      Some("Excel Import".to_string()),
+    )?;
+
+    tx.commit()?;
+
+    Ok(ExcelImportSummary {
+      imported,
+      skipped,
+      conflicts,
+      sheets: summaries,
+    })
+  })?;
+  events::emit_data_changed(&app, "TRANSACTION", None, None);
+  Ok(result)
+}
+
+fn normalize_category_defaults(
+  description: Option<String>,
+  payment_method: Option<String>,
+) -> Result<(Option<String>, Option<String>), AppError> {
+  let description = description.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+  let payment_method = match payment_method.as_deref().map(str::trim).filter(|value| !value.is_empty()) {
+    Some(value) => Some(PaymentMethod::parse(value)?.as_str().to_string()),
+    None => None,
+  };
+  Ok((description, payment_method))
+}
+
+fn load_category_default_mwst(conn: &Connection, category_id: i64) -> Result<(f64, bool), AppError> {
+  conn
+    .query_row(
+      "SELECT default_mwst_rate, is_active FROM categories WHERE id = ?1",
+      params![category_id],
+      |row| Ok((row.get::<_, f64>(0)?, row.get::<_, i64>(1)? == 1)),
+    )
+    .optional()?
+    .ok_or_else(|| AppError::new("CATEGORY_NOT_FOUND", "Kategorie nicht gefunden"))
+}
+
+fn ensure_category_exists(conn: &Connection, category_id: i64) -> Result<(), AppError> {
+  conn
+    .query_row("SELECT 1 FROM categories WHERE id = ?1", params![category_id], |_| Ok(()))
+    .optional()?
+    .ok_or_else(|| AppError::new("CATEGORY_NOT_FOUND", "Kategorie nicht gefunden"))
+}
+
+fn build_search_clause(query: &str, field: Option<&str>, exact: bool) -> Result<(String, Value), AppError> {
+  if exact && field == Some("amount_chf") {
+    let amount: f64 = query
+      .replace(',', ".")
+      .parse()
+      .map_err(|_| AppError::new("INVALID_AMOUNT", "Betrag ist keine Zahl"))?;
+    return Ok(("t.amount_chf = ?1".to_string(), Value::Real(amount)));
+  }
+
+  let columns = match field {
+    Some(field) => vec![search_column(field)?],
+    None => vec![
+      "t.public_id",
+      "t.description",
+      "t.note",
+      "c.name",
+      "t.date",
+      "t.payment_method",
+      "t.ref_public_id",
+      "CAST(t.amount_chf AS TEXT)",
+      "t.type",
+    ],
+  };
+  let (operator, value) = if exact {
+    ("=", query.to_string())
+  } else {
+    ("LIKE", format!("%{}%", query))
+  };
+  let clause = columns
+    .iter()
+    .map(|column| format!("{column} {operator} ?1"))
+    .collect::<Vec<_>>()
+    .join(" OR ");
+  Ok((format!("({clause})"), Value::Text(value)))
+}
+
+fn search_column(field: &str) -> Result<&'static str, AppError> {
+  match field {
+    "public_id" => Ok("t.public_id"),
+    "description" => Ok("t.description"),
+    "note" => Ok("t.note"),
+    "category" => Ok("c.name"),
+    "date" => Ok("t.date"),
+    "payment_method" => Ok("t.payment_method"),
+    "ref_public_id" => Ok("t.ref_public_id"),
+    "amount_chf" => Ok("CAST(t.amount_chf AS TEXT)"),
+    "type" => Ok("t.type"),
+    _ => Err(AppError::new("INVALID_SEARCH_FIELD", format!("Unbekanntes Suchfeld: {field}"))),
+  }
+}
+
+fn transaction_order_clause(sort_by: Option<&str>, sort_dir: Option<&str>) -> Result<String, AppError> {
+  let dir = match sort_dir.map(|value| value.trim().to_lowercase()).as_deref() {
+    None | Some("") | Some("desc") => "DESC",
+    Some("asc") => "ASC",
+    Some(other) => {
+      return Err(AppError::new("INVALID_SORT", format!("Unbekannte Sortierrichtung: {other}")));
+    }
+  };
+  let order = match sort_by.map(|value| value.trim().to_lowercase()).as_deref() {
+    None | Some("") | Some("date") => format!("t.date {dir}, t.public_id {dir}"),
+    Some("amount") => format!("t.amount_chf {dir}, t.public_id {dir}"),
+    Some("id") => format!("t.public_id {dir}"),
+    Some("category") => format!("c.name {dir}, t.date {dir}, t.public_id {dir}"),
+    Some(other) => {
+      return Err(AppError::new("INVALID_SORT", format!("Unbekanntes Sortierfeld: {other}")));
+    }
+  };
+  Ok(order)
 }
 
 fn next_public_id(conn: &Connection) -> Result<String, AppError> {
@@ -1399,15 +3667,10 @@ fn next_public_id(conn: &Connection) -> Result<String, AppError> {
 }
 
 fn fetch_transaction_by_public_id(conn: &Connection, public_id: &str) -> Result<TransactionListItem, AppError> {
-  let mut stmt = conn.prepare(
-    "SELECT t.id, t.public_id, t.date, t.year, t.month, t.type, t.payment_method, t.category_id,
-            c.name, t.description, t.amount_chf, t.mwst_rate, t.receipt_path, t.note, t.ref_public_id,
-            t.created_at, t.updated_at,
-            EXISTS (SELECT 1 FROM transactions x WHERE x.ref_public_id = t.public_id) as is_stornoed
-     FROM transactions t
-     LEFT JOIN categories c ON c.id = t.category_id
-     WHERE t.public_id = ?1",
-  )?;
+  let mut stmt = conn.prepare(&format!(
+    "{TRANSACTION_SELECT}
+     WHERE t.public_id = ?1"
+  ))?;
   let item = stmt.query_row(params![public_id], |row| map_transaction_row(row))?;
   Ok(item)
 }
@@ -1418,13 +3681,13 @@ fn check_duplicate_income(
   amount: f64,
   payment_method: &str,
   note: Option<&str>,
-) -> Result<Option<String>, AppError> {
+) -> Result<Option<DuplicateMatch>, AppError> {
   let start = date - Duration::days(7);
   let end = date + Duration::days(7);
   let note_value = note.unwrap_or("");
 
   let mut stmt = conn.prepare(
-    "SELECT public_id
+    "SELECT public_id, date, amount_chf, note
      FROM transactions
      WHERE type = 'INCOME'
        AND date BETWEEN ?1 AND ?2
@@ -1435,25 +3698,32 @@ fn check_duplicate_income(
   )?;
   let mut rows = stmt.query(params![start.to_string(), end.to_string(), amount, payment_method, note_value])?;
   if let Some(row) = rows.next()? {
-    Ok(Some(row.get(0)?))
+    Ok(Some(map_duplicate_row(row)?))
   } else {
     Ok(None)
   }
 }
 
+fn normalize_import_text(value: Option<&str>) -> Option<String> {
+  value
+    .map(|text| text.trim())
+    .filter(|text| !text.is_empty())
+    .map(|text| text.to_string())
+}
+
 fn check_duplicate_expense(
   conn: &Connection,
   date: NaiveDate,
   amount: f64,
   category_id: i64,
   description: Option<&str>,
-) -> Result<Option<String>, AppError> {
+) -> Result<Option<DuplicateMatch>, AppError> {
   let start = date - Duration::days(7);
   let end = date + Duration::days(7);
   let description_value = description.unwrap_or("");
 
   let mut stmt = conn.prepare(
-    "SELECT public_id
+    "SELECT public_id, date, amount_chf, note
      FROM transactions
      WHERE type = 'EXPENSE'
        AND date BETWEEN ?1 AND ?2
@@ -1464,12 +3734,32 @@ fn check_duplicate_expense(
   )?;
   let mut rows = stmt.query(params![start.to_string(), end.to_string(), amount, category_id, description_value])?;
   if let Some(row) = rows.next()? {
-    Ok(Some(row.get(0)?))
+    Ok(Some(map_duplicate_row(row)?))
   } else {
     Ok(None)
   }
 }
 
+fn map_duplicate_row(row: &rusqlite::Row) -> Result<DuplicateMatch, rusqlite::Error> {
+  Ok(DuplicateMatch {
+    public_id: row.get(0)?,
+    date: row.get(1)?,
+    amount_chf: row.get(2)?,
+    note: row.get(3)?,
+  })
+}
+
+fn duplicate_warning(dup: DuplicateMatch) -> AppError {
+  AppError::new(
+    "DUPLICATE_WARNING",
+    format!(
+      "Moeglicher Doppel-Eintrag: {:.2} CHF am {} (ID {})",
+      dup.amount_chf, dup.date, dup.public_id
+    ),
+  )
+  .with_details(&dup)
+}
+
 
 fn load_or_seed_categories(conn: &Connection) -> Result<Vec<(i64, f64, String)>, AppError> {
   let mut stmt = conn.prepare(
@@ -1520,7 +3810,7 @@ fn random_amount(rng: &mut MockRng, min: f64, max: f64) -> f64 {
   let range = (max - min).max(1.0);
   let base = min + (rng.next_u32() as f64 % range);
   let cents = (rng.next_u32() % 100) as f64 / 100.0;
-  ((base + cents) * 100.0).round() / 100.0
+  mwst::round2(base + cents)
 }
 
 struct MockRng {
@@ -1552,13 +3842,48 @@ const DEMO_PNG_BYTES: &[u8] = &[
 
 fn resolve_receipt_base(settings: &Settings, state: &AppState) -> PathBuf {
   if settings.receipt_base_folder.trim().is_empty() {
-    return state.receipt_base.clone();
+    state.receipt_base.clone()
+  } else {
+    PathBuf::from(&settings.receipt_base_folder)
+  }
+}
+
+// Write paths probe the folder: a configured folder that cannot be written must
+// surface instead of silently using the app dir. Read-only callers skip the probe.
+fn writable_receipt_base(settings: &Settings, state: &AppState) -> Result<PathBuf, AppError> {
+  let path = resolve_receipt_base(settings, state);
+  if !settings.receipt_base_folder.trim().is_empty() {
+    ensure_receipt_folder_writable(&path)?;
+  }
+  Ok(path)
+}
+
+fn ensure_receipt_folder_writable(path: &Path) -> Result<(), AppError> {
+  let unwritable = || {
+    AppError::new_field(
+      "RECEIPT_FOLDER_UNWRITABLE",
+      "receipt_base_folder",
+      format!("Belegordner ist nicht beschreibbar: {}", path.display()),
+    )
+    .with_details(serde_json::json!({ "path": path.to_string_lossy() }))
+  };
+  fs::create_dir_all(path).map_err(|_| unwritable())?;
+  let probe = path.join(format!(".write_test_{}", std::process::id()));
+  fs::write(&probe, b"").map_err(|_| unwritable())?;
+  let _ = fs::remove_file(&probe);
+  Ok(())
+}
+
+fn resolve_export_base(settings: &Settings, state: &AppState) -> PathBuf {
+  let fallback = state.app_dir.join("Exports");
+  if settings.export_base_folder.trim().is_empty() {
+    return fallback;
   }
-  let path = PathBuf::from(&settings.receipt_base_folder);
+  let path = PathBuf::from(&settings.export_base_folder);
   if path.exists() {
     path
   } else {
-    state.receipt_base.clone()
+    fallback
   }
 }
 
@@ -1570,6 +3895,46 @@ fn ensure_month(month: i32) -> Result<(), AppError> {
   }
 }
 
+fn restore_check_details(check: &db::RestoreCheck) -> String {
+  format!(
+    "Buchungen nach Restore: {} (Schema {})",
+    check.tx_count,
+    check.schema_version.as_deref().unwrap_or("-")
+  )
+}
+
+fn normalize_months(mut months: Vec<i32>) -> Result<Vec<i32>, AppError> {
+  if months.is_empty() {
+    return Err(AppError::new("INVALID_MONTH", "Keine Monate ausgewaehlt"));
+  }
+  for &month in &months {
+    ensure_month(month)?;
+  }
+  months.sort_unstable();
+  months.dedup();
+  Ok(months)
+}
+
+fn normalize_export_years(years: Option<&[i32]>) -> Result<Option<Vec<i32>>, AppError> {
+  let Some(years) = years.filter(|years| !years.is_empty()) else {
+    return Ok(None);
+  };
+  let mut normalized = years.to_vec();
+  normalized.sort_unstable();
+  normalized.dedup();
+  if let Some(invalid) = normalized.iter().find(|year| !(1900..=9999).contains(*year)) {
+    return Err(AppError::new_field("INVALID_YEAR", "years", format!("Jahr ungueltig: {invalid}")));
+  }
+  if normalized.len() > MAX_EXPORT_YEARS {
+    return Err(AppError::new_field(
+      "INVALID_YEAR",
+      "years",
+      format!("Maximal {MAX_EXPORT_YEARS} Jahre pro Export"),
+    ));
+  }
+  Ok(Some(normalized))
+}
+
 fn ensure_month_range(month_from: i32, month_to: i32) -> Result<(), AppError> {
   ensure_month(month_from)?;
   ensure_month(month_to)?;
@@ -1591,9 +3956,66 @@ fn build_sync_status(state: &AppState) -> Result<SyncStatus, AppError> {
     last_change,
     paired_devices: snapshot.paired_devices,
     pending_conflict: snapshot.pending_conflict,
+    last_error: state.sync.last_error(),
   })
 }
 
+fn parse_receipt_public_id(path: &std::path::Path) -> Option<String> {
+  let stem = path.file_stem()?.to_str()?;
+  let digits: String = stem.chars().take_while(|ch| ch.is_ascii_digit()).collect();
+  if digits.is_empty() {
+    return None;
+  }
+  let number: i64 = digits.parse().ok()?;
+  Some(format!("{:06}", number))
+}
+
+fn ensure_import_category(conn: &Connection, name: &str, default_mwst: f64) -> Result<i64, AppError> {
+  let existing: Option<i64> = conn
+    .query_row(
+      "SELECT id FROM categories WHERE name = ?1 COLLATE NOCASE LIMIT 1",
+      params![name],
+      |row| row.get(0),
+    )
+    .optional()?;
+  if let Some(id) = existing {
+    return Ok(id);
+  }
+  conn.execute(
+    "INSERT INTO categories (name, description, default_mwst_rate, is_active) VALUES (?1, ?2, ?3, 1)",
+    params![name, "Aus Excel-Import wiederhergestellt", default_mwst],
+  )?;
+  Ok(conn.last_insert_rowid())
+}
+
+// None if the ID is free; otherwise whether the booking holding it is the same one
+// (date, amount, type and category), so a reissued ID is not mistaken for a duplicate.
+fn import_id_matches(
+  conn: &Connection,
+  public_id: &str,
+  tx_type: &str,
+  date: NaiveDate,
+  amount: f64,
+  category: Option<&str>,
+) -> Result<Option<bool>, AppError> {
+  let existing: Option<(String, String, f64, Option<String>)> = conn
+    .query_row(
+      "SELECT t.date, t.type, t.amount_chf, c.name
+       FROM transactions t
+       LEFT JOIN categories c ON c.id = t.category_id
+       WHERE t.public_id = ?1",
+      params![public_id],
+      |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+    )
+    .optional()?;
+  Ok(existing.map(|(existing_date, existing_type, existing_amount, existing_category)| {
+    existing_date == date.format("%Y-%m-%d").to_string()
+      && existing_type == tx_type
+      && (existing_amount - amount).abs() < 0.005
+      && (tx_type == "INCOME" || existing_category.as_deref() == category)
+  }))
+}
+
 fn ensure_fee_category(conn: &Connection, default_mwst: f64) -> Result<i64, AppError> {
   let mut stmt = conn.prepare("SELECT id FROM categories WHERE name = ?1 LIMIT 1")?;
   let mut rows = stmt.query(params!["TWINT Gebuehren"])?;
@@ -1607,7 +4029,7 @@ fn ensure_fee_category(conn: &Connection, default_mwst: f64) -> Result<i64, AppE
   Ok(conn.last_insert_rowid())
 }
 
-fn build_twint_note(reference: Option<&str>, description: Option<&str>) -> Option<String> {
+fn build_twint_note(reference: Option<&str>, description: Option<&str>, is_refund: bool) -> Option<String> {
   let mut parts: Vec<String> = Vec::new();
   if let Some(value) = reference {
     if !value.trim().is_empty() {
@@ -1619,10 +4041,11 @@ fn build_twint_note(reference: Option<&str>, description: Option<&str>) -> Optio
       parts.push(value.trim().to_string());
     }
   }
+  let label = if is_refund { "TWINT Rueckerstattung" } else { "TWINT Import" };
   if parts.is_empty() {
-    Some("TWINT Import".to_string())
+    Some(label.to_string())
   } else {
-    Some(format!("TWINT Import: {}", parts.join(" | ")))
+    Some(format!("{label}: {}", parts.join(" | ")))
   }
 }
 
@@ -1635,5 +4058,7 @@ fn build_twint_fee_description(reference: Option<&str>) -> String {
   "TWINT Gebuehr".to_string()
 }
 
-const OCR_FILE_MAX_BYTES: u64 = 12 * 1024 * 1024;
+const MAX_EXPORT_YEARS: usize = 5;
+const RECEIPT_MIN_BYTES: u64 = 1024 * 1024;
+const RECEIPT_MAX_BYTES_LIMIT: u64 = 200 * 1024 * 1024;
 const IMPORT_FILE_MAX_BYTES: u64 = 5 * 1024 * 1024;
@@ -40,6 +40,7 @@ export function TwintImportWizard() {
   const [incomeMwstRate, setIncomeMwstRate] = useState("8.1");
   const [feeMwstRate, setFeeMwstRate] = useState("0");
   const [skipDuplicates, setSkipDuplicates] = useState(true);
+  const [bookRefunds, setBookRefunds] = useState(false);
   const [summary, setSummary] = useState<TwintImportSummary | null>(null);
   const [importing, setImporting] = useState(false);
 
@@ -118,6 +119,7 @@ export function TwintImportWizard() {
         income_mwst_rate: incomeRate,
         fee_mwst_rate: feeRate,
         skip_duplicates: skipDuplicates,
+        book_refunds: bookRefunds,
       });
       setSummary(result);
       setStep("done");
@@ -253,6 +255,14 @@ export function TwintImportWizard() {
                 />
                 <span>{t("labels.importSkipDuplicates")}</span>
               </div>
+              <div className="flex items-center gap-2 text-sm">
+                <input
+                  type="checkbox"
+                  checked={bookRefunds}
+                  onChange={(event) => setBookRefunds(event.target.checked)}
+                />
+                <span>{t("labels.importBookRefunds")}</span>
+              </div>
             </div>
             <div className="rounded-xl border border-app-border bg-app-surface px-4 py-3 text-xs text-app-neutral">
               {t("labels.importReady", { count: buildRows().length })}
@@ -284,6 +294,12 @@ export function TwintImportWizard() {
                 <div className="text-xs text-app-neutral">{t("labels.importSkipCount")}</div>
                 <div className="text-base font-semibold text-app-primary">{summary.skipped_duplicates}</div>
               </div>
+              <div className="rounded-xl border border-app-border bg-app-surface px-4 py-3 text-sm">
+                <div className="text-xs text-app-neutral">{t("labels.importRefundCount")}</div>
+                <div className="text-base font-semibold text-app-primary">
+                  {bookRefunds ? summary.refunds_booked : summary.refunds_skipped}
+                </div>
+              </div>
             </div>
             <Button variant="secondary" onClick={() => setStep("select")}>
               {t("labels.importStartOver")}
@@ -4,7 +4,7 @@ use std::sync::Mutex;
 use std::time::Duration;
 
 use chrono::Utc;
-use rusqlite::{params, Connection};
+use rusqlite::{params, params_from_iter, Connection, DatabaseName};
 
 use crate::error::AppError;
 use crate::files::receipts;
@@ -15,6 +15,29 @@ pub struct Db {
   pub db_path: PathBuf,
 }
 
+// Precedence: --data-dir flag > PIZZA_DAMICO_PORTABLE env > portable.flag/data folder > AppData.
+pub fn resolve_app_dir_with_args(args: impl Iterator<Item = String>) -> Result<PathBuf, AppError> {
+  if let Some(dir) = data_dir_from_args(args) {
+    fs::create_dir_all(&dir)?;
+    return Ok(dir);
+  }
+  resolve_app_dir()
+}
+
+fn data_dir_from_args(mut args: impl Iterator<Item = String>) -> Option<PathBuf> {
+  while let Some(arg) = args.next() {
+    if arg == "--data-dir" {
+      return args.next().filter(|value| !value.trim().is_empty()).map(PathBuf::from);
+    }
+    if let Some(value) = arg.strip_prefix("--data-dir=") {
+      if !value.trim().is_empty() {
+        return Some(PathBuf::from(value));
+      }
+    }
+  }
+  None
+}
+
 pub fn resolve_app_dir() -> Result<PathBuf, AppError> {
   if let Some(portable) = resolve_portable_dir()? {
     return Ok(portable);
@@ -35,8 +58,7 @@ pub fn init_db(app_dir: &Path) -> Result<(Db, PathBuf), AppError> {
   run_migrations(&mut conn)?;
 
   let receipt_base = receipts::ensure_receipt_base(app_dir)?;
-  settings::ensure_defaults(&conn, &receipt_base)?;
-  seed_default_categories(&conn)?;
+  seed_defaults(&conn, &receipt_base)?;
 
   Ok((
     Db {
@@ -52,6 +74,25 @@ pub fn with_conn<T>(db: &Db, f: impl FnOnce(&mut Connection) -> Result<T, AppErr
   f(&mut guard)
 }
 
+const BUSY_RETRIES: u32 = 3;
+const BUSY_BACKOFF_MS: u64 = 200;
+
+pub fn with_conn_retry<T>(
+  db: &Db,
+  mut f: impl FnMut(&mut Connection) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+  let mut attempt = 0;
+  loop {
+    match with_conn(db, &mut f) {
+      Err(err) if err.code == "DB_BUSY" && attempt < BUSY_RETRIES => {
+        attempt += 1;
+        std::thread::sleep(Duration::from_millis(BUSY_BACKOFF_MS << (attempt - 1)));
+      }
+      result => return result,
+    }
+  }
+}
+
 pub fn reload_connection(db: &Db) -> Result<(), AppError> {
   let mut guard = db.conn.lock()?;
   let conn = Connection::open(&db.db_path)?;
@@ -66,56 +107,190 @@ pub fn checkpoint(conn: &Connection) -> Result<(), AppError> {
   Ok(())
 }
 
-fn run_migrations(conn: &mut Connection) -> Result<(), AppError> {
+pub fn wal_size(conn: &Connection) -> Result<u64, AppError> {
+  let wal_path = match conn.path() {
+    Some(path) if !path.is_empty() => PathBuf::from(format!("{path}-wal")),
+    _ => return Ok(0),
+  };
+  match fs::metadata(&wal_path) {
+    Ok(metadata) => Ok(metadata.len()),
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(0),
+    Err(err) => Err(err.into()),
+  }
+}
+
+pub fn ensure_wal_drained(conn: &Connection) -> Result<(), AppError> {
+  let busy: i64 = conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| row.get(0))?;
+  let remaining = wal_size(conn)?;
+  if busy != 0 || remaining > 0 {
+    return Err(AppError::new(
+      "DB_BUSY_WAL",
+      format!("Datenbank ist beschaeftigt, WAL konnte nicht geleert werden ({remaining} Bytes offen)"),
+    ));
+  }
+  Ok(())
+}
+
+const MIGRATIONS: &[(&str, &str)] = &[
+  ("001_init", include_str!("../migrations/001_init.sql")),
+  ("002_year_closing", include_str!("../migrations/002_year_closing.sql")),
+  ("003_sync_log", include_str!("../migrations/003_sync_log.sql")),
+  ("004_transaction_actors", include_str!("../migrations/004_transaction_actors.sql")),
+  ("005_receipt_not_required", include_str!("../migrations/005_receipt_not_required.sql")),
+  ("006_month_closing_comment", include_str!("../migrations/006_month_closing_comment.sql")),
+  ("007_transaction_is_demo", include_str!("../migrations/007_transaction_is_demo.sql")),
+  ("008_category_defaults", include_str!("../migrations/008_category_defaults.sql")),
+  ("009_export_log", include_str!("../migrations/009_export_log.sql")),
+];
+
+pub struct RestoreCheck {
+  pub tx_count: i64,
+  pub schema_version: Option<String>,
+}
+
+pub fn latest_schema_version() -> &'static str {
+  MIGRATIONS.last().map(|(version, _)| *version).unwrap_or_default()
+}
+
+pub fn is_known_schema_version(version: &str) -> bool {
+  MIGRATIONS.iter().any(|(known, _)| *known == version)
+}
+
+pub fn current_schema_version(conn: &Connection) -> Result<Option<String>, AppError> {
+  let version = conn.query_row("SELECT MAX(version) FROM schema_migrations", [], |row| row.get(0))?;
+  Ok(version)
+}
+
+pub fn is_writable(db: &Db, conn: &Connection) -> bool {
+  let file_writable = fs::metadata(&db.db_path)
+    .map(|meta| !meta.permissions().readonly())
+    .unwrap_or(false);
+  file_writable && !conn.is_readonly(DatabaseName::Main).unwrap_or(true)
+}
+
+pub fn run_migrations(conn: &mut Connection) -> Result<Vec<String>, AppError> {
   conn.execute_batch(
     "CREATE TABLE IF NOT EXISTS schema_migrations (version TEXT PRIMARY KEY, applied_at TEXT NOT NULL)",
   )?;
 
-  apply_migration(conn, "001_init", include_str!("../migrations/001_init.sql"))?;
-  Ok(())
+  let mut applied = Vec::new();
+  for (version, sql) in MIGRATIONS {
+    if apply_migration(conn, version, sql)? {
+      applied.push(version.to_string());
+    }
+  }
+  Ok(applied)
+}
+
+pub fn verify_restored_db(conn: &mut Connection) -> Result<RestoreCheck, AppError> {
+  let incompatible = |err: AppError| {
+    AppError::new(
+      "RESTORE_INCOMPATIBLE",
+      format!("Wiederhergestellte Datenbank ist nicht kompatibel: {}", err.message),
+    )
+  };
+
+  run_migrations(conn).map_err(incompatible)?;
+
+  let mut stmt = conn
+    .prepare("SELECT version FROM schema_migrations ORDER BY version")
+    .map_err(|err| incompatible(err.into()))?;
+  let versions: Vec<String> = stmt
+    .query_map([], |row| row.get(0))
+    .and_then(|rows| rows.collect())
+    .map_err(|err| incompatible(err.into()))?;
+  drop(stmt);
+  if let Some(unknown) = versions
+    .iter()
+    .find(|version| !is_known_schema_version(version))
+  {
+    return Err(AppError::new(
+      "RESTORE_INCOMPATIBLE",
+      format!("Backup stammt aus einer neueren Version (Schema {unknown})"),
+    ));
+  }
+
+  let tx_count: i64 = conn
+    .query_row("SELECT COUNT(*) FROM transactions", [], |row| row.get(0))
+    .map_err(|err| incompatible(err.into()))?;
+
+  Ok(RestoreCheck {
+    tx_count,
+    schema_version: versions.last().cloned(),
+  })
 }
 
-fn apply_migration(conn: &mut Connection, version: &str, sql: &str) -> Result<(), AppError> {
+fn apply_migration(conn: &mut Connection, version: &str, sql: &str) -> Result<bool, AppError> {
   let exists: i64 = conn.query_row(
     "SELECT COUNT(*) FROM schema_migrations WHERE version = ?1",
     params![version],
     |row| row.get(0),
   )?;
   if exists > 0 {
-    return Ok(());
+    return Ok(false);
   }
 
-  conn.execute_batch(sql)?;
-  conn.execute(
+  // Schema change and version row commit together, so a failing migration leaves no partial state.
+  let tx = conn.transaction()?;
+  tx.execute_batch(sql)?;
+  tx.execute(
     "INSERT INTO schema_migrations (version, applied_at) VALUES (?1, ?2)",
     params![version, Utc::now().to_rfc3339()],
   )?;
-  Ok(())
+  tx.commit()?;
+  Ok(true)
 }
 
+pub fn seed_defaults(conn: &Connection, receipt_base: &Path) -> Result<(), AppError> {
+  settings::ensure_defaults(conn, receipt_base)?;
+  seed_default_categories(conn)
+}
+
+const DEFAULT_CATEGORIES: [(&str, &str, f64); 7] = [
+  ("Lebensmittel", "Einkauf Zutaten", 2.6),
+  ("Verpackung", "Boxen, Becher, Besteck", 8.1),
+  ("Standplatz", "Miete, Gebuehren", 8.1),
+  ("Fahrzeug", "Wartung, Treibstoff", 8.1),
+  ("Marketing", "Werbung, Aktionen", 8.1),
+  ("Versicherung", "Versicherungen", 8.1),
+  ("Diverses", "Sonstiges", 8.1),
+];
+
 fn seed_default_categories(conn: &Connection) -> Result<(), AppError> {
   let count: i64 = conn.query_row("SELECT COUNT(*) FROM categories", [], |row| row.get(0))?;
   if count > 0 {
     return Ok(());
   }
 
-  let defaults = vec![
-    ("Lebensmittel", "Einkauf Zutaten", 2.6),
-    ("Verpackung", "Boxen, Becher, Besteck", 8.1),
-    ("Standplatz", "Miete, Gebuehren", 8.1),
-    ("Fahrzeug", "Wartung, Treibstoff", 8.1),
-    ("Marketing", "Werbung, Aktionen", 8.1),
-    ("Versicherung", "Versicherungen", 8.1),
-    ("Diverses", "Sonstiges", 8.1),
-  ];
-
-  for (name, description, rate) in defaults {
+  for (name, description, rate) in DEFAULT_CATEGORIES {
     conn.execute(
       "INSERT INTO categories (name, description, default_mwst_rate, is_active) VALUES (?1, ?2, ?3, 1)",
       params![name, description, rate],
     )?;
   }
 
+  Ok(())
+}
+
+// Used by factory_reset: user categories are dropped, the shipped ones are put back to their defaults.
+pub fn reset_default_categories(conn: &Connection) -> Result<(), AppError> {
+  let placeholders = vec!["?"; DEFAULT_CATEGORIES.len()].join(", ");
+  conn.execute(
+    &format!("DELETE FROM categories WHERE name NOT IN ({placeholders})"),
+    params_from_iter(DEFAULT_CATEGORIES.iter().map(|(name, _, _)| *name)),
+  )?;
+  for (name, description, rate) in DEFAULT_CATEGORIES {
+    conn.execute(
+      "INSERT INTO categories (name, description, default_mwst_rate, is_active) VALUES (?1, ?2, ?3, 1)
+       ON CONFLICT(name) DO UPDATE SET
+         description = excluded.description,
+         default_mwst_rate = excluded.default_mwst_rate,
+         is_active = 1,
+         default_description = NULL,
+         default_payment_method = NULL",
+      params![name, description, rate],
+    )?;
+  }
   Ok(())
 }
 
@@ -143,3 +318,127 @@ fn resolve_portable_dir() -> Result<Option<PathBuf>, AppError> {
 
   Ok(None)
 }
+
+#[cfg(test)]
+pub(crate) fn open_test_db() -> Connection {
+  let mut conn = Connection::open_in_memory().unwrap();
+  conn.execute_batch("PRAGMA foreign_keys = ON;").unwrap();
+  run_migrations(&mut conn).unwrap();
+  seed_defaults(&conn, Path::new("Belege")).unwrap();
+  conn
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+      "pizza_damico_{name}_{}",
+      Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  #[test]
+  fn ensure_wal_drained_truncates_wal() {
+    let dir = temp_dir("wal");
+    let conn = Connection::open(dir.join("test.sqlite")).unwrap();
+    conn.execute_batch("PRAGMA journal_mode = WAL; CREATE TABLE t (v INTEGER); INSERT INTO t VALUES (1);")
+      .unwrap();
+    assert!(wal_size(&conn).unwrap() > 0);
+
+    ensure_wal_drained(&conn).unwrap();
+    assert_eq!(wal_size(&conn).unwrap(), 0);
+
+    drop(conn);
+    let _ = fs::remove_dir_all(dir);
+  }
+
+  #[test]
+  fn apply_migration_twice_is_a_no_op() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    run_migrations(&mut conn).unwrap();
+    let (version, sql) = MIGRATIONS[0];
+
+    assert!(!apply_migration(&mut conn, version, sql).unwrap());
+    assert!(run_migrations(&mut conn).unwrap().is_empty());
+    let count: i64 = conn
+      .query_row("SELECT COUNT(*) FROM schema_migrations WHERE version = ?1", params![version], |row| row.get(0))
+      .unwrap();
+    assert_eq!(count, 1);
+  }
+
+  #[test]
+  fn failing_migration_rolls_back() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    run_migrations(&mut conn).unwrap();
+
+    let result = apply_migration(
+      &mut conn,
+      "999_broken",
+      "CREATE TABLE half_done (id INTEGER); INSERT INTO missing_table VALUES (1);",
+    );
+    assert!(result.is_err());
+    let tables: i64 = conn
+      .query_row("SELECT COUNT(*) FROM sqlite_master WHERE name = 'half_done'", [], |row| row.get(0))
+      .unwrap();
+    assert_eq!(tables, 0);
+    assert_eq!(current_schema_version(&conn).unwrap().as_deref(), Some(latest_schema_version()));
+  }
+
+  #[test]
+  fn verify_restored_db_upgrades_older_schema() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    conn
+      .execute_batch("CREATE TABLE schema_migrations (version TEXT PRIMARY KEY, applied_at TEXT NOT NULL)")
+      .unwrap();
+    for (version, sql) in &MIGRATIONS[..3] {
+      apply_migration(&mut conn, version, sql).unwrap();
+    }
+    assert_eq!(current_schema_version(&conn).unwrap().as_deref(), Some("003_sync_log"));
+
+    let check = verify_restored_db(&mut conn).unwrap();
+    assert_eq!(check.schema_version.as_deref(), Some(latest_schema_version()));
+    assert_eq!(check.tx_count, 0);
+  }
+
+  #[test]
+  fn verify_restored_db_rejects_newer_schema() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    run_migrations(&mut conn).unwrap();
+    conn
+      .execute(
+        "INSERT INTO schema_migrations (version, applied_at) VALUES ('999_future', ?1)",
+        params![Utc::now().to_rfc3339()],
+      )
+      .unwrap();
+
+    let err = verify_restored_db(&mut conn).err().unwrap();
+    assert_eq!(err.code, "RESTORE_INCOMPATIBLE");
+  }
+
+  #[test]
+  fn reset_default_categories_drops_user_categories() {
+    let conn = open_test_db();
+    conn
+      .execute_batch(
+        "INSERT INTO categories (name, default_mwst_rate) VALUES ('Eigene', 8.1);
+         UPDATE categories SET default_mwst_rate = 0, is_active = 0 WHERE name = 'Lebensmittel';",
+      )
+      .unwrap();
+
+    reset_default_categories(&conn).unwrap();
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM categories", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, DEFAULT_CATEGORIES.len() as i64);
+    let (rate, active): (f64, i64) = conn
+      .query_row(
+        "SELECT default_mwst_rate, is_active FROM categories WHERE name = 'Lebensmittel'",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+      )
+      .unwrap();
+    assert_eq!((rate, active), (2.6, 1));
+  }
+}
@@ -1,7 +1,7 @@
 ﻿use rusqlite::{params, Connection};
 
 use crate::error::AppError;
-use crate::models::MonthStatus;
+use crate::models::{EditPermission, MonthStatus};
 
 pub fn is_month_closed(conn: &Connection, year: i32, month: i32) -> Result<bool, AppError> {
   let mut stmt = conn.prepare(
@@ -16,9 +16,45 @@ pub fn is_month_closed(conn: &Connection, year: i32, month: i32) -> Result<bool,
   }
 }
 
+pub fn is_year_closed(conn: &Connection, year: i32) -> Result<bool, AppError> {
+  let mut stmt = conn.prepare("SELECT is_closed FROM year_closing WHERE year = ?1 LIMIT 1")?;
+  let mut rows = stmt.query(params![year])?;
+  if let Some(row) = rows.next()? {
+    let is_closed: i64 = row.get(0)?;
+    Ok(is_closed == 1)
+  } else {
+    Ok(false)
+  }
+}
+
+pub fn period_permission(conn: &Connection, year: i32, month: i32) -> Result<EditPermission, AppError> {
+  let reason = if is_year_closed(conn, year)? {
+    "YEAR_CLOSED"
+  } else if is_month_closed(conn, year, month)? {
+    "MONTH_CLOSED"
+  } else {
+    "OK"
+  };
+  Ok(EditPermission {
+    year,
+    month,
+    allowed: reason == "OK",
+    reason: reason.to_string(),
+  })
+}
+
+pub fn ensure_period_open(conn: &Connection, year: i32, month: i32) -> Result<(), AppError> {
+  match period_permission(conn, year, month)?.reason.as_str() {
+    "YEAR_CLOSED" => Err(AppError::new("YEAR_CLOSED", "Jahr abgeschlossen")),
+    "MONTH_CLOSED" => Err(AppError::new("MONTH_CLOSED", "Monat abgeschlossen")),
+    _ => Ok(()),
+  }
+}
+
 pub fn get_month_status(conn: &Connection, year: i32, month: i32) -> Result<MonthStatus, AppError> {
+  let year_closed = is_year_closed(conn, year)?;
   let mut stmt = conn.prepare(
-    "SELECT is_closed, closed_at, closed_by FROM month_closing WHERE year = ?1 AND month = ?2 LIMIT 1",
+    "SELECT is_closed, closed_at, closed_by, comment FROM month_closing WHERE year = ?1 AND month = ?2 LIMIT 1",
   )?;
   let mut rows = stmt.query(params![year, month])?;
   if let Some(row) = rows.next()? {
@@ -29,6 +65,8 @@ pub fn get_month_status(conn: &Connection, year: i32, month: i32) -> Result<Mont
       is_closed: is_closed == 1,
       closed_at: row.get(1)?,
       closed_by: row.get(2)?,
+      comment: row.get(3)?,
+      year_closed,
     })
   } else {
     Ok(MonthStatus {
@@ -37,6 +75,8 @@ pub fn get_month_status(conn: &Connection, year: i32, month: i32) -> Result<Mont
       is_closed: false,
       closed_at: None,
       closed_by: None,
+      comment: None,
+      year_closed,
     })
   }
 }
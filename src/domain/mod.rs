@@ -1,3 +1,4 @@
 ﻿pub mod closing;
 pub mod mwst;
+pub mod time;
 pub mod validation;
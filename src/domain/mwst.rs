@@ -1,23 +1,113 @@
 ﻿pub fn mwst_from_brutto(brutto: f64, rate: f64) -> f64 {
-  if rate <= 0.0 {
+  if !brutto.is_finite() || !rate.is_finite() || rate <= 0.0 {
     0.0
   } else {
     brutto * (rate / (100.0 + rate))
   }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MwstMode {
+  Effektiv,
+  Saldo,
+}
+
+impl MwstMode {
+  pub const ALL: [&'static str; 2] = ["EFFEKTIV", "SALDO"];
+
+  pub fn parse(value: &str) -> Option<Self> {
+    match value.trim().to_uppercase().as_str() {
+      "EFFEKTIV" => Some(Self::Effektiv),
+      "SALDO" => Some(Self::Saldo),
+      _ => None,
+    }
+  }
+
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Self::Effektiv => "EFFEKTIV",
+      Self::Saldo => "SALDO",
+    }
+  }
+}
+
+// PER_LINE sums the unrounded VAT of every booking, AGGREGATE applies each
+// rate once to the summed turnover and rounds to the rappen. The two can
+// differ by a few rappen over many bookings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VatRoundingMode {
+  PerLine,
+  Aggregate,
+}
+
+impl VatRoundingMode {
+  pub const ALL: [&'static str; 2] = ["PER_LINE", "AGGREGATE"];
+
+  pub fn parse(value: &str) -> Option<Self> {
+    match value.trim().to_uppercase().as_str() {
+      "PER_LINE" => Some(Self::PerLine),
+      "AGGREGATE" => Some(Self::Aggregate),
+      _ => None,
+    }
+  }
+
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Self::PerLine => "PER_LINE",
+      Self::Aggregate => "AGGREGATE",
+    }
+  }
+}
+
+pub fn mwst_from_aggregate(turnover: f64, rate: f64) -> f64 {
+  round2(mwst_from_brutto(turnover, rate))
+}
+
+// Half-up to the rappen. The nudge compensates binary representation, so
+// 2.675 (stored as 2.67499999...) becomes 2.68 instead of 2.67.
+pub fn round2(value: f64) -> f64 {
+  let scaled = finite_or_zero(value) * 100.0;
+  let nudge = scaled.signum() * scaled.abs().max(1.0) * f64::EPSILON * 8.0;
+  (scaled + nudge).round() / 100.0
+}
+
 pub fn effective_due(mwst_income: f64, mwst_expense: f64) -> f64 {
-  mwst_income - mwst_expense
+  finite_or_zero(mwst_income) - finite_or_zero(mwst_expense)
 }
 
 pub fn saldo_due(income_total: f64, saldo_rate: f64) -> f64 {
-  income_total * (saldo_rate / 100.0)
+  if !saldo_rate.is_finite() || saldo_rate <= 0.0 {
+    0.0
+  } else {
+    finite_or_zero(income_total) * (saldo_rate / 100.0)
+  }
 }
 
 pub fn safe_margin(result: f64, income_total: f64) -> f64 {
-  if income_total.abs() < f64::EPSILON {
+  if !result.is_finite() || !income_total.is_finite() || income_total.abs() < f64::EPSILON {
     0.0
   } else {
     result / income_total
   }
 }
+
+pub fn finite_or_zero(value: f64) -> f64 {
+  if value.is_finite() {
+    value
+  } else {
+    0.0
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn round2_rounds_half_up_despite_binary_representation() {
+    assert_eq!(round2(2.675), 2.68);
+    assert_eq!(round2(1.005), 1.01);
+    assert_eq!(round2(-2.675), -2.68);
+    assert_eq!(round2(f64::NAN), 0.0);
+  }
+}
@@ -0,0 +1,61 @@
+﻿use chrono::{DateTime, FixedOffset, Local, Utc};
+
+pub const DEFAULT_TIMEZONE: &str = "LOCAL";
+const DISPLAY_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// Without a tz database only the system zone, UTC or a fixed offset can be offered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timezone {
+  Local,
+  Utc,
+  Fixed(FixedOffset),
+}
+
+impl Timezone {
+  pub fn parse(value: &str) -> Option<Self> {
+    let value = value.trim();
+    if value.is_empty() || value.eq_ignore_ascii_case("LOCAL") {
+      return Some(Self::Local);
+    }
+    if value.eq_ignore_ascii_case("UTC") || value == "Z" {
+      return Some(Self::Utc);
+    }
+    parse_offset(value).map(Self::Fixed)
+  }
+
+  pub fn to_setting(&self) -> String {
+    match self {
+      Self::Local => DEFAULT_TIMEZONE.to_string(),
+      Self::Utc => "UTC".to_string(),
+      Self::Fixed(offset) => offset.to_string(),
+    }
+  }
+}
+
+fn parse_offset(value: &str) -> Option<FixedOffset> {
+  let (sign, rest) = match value.as_bytes().first()? {
+    b'+' => (1, &value[1..]),
+    b'-' => (-1, &value[1..]),
+    _ => return None,
+  };
+  let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+  let hours: i32 = hours.parse().ok()?;
+  let minutes: i32 = minutes.parse().ok()?;
+  if !(0..=14).contains(&hours) || !(0..60).contains(&minutes) {
+    return None;
+  }
+  FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+// Stored timestamps stay UTC RFC3339; this is only for display.
+pub fn format_local_ts(ts: &str, timezone: Timezone) -> String {
+  let Ok(parsed) = DateTime::parse_from_rfc3339(ts) else {
+    return ts.to_string();
+  };
+  let utc = parsed.with_timezone(&Utc);
+  match timezone {
+    Timezone::Local => utc.with_timezone(&Local).format(DISPLAY_FORMAT).to_string(),
+    Timezone::Utc => utc.format(DISPLAY_FORMAT).to_string(),
+    Timezone::Fixed(offset) => utc.with_timezone(&offset).format(DISPLAY_FORMAT).to_string(),
+  }
+}
@@ -4,12 +4,12 @@ use crate::error::AppError;
 
 pub fn parse_date(date: &str) -> Result<NaiveDate, AppError> {
   NaiveDate::parse_from_str(date, "%Y-%m-%d")
-    .map_err(|_| AppError::new("INVALID_DATE", "Datum muss YYYY-MM-DD sein"))
+    .map_err(|_| AppError::new_field("INVALID_DATE", "date", "Datum muss YYYY-MM-DD sein"))
 }
 
 pub fn ensure_amount_positive(amount: f64) -> Result<(), AppError> {
   if amount <= 0.0 {
-    Err(AppError::new("INVALID_AMOUNT", "Betrag muss > 0 sein"))
+    Err(AppError::new_field("INVALID_AMOUNT", "amount_chf", "Betrag muss > 0 sein"))
   } else {
     Ok(())
   }
@@ -17,8 +17,41 @@ pub fn ensure_amount_positive(amount: f64) -> Result<(), AppError> {
 
 pub fn ensure_mwst_rate(rate: f64) -> Result<(), AppError> {
   if !(0.0..100.0).contains(&rate) {
-    Err(AppError::new("INVALID_MWST", "MWST Satz muss zwischen 0 und 100 liegen"))
+    Err(AppError::new_field("INVALID_MWST", "mwst_rate", "MWST Satz muss zwischen 0 und 100 liegen"))
   } else {
     Ok(())
   }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentMethod {
+  Bar,
+  Twint,
+}
+
+impl PaymentMethod {
+  pub const ALL: [PaymentMethod; 2] = [PaymentMethod::Bar, PaymentMethod::Twint];
+
+  pub fn parse(value: &str) -> Result<Self, AppError> {
+    let normalized = value.trim().to_uppercase();
+    Self::ALL
+      .into_iter()
+      .find(|method| method.as_str() == normalized)
+      .ok_or_else(|| {
+        let allowed: Vec<&str> = Self::ALL.iter().map(|method| method.as_str()).collect();
+        AppError::new_field(
+          "INVALID_PAYMENT",
+          "payment_method",
+          format!("Zahlungsart muss {} sein", allowed.join(" oder ")),
+        )
+        .with_details(serde_json::json!({ "allowed": allowed }))
+      })
+  }
+
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      PaymentMethod::Bar => "BAR",
+      PaymentMethod::Twint => "TWINT",
+    }
+  }
+}
@@ -5,6 +5,10 @@ use rust_xlsxwriter::XlsxError;
 pub struct AppError {
   pub code: String,
   pub message: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub details: Option<serde_json::Value>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub field: Option<String>,
 }
 
 impl AppError {
@@ -12,8 +16,22 @@ impl AppError {
     Self {
       code: code.to_string(),
       message: message.into(),
+      details: None,
+      field: None,
     }
   }
+
+  pub fn new_field(code: &str, field: &str, message: impl Into<String>) -> Self {
+    Self {
+      field: Some(field.to_string()),
+      ..Self::new(code, message)
+    }
+  }
+
+  pub fn with_details(mut self, details: impl Serialize) -> Self {
+    self.details = serde_json::to_value(details).ok();
+    self
+  }
 }
 
 impl std::fmt::Display for AppError {
@@ -26,7 +44,14 @@ impl std::error::Error for AppError {}
 
 impl From<rusqlite::Error> for AppError {
   fn from(err: rusqlite::Error) -> Self {
-    AppError::new("DB_ERROR", err.to_string())
+    match err.sqlite_error_code() {
+      Some(rusqlite::ErrorCode::DatabaseBusy) | Some(rusqlite::ErrorCode::DatabaseLocked) => AppError::new(
+        "DB_BUSY",
+        "Datenbank ist gerade beschaeftigt, bitte erneut versuchen",
+      )
+      .with_details(serde_json::json!({ "cause": err.to_string() })),
+      _ => AppError::new("DB_ERROR", err.to_string()),
+    }
   }
 }
 
@@ -0,0 +1,16 @@
+use tauri::{AppHandle, Emitter};
+
+use crate::models::DataChanged;
+
+pub const DATA_CHANGED_EVENT: &str = "data://changed";
+
+pub fn emit_data_changed(handle: &AppHandle, entity: &str, year: Option<i32>, month: Option<i32>) {
+  let _ = handle.emit(
+    DATA_CHANGED_EVENT,
+    DataChanged {
+      entity: entity.to_string(),
+      year,
+      month,
+    },
+  );
+}
@@ -0,0 +1,176 @@
+﻿use chrono::NaiveDate;
+
+use crate::error::AppError;
+use crate::models::BankImportRow;
+
+// Turns a bank account export into rows for import_bank_csv. Only the mapped
+// columns are read; the delimiter is taken from the header line since most
+// Swiss banks export with ';' and others with ','.
+
+pub struct BankCsvColumns<'a> {
+  pub date: &'a str,
+  pub amount: &'a str,
+  pub description: Option<&'a str>,
+}
+
+const DATE_FORMATS: [&str; 4] = ["%Y-%m-%d", "%d.%m.%Y", "%d.%m.%y", "%d/%m/%Y"];
+
+pub fn parse_bank_csv(content: &str, columns: &BankCsvColumns) -> Result<Vec<BankImportRow>, AppError> {
+  let content = content.trim_start_matches('\u{feff}');
+  let mut records = split_records(content, detect_delimiter(content)).into_iter();
+  let header = records
+    .next()
+    .ok_or_else(|| AppError::new("IMPORT_EMPTY", "Keine Daten fuer den Import"))?;
+  let date_index = column_index(&header, columns.date)?;
+  let amount_index = column_index(&header, columns.amount)?;
+  let description_index = columns
+    .description
+    .map(|name| column_index(&header, name))
+    .transpose()?;
+
+  let mut rows = Vec::new();
+  for (index, record) in records.enumerate() {
+    if record.iter().all(|value| value.trim().is_empty()) {
+      continue;
+    }
+    let line = index + 2;
+    let field = |column: usize| record.get(column).map(|value| value.trim()).unwrap_or("");
+    let date = parse_bank_date(field(date_index)).ok_or_else(|| row_error(line, "Datum", field(date_index)))?;
+    let amount_chf = parse_bank_amount(field(amount_index)).ok_or_else(|| row_error(line, "Betrag", field(amount_index)))?;
+    let description = description_index
+      .map(field)
+      .filter(|value| !value.is_empty())
+      .map(str::to_string);
+    rows.push(BankImportRow {
+      date: date.format("%Y-%m-%d").to_string(),
+      amount_chf,
+      description,
+    });
+  }
+
+  if rows.is_empty() {
+    return Err(AppError::new("IMPORT_EMPTY", "Keine Daten fuer den Import"));
+  }
+  Ok(rows)
+}
+
+fn detect_delimiter(content: &str) -> char {
+  let header = content.lines().next().unwrap_or("");
+  [',', '\t', ';']
+    .into_iter()
+    .max_by_key(|delimiter| header.matches(*delimiter).count())
+    .unwrap_or(';')
+}
+
+fn split_records(content: &str, delimiter: char) -> Vec<Vec<String>> {
+  let mut records = Vec::new();
+  let mut record = Vec::new();
+  let mut field = String::new();
+  let mut in_quotes = false;
+  let mut chars = content.chars().peekable();
+  while let Some(ch) = chars.next() {
+    match ch {
+      '"' if in_quotes && chars.peek() == Some(&'"') => {
+        field.push('"');
+        chars.next();
+      }
+      '"' => in_quotes = !in_quotes,
+      '\r' if !in_quotes => {}
+      '\n' if !in_quotes => {
+        record.push(std::mem::take(&mut field));
+        records.push(std::mem::take(&mut record));
+      }
+      ch if ch == delimiter && !in_quotes => record.push(std::mem::take(&mut field)),
+      ch => field.push(ch),
+    }
+  }
+  if !field.is_empty() || !record.is_empty() {
+    record.push(field);
+    records.push(record);
+  }
+  records
+}
+
+fn column_index(header: &[String], name: &str) -> Result<usize, AppError> {
+  header
+    .iter()
+    .position(|column| column.trim().eq_ignore_ascii_case(name.trim()))
+    .ok_or_else(|| AppError::new_field("CSV_COLUMN_MISSING", "column", format!("Spalte '{name}' fehlt in der CSV Datei")))
+}
+
+fn parse_bank_date(value: &str) -> Option<NaiveDate> {
+  // Some banks append a time ("01.05.2024 00:00"); only the date part matters.
+  let value = value.split_whitespace().next()?;
+  DATE_FORMATS
+    .iter()
+    .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+}
+
+// Accepts Swiss ("1'234.50"), German ("1.234,50") and plain ("-1234.5") notation.
+fn parse_bank_amount(value: &str) -> Option<f64> {
+  let cleaned: String = value
+    .trim()
+    .trim_start_matches("CHF")
+    .trim_end_matches("CHF")
+    .chars()
+    .filter(|ch| !matches!(ch, ' ' | '\'' | '\u{2019}' | '\u{a0}'))
+    .collect();
+  let decimal_comma = match (cleaned.rfind(','), cleaned.rfind('.')) {
+    (Some(comma), Some(dot)) => comma > dot,
+    (Some(_), None) => true,
+    _ => false,
+  };
+  let normalized = if decimal_comma {
+    cleaned.replace('.', "").replace(',', ".")
+  } else {
+    cleaned.replace(',', "")
+  };
+  normalized.parse::<f64>().ok().filter(|amount| amount.is_finite())
+}
+
+fn row_error(line: usize, field: &str, value: &str) -> AppError {
+  AppError::new("CSV_ROW_INVALID", format!("Zeile {line}: {field} '{value}' ist ungueltig"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const COLUMNS: BankCsvColumns<'static> = BankCsvColumns {
+    date: "Buchungsdatum",
+    amount: "Betrag",
+    description: Some("Buchungstext"),
+  };
+
+  #[test]
+  fn parses_semicolon_export_with_swiss_amounts() {
+    let content = "\u{feff}Buchungsdatum;Buchungstext;Betrag\r\n\
+      01.05.2024;\"Metro; Einkauf\";-1'234.50\r\n\
+      02.05.2024;Gutschrift;250\r\n\
+      \r\n";
+    let rows = parse_bank_csv(content, &COLUMNS).unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].date, "2024-05-01");
+    assert_eq!(rows[0].amount_chf, -1234.5);
+    assert_eq!(rows[0].description.as_deref(), Some("Metro; Einkauf"));
+    assert_eq!(rows[1].amount_chf, 250.0);
+  }
+
+  #[test]
+  fn rejects_missing_column_and_bad_amount() {
+    let err = parse_bank_csv("Datum,Betrag\n2024-05-01,10\n", &COLUMNS).err().unwrap();
+    assert_eq!(err.code, "CSV_COLUMN_MISSING");
+
+    let err = parse_bank_csv("Buchungsdatum,Betrag,Buchungstext\n2024-05-01,abc,x\n", &COLUMNS)
+      .err()
+      .unwrap();
+    assert_eq!(err.code, "CSV_ROW_INVALID");
+  }
+
+  #[test]
+  fn amount_notations() {
+    assert_eq!(parse_bank_amount("1.234,50"), Some(1234.5));
+    assert_eq!(parse_bank_amount("-12,30"), Some(-12.3));
+    assert_eq!(parse_bank_amount("CHF 1'000.00"), Some(1000.0));
+  }
+}
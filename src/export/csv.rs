@@ -1,16 +1,28 @@
 ﻿use std::fs::File;
-use std::io::Write;
+use std::io::{BufWriter, Write};
 use std::path::Path;
 
 use rusqlite::{params, Connection};
 
+use crate::domain::mwst;
 use crate::error::AppError;
+use crate::export::write_atomic;
 
-pub fn export_year_csv(conn: &Connection, year: i32, path: &Path) -> Result<(), AppError> {
-  let mut file = File::create(path)?;
+const MONTH_NAMES: [&str; 12] = [
+  "Januar", "Februar", "Maerz", "April", "Mai", "Juni", "Juli", "August", "September", "Oktober", "November", "Dezember",
+];
+
+pub fn export_year_csv(conn: &Connection, year: i32, path: &Path, with_subtotals: bool) -> Result<(), AppError> {
+  write_atomic(path, |temp_path| write_year_csv(conn, year, temp_path, with_subtotals))
+}
+
+fn write_year_csv(conn: &Connection, year: i32, path: &Path, with_subtotals: bool) -> Result<(), AppError> {
+  let mut file = BufWriter::new(File::create(path)?);
+  // The subtotal layout adds a VAT amount column; the flat layout stays unchanged for re-import tools.
   writeln!(
     file,
-    "public_id,date,year,month,type,payment_method,category,description,amount_chf,mwst_rate,receipt_path,note,ref_public_id"
+    "public_id,date,year,month,type,payment_method,category,description,amount_chf,mwst_rate,receipt_path,note,ref_public_id{}",
+    if with_subtotals { ",mwst_chf" } else { "" }
   )?;
 
   let mut stmt = conn.prepare(
@@ -39,9 +51,23 @@ pub fn export_year_csv(conn: &Connection, year: i32, path: &Path) -> Result<(),
     ))
   })?;
 
+  let mut current_month: Option<i32> = None;
+  let mut month_total = Subtotal::default();
+  let mut year_total = Subtotal::default();
+
   for row in rows {
     let (public_id, date, year, month, tx_type, payment_method, category, description, amount, mwst_rate, receipt_path, note, ref_public_id) = row?;
-    writeln!(
+    if with_subtotals {
+      if let Some(previous) = current_month.filter(|previous| *previous != month) {
+        write_subtotal_rows(&mut file, &month_label(previous), &month_total)?;
+        month_total = Subtotal::default();
+      }
+      current_month = Some(month);
+    }
+    let mwst_chf = mwst::round2(mwst::mwst_from_brutto(amount, mwst_rate));
+    month_total.add(&tx_type, amount, mwst_chf);
+    year_total.add(&tx_type, amount, mwst_chf);
+    write!(
       file,
       "{},{},{},{},{},{},{},{},{},{},{},{},{}",
       escape_csv(&public_id),
@@ -58,11 +84,128 @@ pub fn export_year_csv(conn: &Connection, year: i32, path: &Path) -> Result<(),
       escape_csv(note.as_deref().unwrap_or("")),
       escape_csv(ref_public_id.as_deref().unwrap_or(""))
     )?;
+    if with_subtotals {
+      write!(file, ",{}", mwst_chf)?;
+    }
+    writeln!(file)?;
   }
 
+  if with_subtotals {
+    if let Some(previous) = current_month {
+      write_subtotal_rows(&mut file, &month_label(previous), &month_total)?;
+    }
+    write_subtotal_rows(&mut file, &format!("Gesamt {year}"), &year_total)?;
+  }
+
+  file.flush()?;
+  let file = file.into_inner().map_err(|err| err.into_error())?;
+  file.sync_all()?;
   Ok(())
 }
 
+fn month_label(month: i32) -> String {
+  let name = MONTH_NAMES.get((month - 1) as usize).copied().unwrap_or("Monat");
+  format!("Summe {name}")
+}
+
+// Income and expenses are summed separately; adding them together would mean nothing.
+// Corrections are left out, matching the dashboard KPIs.
+#[derive(Default)]
+struct Subtotal {
+  income: f64,
+  income_mwst: f64,
+  expense: f64,
+  expense_mwst: f64,
+}
+
+impl Subtotal {
+  fn add(&mut self, tx_type: &str, amount: f64, mwst_chf: f64) {
+    match tx_type {
+      "INCOME" => {
+        self.income += amount;
+        self.income_mwst += mwst_chf;
+      }
+      "EXPENSE" => {
+        self.expense += amount;
+        self.expense_mwst += mwst_chf;
+      }
+      _ => {}
+    }
+  }
+}
+
+// The label is always quoted so parsers that skip summary rows can recognise them.
+fn write_subtotal_rows<W: Write>(file: &mut W, label: &str, subtotal: &Subtotal) -> Result<(), AppError> {
+  writeln!(file)?;
+  for (kind, amount, mwst_chf) in [
+    ("Einnahmen", subtotal.income, subtotal.income_mwst),
+    ("Ausgaben", subtotal.expense, subtotal.expense_mwst),
+  ] {
+    writeln!(
+      file,
+      "\"{} {}\",,,,,,,,{},,,,,{}",
+      label.replace('"', "\"\""),
+      kind,
+      mwst::round2(amount),
+      mwst::round2(mwst_chf)
+    )?;
+  }
+  Ok(())
+}
+
+pub fn export_audit_log(conn: &Connection, path: &Path) -> Result<i64, AppError> {
+  let mut written = 0_i64;
+  write_atomic(path, |temp_path| {
+    written = write_audit_log_csv(conn, temp_path)?;
+    Ok(())
+  })?;
+  Ok(written)
+}
+
+// Rows are written straight from the statement iterator so large logs never sit in memory.
+fn write_audit_log_csv(conn: &Connection, path: &Path) -> Result<i64, AppError> {
+  let mut file = BufWriter::new(File::create(path)?);
+  writeln!(file, "id,ts,actor,action,entity_type,entity_id,ref_id,payload_json,details")?;
+
+  let mut stmt = conn.prepare(
+    "SELECT id, ts, actor, action, entity_type, entity_id, ref_id, payload_json, details
+     FROM audit_log
+     ORDER BY id",
+  )?;
+  let mut rows = stmt.query([])?;
+  let mut written = 0_i64;
+  while let Some(row) = rows.next()? {
+    let id: i64 = row.get(0)?;
+    let ts: String = row.get(1)?;
+    let actor: Option<String> = row.get(2)?;
+    let action: String = row.get(3)?;
+    let entity_type: String = row.get(4)?;
+    let entity_id: Option<String> = row.get(5)?;
+    let ref_id: Option<String> = row.get(6)?;
+    let payload_json: String = row.get(7)?;
+    let details: Option<String> = row.get(8)?;
+    writeln!(
+      file,
+      "{},{},{},{},{},{},{},{},{}",
+      id,
+      escape_csv(&ts),
+      escape_csv(actor.as_deref().unwrap_or("")),
+      escape_csv(&action),
+      escape_csv(&entity_type),
+      escape_csv(entity_id.as_deref().unwrap_or("")),
+      escape_csv(ref_id.as_deref().unwrap_or("")),
+      escape_csv(&payload_json),
+      escape_csv(details.as_deref().unwrap_or(""))
+    )?;
+    written += 1;
+  }
+
+  file.flush()?;
+  let file = file.into_inner().map_err(|err| err.into_error())?;
+  file.sync_all()?;
+  Ok(written)
+}
+
 fn escape_csv(value: &str) -> String {
   if value.contains(',') || value.contains('"') || value.contains('\n') {
     format!("\"{}\"", value.replace('"', "\"\""))
@@ -70,3 +213,23 @@ fn escape_csv(value: &str) -> String {
     value.to_string()
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn subtotals_keep_income_and_expenses_apart() {
+    let mut subtotal = Subtotal::default();
+    subtotal.add("INCOME", 108.1, 8.1);
+    subtotal.add("EXPENSE", 54.05, 4.05);
+    subtotal.add("INCOME", -8.1, -0.61);
+
+    let mut out = Vec::new();
+    write_subtotal_rows(&mut out, "Summe Mai", &subtotal).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[1], "\"Summe Mai Einnahmen\",,,,,,,,100,,,,,7.49");
+    assert_eq!(lines[2], "\"Summe Mai Ausgaben\",,,,,,,,54.05,,,,,4.05");
+  }
+}
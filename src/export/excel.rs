@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -8,10 +8,16 @@ use rust_xlsxwriter::{Color, ExcelDateTime, Format, FormatAlign, Url, Workbook,
 
 use crate::domain::mwst;
 use crate::error::AppError;
-use crate::models::YearKpis;
+use crate::export::{unique_receipt_path, write_atomic};
+use crate::models::{Settings, YearKpis};
 use crate::reports;
+use crate::settings;
 
-const EXPORT_RECEIPTS_DIR: &str = "Belege";
+pub(crate) const EXPORT_RECEIPTS_DIR: &str = "Belege";
+const MWST_FORM_RATES: [f64; 4] = [0.0, 2.6, 3.8, 8.1];
+pub(crate) const MONTH_SHEET_NAMES: [&str; 12] = [
+  "JAN", "FEB", "MAR", "APR", "MAI", "JUN", "JUL", "AUG", "SEP", "OKT", "NOV", "DEZ",
+];
 
 struct ReceiptExport {
   receipts_dir: PathBuf,
@@ -61,32 +67,8 @@ impl ReceiptExport {
     );
     let relative = relative.replace('\\', "/");
     self.copied.insert(cache_key, relative.clone());
-    Ok(Some((format!("file:///{}", relative), file_name.to_string())))
-  }
-}
-
-fn unique_receipt_path(base_dir: &Path, file_name: &str) -> PathBuf {
-  let mut candidate = base_dir.join(file_name);
-  if !candidate.exists() {
-    return candidate;
-  }
-  let stem = Path::new(file_name)
-    .file_stem()
-    .and_then(|value| value.to_str())
-    .unwrap_or("beleg");
-  let ext = Path::new(file_name).extension().and_then(|value| value.to_str()).unwrap_or("");
-  let mut counter = 1;
-  loop {
-    let next_name = if ext.is_empty() {
-      format!("{stem}_{counter}")
-    } else {
-      format!("{stem}_{counter}.{ext}")
-    };
-    candidate = base_dir.join(next_name);
-    if !candidate.exists() {
-      return candidate;
-    }
-    counter += 1;
+    let display = candidate.file_name().and_then(|name| name.to_str()).unwrap_or(file_name).to_string();
+    Ok(Some((format!("file:///{}", relative), display)))
   }
 }
 
@@ -100,13 +82,28 @@ pub fn export_year(conn: &Connection, year: i32, path: &Path, receipts_dir: Opti
   };
 
   for month in 1..=12 {
-    write_month_sheet(&mut workbook, conn, year, month, receipt_export.as_mut())?;
+    write_month_sheet(&mut workbook, conn, year, month, receipt_export.as_mut(), false)?;
   }
 
-  workbook
-    .save(path)
-    .map_err(|err| AppError::new("EXPORT", err.to_string()))?;
-  Ok(())
+  save_workbook(&mut workbook, path)
+}
+
+pub fn export_years(conn: &Connection, years: &[i32], path: &Path, receipts_dir: Option<&Path>) -> Result<(), AppError> {
+  let mut workbook = Workbook::new();
+  write_years_sheet(&mut workbook, conn, years)?;
+  let mut receipt_export = if let Some(dir) = receipts_dir {
+    Some(ReceiptExport::new(dir.to_path_buf())?)
+  } else {
+    None
+  };
+
+  for year in years {
+    for month in 1..=12 {
+      write_month_sheet(&mut workbook, conn, *year, month, receipt_export.as_mut(), true)?;
+    }
+  }
+
+  save_workbook(&mut workbook, path)
 }
 
 pub fn export_month(
@@ -122,11 +119,8 @@ pub fn export_month(
   } else {
     None
   };
-  write_month_sheet(&mut workbook, conn, year, month, receipt_export.as_mut())?;
-  workbook
-    .save(path)
-    .map_err(|err| AppError::new("EXPORT", err.to_string()))?;
-  Ok(())
+  write_month_sheet(&mut workbook, conn, year, month, receipt_export.as_mut(), false)?;
+  save_workbook(&mut workbook, path)
 }
 
 pub fn export_range(
@@ -146,32 +140,108 @@ pub fn export_range(
   };
 
   for month in month_from..=month_to {
-    write_month_sheet(&mut workbook, conn, year, month, receipt_export.as_mut())?;
+    write_month_sheet(&mut workbook, conn, year, month, receipt_export.as_mut(), false)?;
   }
 
-  workbook
-    .save(path)
+  save_workbook(&mut workbook, path)
+}
+
+fn save_workbook(workbook: &mut Workbook, path: &Path) -> Result<(), AppError> {
+  write_atomic(path, |temp_path| {
+    workbook
+      .save(temp_path)
+      .map_err(|err| AppError::new("EXPORT", err.to_string()))
+  })
+}
+
+fn write_years_sheet(workbook: &mut Workbook, conn: &Connection, years: &[i32]) -> Result<(), AppError> {
+  let settings = settings::get_settings(conn)?;
+  let mut columns = Vec::new();
+  let mut methods = BTreeSet::new();
+  for year in years {
+    let kpis = reports::kpis_from_base(reports::get_year_base_kpis(conn, *year)?, &settings);
+    let split: HashMap<String, f64> = reports::get_payment_split(conn, *year, None)?
+      .into_iter()
+      .map(|split| (split.payment_method, split.amount))
+      .collect();
+    methods.extend(split.keys().cloned());
+    columns.push((*year, kpis, split));
+  }
+
+  let sheet = workbook.add_worksheet();
+  sheet
+    .set_name("VERGLEICH")
     .map_err(|err| AppError::new("EXPORT", err.to_string()))?;
+
+  let header = Format::new()
+    .set_bold()
+    .set_font_color(Color::White)
+    .set_background_color(Color::RGB(0x1A2433));
+  let label = Format::new().set_bold();
+  let money = money_format(&settings.currency_code);
+  let percent = Format::new().set_num_format("0.00%");
+
+  let last_col = columns.len().max(1) as u16;
+  let title = match (years.first(), years.last()) {
+    (Some(first), Some(last)) if first != last => format!("Jahresvergleich {first}-{last}"),
+    (Some(first), _) => format!("Jahresvergleich {first}"),
+    _ => "Jahresvergleich".to_string(),
+  };
+  sheet.merge_range(0, 0, 0, last_col.max(3), &title, &header)?;
+
+  sheet.write_string_with_format(2, 0, "Kennzahl", &label)?;
+  for (idx, (year, _, _)) in columns.iter().enumerate() {
+    sheet.write_string_with_format(2, idx as u16 + 1, &year.to_string(), &label)?;
+  }
+
+  // Same layout as the JAHR sheet: split rows for every method used in any of the years.
+  let mut rows: Vec<(String, Vec<f64>)> = vec![(
+    "Einnahmen Total".to_string(),
+    columns.iter().map(|(_, kpis, _)| kpis.income_total).collect(),
+  )];
+  for method in &methods {
+    rows.push((
+      format!("Einnahmen {method}"),
+      columns.iter().map(|(_, _, split)| split.get(method).copied().unwrap_or(0.0)).collect(),
+    ));
+  }
+  let kpi_rows: [(&str, fn(&YearKpis) -> f64); 7] = [
+    ("Ausgaben Total", |kpis| kpis.expense_total),
+    ("Ergebnis", |kpis| kpis.result),
+    ("Marge", |kpis| kpis.margin),
+    ("MWST Einnahmen", |kpis| kpis.mwst_income),
+    ("MWST Ausgaben", |kpis| kpis.mwst_expense),
+    ("MWST Zahllast", |kpis| kpis.mwst_due),
+    ("Missing Receipts Summe", |kpis| kpis.missing_receipts_sum),
+  ];
+  for (label_text, value_of) in kpi_rows {
+    rows.push((label_text.to_string(), columns.iter().map(|(_, kpis, _)| value_of(kpis)).collect()));
+  }
+
+  let mut row = 3;
+  for (label_text, values) in rows {
+    sheet.write_string_with_format(row, 0, &label_text, &label)?;
+    for (idx, value) in values.into_iter().enumerate() {
+      let col = idx as u16 + 1;
+      if label_text == "Marge" {
+        sheet.write_number_with_format(row, col, value, &percent)?;
+      } else {
+        sheet.write_number_with_format(row, col, value, &money)?;
+      }
+    }
+    row += 1;
+  }
+
+  sheet.set_column_width(0, 28)?;
+  for idx in 0..columns.len() {
+    sheet.set_column_width(idx as u16 + 1, 18)?;
+  }
   Ok(())
 }
 
 fn write_year_sheet(workbook: &mut Workbook, conn: &Connection, year: i32) -> Result<(), AppError> {
-  let base = reports::get_year_base_kpis(conn, year)?;
-  let result = base.income_total - base.expense_total;
-  let margin = mwst::safe_margin(result, base.income_total);
-  let kpis = YearKpis {
-    income_total: base.income_total,
-    income_bar: base.income_bar,
-    income_twint: base.income_twint,
-    expense_total: base.expense_total,
-    result,
-    margin,
-    mwst_income: base.mwst_income,
-    mwst_expense: base.mwst_expense,
-    mwst_due: base.mwst_income - base.mwst_expense,
-    missing_receipts_count: base.missing_receipts_count,
-    missing_receipts_sum: base.missing_receipts_sum,
-  };
+  let settings = settings::get_settings(conn)?;
+  let rows = year_summary_rows(conn, year, &settings)?;
 
   let sheet = workbook.add_worksheet();
   sheet
@@ -183,27 +253,14 @@ fn write_year_sheet(workbook: &mut Workbook, conn: &Connection, year: i32) -> Re
     .set_font_color(Color::White)
     .set_background_color(Color::RGB(0x1A2433));
   let label = Format::new().set_bold();
-  let money = Format::new().set_num_format("[$CHF] #,##0.00");
+  let money = money_format(&settings.currency_code);
   let percent = Format::new().set_num_format("0.00%");
 
   sheet.merge_range(0, 0, 0, 3, &format!("Jahresuebersicht {year}"), &header)?;
 
-  let rows = vec![
-    ("Einnahmen Total", kpis.income_total),
-    ("Einnahmen BAR", kpis.income_bar),
-    ("Einnahmen TWINT", kpis.income_twint),
-    ("Ausgaben Total", kpis.expense_total),
-    ("Ergebnis", kpis.result),
-    ("Marge", kpis.margin),
-    ("MWST Einnahmen", kpis.mwst_income),
-    ("MWST Ausgaben", kpis.mwst_expense),
-    ("MWST Zahllast", kpis.mwst_due),
-    ("Missing Receipts Summe", kpis.missing_receipts_sum),
-  ];
-
   let mut row = 2;
   for (label_text, value) in rows {
-    sheet.write_string_with_format(row, 0, label_text, &label)?;
+    sheet.write_string_with_format(row, 0, &label_text, &label)?;
     if label_text == "Marge" {
       sheet.write_number_with_format(row, 1, value, &percent)?;
     } else {
@@ -217,6 +274,32 @@ fn write_year_sheet(workbook: &mut Workbook, conn: &Connection, year: i32) -> Re
   Ok(())
 }
 
+fn year_summary_rows(conn: &Connection, year: i32, settings: &Settings) -> Result<Vec<(String, f64)>, AppError> {
+  let kpis = reports::kpis_from_base(reports::get_year_base_kpis(conn, year)?, settings);
+
+  // "Einnahmen Total" stays the first row; one split row per payment method
+  // follows it, so new methods show up without shifting the total.
+  let mut rows = vec![("Einnahmen Total".to_string(), kpis.income_total)];
+  rows.extend(
+    reports::get_payment_split(conn, year, None)?
+      .into_iter()
+      .map(|split| (format!("Einnahmen {}", split.payment_method), split.amount)),
+  );
+  rows.extend(
+    [
+      ("Ausgaben Total", kpis.expense_total),
+      ("Ergebnis", kpis.result),
+      ("Marge", kpis.margin),
+      ("MWST Einnahmen", kpis.mwst_income),
+      ("MWST Ausgaben", kpis.mwst_expense),
+      ("MWST Zahllast", kpis.mwst_due),
+      ("Missing Receipts Summe", kpis.missing_receipts_sum),
+    ]
+    .map(|(label_text, value)| (label_text.to_string(), value)),
+  );
+  Ok(rows)
+}
+
 fn write_range_sheet(
   workbook: &mut Workbook,
   conn: &Connection,
@@ -224,22 +307,8 @@ fn write_range_sheet(
   month_from: i32,
   month_to: i32,
 ) -> Result<(), AppError> {
-  let base = reports::get_range_base_kpis(conn, year, month_from, month_to)?;
-  let result = base.income_total - base.expense_total;
-  let margin = mwst::safe_margin(result, base.income_total);
-  let kpis = YearKpis {
-    income_total: base.income_total,
-    income_bar: base.income_bar,
-    income_twint: base.income_twint,
-    expense_total: base.expense_total,
-    result,
-    margin,
-    mwst_income: base.mwst_income,
-    mwst_expense: base.mwst_expense,
-    mwst_due: base.mwst_income - base.mwst_expense,
-    missing_receipts_count: base.missing_receipts_count,
-    missing_receipts_sum: base.missing_receipts_sum,
-  };
+  let settings = settings::get_settings(conn)?;
+  let kpis = reports::kpis_from_base(reports::get_range_base_kpis(conn, year, month_from, month_to)?, &settings);
 
   let sheet = workbook.add_worksheet();
   sheet
@@ -251,7 +320,7 @@ fn write_range_sheet(
     .set_font_color(Color::White)
     .set_background_color(Color::RGB(0x1A2433));
   let label = Format::new().set_bold();
-  let money = Format::new().set_num_format("[$CHF] #,##0.00");
+  let money = money_format(&settings.currency_code);
   let percent = Format::new().set_num_format("0.00%");
 
   sheet.merge_range(
@@ -298,26 +367,18 @@ fn write_month_sheet(
   year: i32,
   month: i32,
   mut receipt_export: Option<&mut ReceiptExport>,
+  prefix_year: bool,
 ) -> Result<(), AppError> {
-  let month_name = match month {
-    1 => "JAN",
-    2 => "FEB",
-    3 => "MAR",
-    4 => "APR",
-    5 => "MAI",
-    6 => "JUN",
-    7 => "JUL",
-    8 => "AUG",
-    9 => "SEP",
-    10 => "OKT",
-    11 => "NOV",
-    12 => "DEZ",
-    _ => "MON",
-  };
+  let month_name = MONTH_SHEET_NAMES.get((month - 1) as usize).copied().unwrap_or("MON");
 
+  let sheet_name = if prefix_year {
+    format!("{year} {month_name}")
+  } else {
+    month_name.to_string()
+  };
   let mut sheet = workbook.add_worksheet();
   sheet
-    .set_name(month_name)
+    .set_name(&sheet_name)
     .map_err(|err| AppError::new("EXPORT", err.to_string()))?;
 
   let header = Format::new()
@@ -325,7 +386,7 @@ fn write_month_sheet(
     .set_background_color(Color::RGB(0xE2E8F0))
     .set_align(FormatAlign::Center);
   let title = Format::new().set_bold().set_font_size(14.0);
-  let money = Format::new().set_num_format("[$CHF] #,##0.00");
+  let money = money_format(&settings::get_currency_code(conn)?);
   let percent = Format::new().set_num_format("0.0\"%\"");
   let date_format = Format::new().set_num_format("dd.mm.yyyy");
 
@@ -339,6 +400,7 @@ fn write_month_sheet(
     "MWST %",
     "MWST CHF",
     "Notiz",
+    "RefID",
   ];
   for (idx, label) in income_headers.iter().enumerate() {
     sheet.write_string_with_format(2, idx as u16, *label, &header)?;
@@ -346,7 +408,7 @@ fn write_month_sheet(
 
   let mut row = 3;
   let mut stmt = conn.prepare(
-    "SELECT public_id, date, payment_method, amount_chf, mwst_rate, note
+    "SELECT public_id, date, payment_method, amount_chf, mwst_rate, note, ref_public_id
      FROM transactions
      WHERE year = ?1 AND month = ?2 AND type = 'INCOME'
      ORDER BY date, public_id",
@@ -359,11 +421,12 @@ fn write_month_sheet(
       row.get::<_, f64>(3)?,
       row.get::<_, f64>(4)?,
       row.get::<_, Option<String>>(5)?,
+      row.get::<_, Option<String>>(6)?,
     ))
   })?;
 
   for item in income_iter {
-    let (public_id, date, payment_method, amount, mwst_rate, note) = item?;
+    let (public_id, date, payment_method, amount, mwst_rate, note, ref_id) = item?;
     sheet.write_string(row, 0, &public_id)?;
     write_date(&mut sheet, row, 1, &date, &date_format)?;
     sheet.write_string(row, 2, payment_method.as_deref().unwrap_or(""))?;
@@ -372,6 +435,7 @@ fn write_month_sheet(
     let mwst_chf = mwst::mwst_from_brutto(amount, mwst_rate);
     sheet.write_number_with_format(row, 5, mwst_chf, &money)?;
     sheet.write_string(row, 6, note.as_deref().unwrap_or(""))?;
+    sheet.write_string(row, 7, ref_id.as_deref().unwrap_or(""))?;
     row += 1;
   }
 
@@ -447,6 +511,8 @@ fn write_month_sheet(
     row += 1;
   }
 
+  write_mwst_by_rate(&mut sheet, conn, year, month, row + 1)?;
+
   sheet.set_column_width(0, 12)?;
   sheet.set_column_width(1, 12)?;
   sheet.set_column_width(2, 18)?;
@@ -465,6 +531,86 @@ fn write_month_sheet(
   Ok(())
 }
 
+fn write_mwst_by_rate(
+  sheet: &mut Worksheet,
+  conn: &Connection,
+  year: i32,
+  month: i32,
+  start_row: u32,
+) -> Result<(), AppError> {
+  let header = Format::new()
+    .set_bold()
+    .set_background_color(Color::RGB(0xE2E8F0))
+    .set_align(FormatAlign::Center);
+  let title = Format::new().set_bold().set_font_size(14.0);
+  let money = money_format(&settings::get_currency_code(conn)?);
+  let percent = Format::new().set_num_format("0.0\"%\"");
+
+  let mut totals: BTreeMap<i64, (f64, f64, f64, f64)> = MWST_FORM_RATES
+    .iter()
+    .map(|rate| (rate_key(*rate), (0.0, 0.0, 0.0, 0.0)))
+    .collect();
+
+  let mut stmt = conn.prepare(
+    "SELECT type, mwst_rate, amount_chf
+     FROM transactions
+     WHERE year = ?1 AND month = ?2",
+  )?;
+  let rows = stmt.query_map(params![year, month], |row| {
+    Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?, row.get::<_, f64>(2)?))
+  })?;
+  for item in rows {
+    let (tx_type, mwst_rate, amount) = item?;
+    let entry = totals.entry(rate_key(mwst_rate)).or_insert((0.0, 0.0, 0.0, 0.0));
+    let mwst_chf = mwst::mwst_from_brutto(amount, mwst_rate);
+    if tx_type == "INCOME" {
+      entry.0 += amount;
+      entry.1 += mwst_chf;
+    } else {
+      entry.2 += amount;
+      entry.3 += mwst_chf;
+    }
+  }
+  if settings::get_vat_rounding_mode(conn)? == mwst::VatRoundingMode::Aggregate {
+    for (key, entry) in totals.iter_mut() {
+      let rate = *key as f64 / 10.0;
+      entry.1 = mwst::mwst_from_aggregate(entry.0, rate);
+      entry.3 = mwst::mwst_from_aggregate(entry.2, rate);
+    }
+  }
+
+  sheet.write_string_with_format(start_row, 0, "MWST nach Satz", &title)?;
+  let headers = [
+    "MWST %",
+    "Umsatz Einnahmen",
+    "MWST Einnahmen",
+    "Aufwand Ausgaben",
+    "MWST Ausgaben",
+  ];
+  for (idx, label) in headers.iter().enumerate() {
+    sheet.write_string_with_format(start_row + 1, idx as u16, *label, &header)?;
+  }
+
+  let mut row = start_row + 2;
+  for (key, (income, income_mwst, expense, expense_mwst)) in totals {
+    sheet.write_number_with_format(row, 0, key as f64 / 10.0, &percent)?;
+    sheet.write_number_with_format(row, 1, income, &money)?;
+    sheet.write_number_with_format(row, 2, income_mwst, &money)?;
+    sheet.write_number_with_format(row, 3, expense, &money)?;
+    sheet.write_number_with_format(row, 4, expense_mwst, &money)?;
+    row += 1;
+  }
+  Ok(())
+}
+
+fn money_format(currency_code: &str) -> Format {
+  Format::new().set_num_format(format!("[${currency_code}] #,##0.00"))
+}
+
+fn rate_key(rate: f64) -> i64 {
+  (rate * 10.0).round() as i64
+}
+
 fn write_date(sheet: &mut Worksheet, row: u32, col: u16, date: &str, format: &Format) -> Result<(), AppError> {
   let parsed = NaiveDate::parse_from_str(date, "%Y-%m-%d")
     .map_err(|_| AppError::new("INVALID_DATE", "Ungueltiges Datum"))?;
@@ -474,3 +620,51 @@ fn write_date(sheet: &mut Worksheet, row: u32, col: u16, date: &str, format: &Fo
   sheet.write_datetime_with_format(row, col, &date, format)?;
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn year_sheet_vat_matches_dashboard_in_saldo_mode() {
+    let conn = crate::db::open_test_db();
+    conn
+      .execute_batch(
+        "UPDATE settings SET value = 'SALDO' WHERE key = 'mwst_mode';
+         UPDATE settings SET value = '6.2' WHERE key = 'mwst_saldo_rate';
+         INSERT INTO transactions (public_id, date, year, month, type, payment_method, amount_chf, mwst_rate, created_at, updated_at)
+         VALUES ('000001', '2024-05-10', 2024, 5, 'INCOME', 'BAR', 1000.0, 8.1, '2024-05-10T00:00:00Z', '2024-05-10T00:00:00Z'),
+                ('000002', '2024-05-11', 2024, 5, 'EXPENSE', NULL, 200.0, 8.1, '2024-05-11T00:00:00Z', '2024-05-11T00:00:00Z');",
+      )
+      .unwrap();
+
+    let settings = settings::get_settings(&conn).unwrap();
+    let dashboard = reports::kpis_from_base(reports::get_year_base_kpis(&conn, 2024).unwrap(), &settings);
+    let rows = year_summary_rows(&conn, 2024, &settings).unwrap();
+    let exported = rows
+      .iter()
+      .find(|(label_text, _)| label_text == "MWST Zahllast")
+      .map(|(_, value)| *value)
+      .unwrap();
+
+    assert_eq!(exported, dashboard.mwst_due);
+    assert_eq!(exported, 62.0);
+  }
+
+  #[test]
+  fn year_sheet_starts_with_income_total() {
+    let conn = crate::db::open_test_db();
+    conn
+      .execute_batch(
+        "INSERT INTO transactions (public_id, date, year, month, type, payment_method, amount_chf, mwst_rate, created_at, updated_at)
+         VALUES ('000001', '2024-05-10', 2024, 5, 'INCOME', 'TWINT', 80.0, 8.1, '2024-05-10T00:00:00Z', '2024-05-10T00:00:00Z');",
+      )
+      .unwrap();
+
+    let settings = settings::get_settings(&conn).unwrap();
+    let rows = year_summary_rows(&conn, 2024, &settings).unwrap();
+    assert_eq!(rows[0], ("Einnahmen Total".to_string(), 80.0));
+    assert_eq!(rows[1], ("Einnahmen TWINT".to_string(), 80.0));
+    assert_eq!(rows[2].0, "Ausgaben Total");
+  }
+}
@@ -0,0 +1,522 @@
+﻿use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, Duration, NaiveDate};
+use zip::ZipArchive;
+
+use crate::error::AppError;
+use crate::export::excel::{EXPORT_RECEIPTS_DIR, MONTH_SHEET_NAMES};
+
+// Reads back the month sheets written by excel::write_month_sheet. Only the
+// app's own layout is recognised; other sheets (JAHR, VERGLEICH, ...) are ignored.
+
+enum Cell {
+  Text(String),
+  Number(f64),
+}
+
+type SheetRows = BTreeMap<u32, BTreeMap<u16, Cell>>;
+
+pub struct MonthSheet {
+  pub name: String,
+  pub year: i32,
+  pub month: i32,
+  pub income: Vec<IncomeRow>,
+  pub expenses: Vec<ExpenseRow>,
+  pub invalid_rows: i64,
+}
+
+pub struct IncomeRow {
+  pub public_id: String,
+  pub date: NaiveDate,
+  pub payment_method: String,
+  pub amount_chf: f64,
+  pub mwst_rate: f64,
+  pub note: Option<String>,
+  pub ref_public_id: Option<String>,
+}
+
+pub struct ExpenseRow {
+  pub public_id: String,
+  pub date: NaiveDate,
+  pub category: Option<String>,
+  pub description: Option<String>,
+  pub amount_chf: f64,
+  pub mwst_rate: f64,
+  pub receipt: Option<String>,
+  pub note: Option<String>,
+  pub ref_public_id: Option<String>,
+}
+
+pub fn read_month_sheets(path: &Path) -> Result<Vec<MonthSheet>, AppError> {
+  let file = File::open(path)?;
+  let mut archive = ZipArchive::new(file)?;
+  let workbook = read_entry(&mut archive, "xl/workbook.xml")?
+    .ok_or_else(|| AppError::new("EXCEL_IMPORT_INVALID", "Datei ist keine gueltige Excel-Arbeitsmappe"))?;
+  let rels = read_entry(&mut archive, "xl/_rels/workbook.xml.rels")?.unwrap_or_default();
+  let shared = read_entry(&mut archive, "xl/sharedStrings.xml")?
+    .map(|xml| parse_shared_strings(&xml))
+    .unwrap_or_default();
+
+  let targets: HashMap<String, String> = elements(&rels, "Relationship")
+    .into_iter()
+    .filter_map(|(attrs, _)| Some((attr(attrs, "Id")?, attr(attrs, "Target")?)))
+    .collect();
+
+  let mut sheets = Vec::new();
+  for (attrs, _) in elements(&workbook, "sheet") {
+    let name = match attr(attrs, "name") {
+      Some(name) => name,
+      None => continue,
+    };
+    let (name_year, month) = match parse_sheet_name(&name) {
+      Some(parsed) => parsed,
+      None => continue,
+    };
+    let target = match attr(attrs, "r:id").and_then(|id| targets.get(&id).cloned()) {
+      Some(target) => target,
+      None => continue,
+    };
+    let entry = match target.strip_prefix('/') {
+      Some(absolute) => absolute.to_string(),
+      None => format!("xl/{target}"),
+    };
+    let xml = match read_entry(&mut archive, &entry)? {
+      Some(xml) => xml,
+      None => continue,
+    };
+    let rows = parse_sheet(&xml, &shared);
+    let year = match name_year.or_else(|| title_year(&rows)) {
+      Some(year) => year,
+      None => continue,
+    };
+    sheets.push(parse_month_sheet(name, year, month, &rows));
+  }
+
+  if sheets.is_empty() {
+    return Err(AppError::new(
+      "EXCEL_IMPORT_NO_SHEETS",
+      "Keine Monatsblaetter gefunden (erwartet JAN bis DEZ aus dem App-Export)",
+    ));
+  }
+  Ok(sheets)
+}
+
+// The export copies receipts to Belege/<yyyy>/<mm>/ next to the workbook and
+// shows only the file name, so look there before trusting the cell as a path.
+pub fn resolve_receipt(workbook: &Path, date: NaiveDate, receipt: &str) -> Option<PathBuf> {
+  let receipt = receipt.trim();
+  if receipt.is_empty() {
+    return None;
+  }
+  let direct = Path::new(receipt);
+  if direct.is_absolute() && direct.is_file() {
+    return Some(direct.to_path_buf());
+  }
+  let file_name = direct.file_name()?;
+  let exported = workbook
+    .parent()
+    .unwrap_or_else(|| Path::new("."))
+    .join(EXPORT_RECEIPTS_DIR)
+    .join(format!("{}", date.year()))
+    .join(format!("{:02}", date.month()))
+    .join(file_name);
+  exported.is_file().then_some(exported)
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<Option<String>, AppError> {
+  let mut file = match archive.by_name(name) {
+    Ok(file) => file,
+    Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+    Err(err) => return Err(err.into()),
+  };
+  let mut content = String::new();
+  file.read_to_string(&mut content)?;
+  Ok(Some(content))
+}
+
+fn parse_sheet_name(name: &str) -> Option<(Option<i32>, i32)> {
+  let name = name.trim();
+  let (year, code) = match name.split_once(' ') {
+    Some((year, code)) => (Some(year.parse().ok()?), code),
+    None => (None, name),
+  };
+  let month = MONTH_SHEET_NAMES.iter().position(|known| *known == code)? as i32 + 1;
+  Some((year, month))
+}
+
+// Sheets without a year prefix carry it in the title cell, e.g. "JAN 2024".
+fn title_year(rows: &SheetRows) -> Option<i32> {
+  text(rows, 0, 0)?.rsplit(' ').next()?.parse().ok()
+}
+
+fn parse_month_sheet(name: String, year: i32, month: i32, rows: &SheetRows) -> MonthSheet {
+  let mut sheet = MonthSheet {
+    name,
+    year,
+    month,
+    income: Vec::new(),
+    expenses: Vec::new(),
+    invalid_rows: 0,
+  };
+  let in_period = |date: NaiveDate| date.year() == year && date.month() as i32 == month;
+
+  if let Some(header) = find_header(rows, "Zahlungsart") {
+    for row in data_rows(rows, header) {
+      match income_row(rows, row).filter(|item| in_period(item.date)) {
+        Some(item) => sheet.income.push(item),
+        None => sheet.invalid_rows += 1,
+      }
+    }
+  }
+  if let Some(header) = find_header(rows, "Kategorie") {
+    for row in data_rows(rows, header) {
+      match expense_row(rows, row).filter(|item| in_period(item.date)) {
+        Some(item) => sheet.expenses.push(item),
+        None => sheet.invalid_rows += 1,
+      }
+    }
+  }
+  sheet
+}
+
+fn find_header(rows: &SheetRows, third_column: &str) -> Option<u32> {
+  rows.keys().copied().find(|&row| {
+    text(rows, row, 0).as_deref() == Some("ID")
+      && text(rows, row, 1).as_deref() == Some("Datum")
+      && text(rows, row, 2).as_deref() == Some(third_column)
+  })
+}
+
+// Data rows follow the header until the first row without an ID.
+fn data_rows(rows: &SheetRows, header: u32) -> Vec<u32> {
+  let mut result = Vec::new();
+  let mut row = header + 1;
+  while text(rows, row, 0).is_some() {
+    result.push(row);
+    row += 1;
+  }
+  result
+}
+
+fn income_row(rows: &SheetRows, row: u32) -> Option<IncomeRow> {
+  Some(IncomeRow {
+    public_id: text(rows, row, 0)?,
+    date: date(rows, row, 1)?,
+    payment_method: text(rows, row, 2)?,
+    amount_chf: number(rows, row, 3)?,
+    mwst_rate: number(rows, row, 4)?,
+    note: text(rows, row, 6),
+    ref_public_id: text(rows, row, 7),
+  })
+}
+
+fn expense_row(rows: &SheetRows, row: u32) -> Option<ExpenseRow> {
+  Some(ExpenseRow {
+    public_id: text(rows, row, 0)?,
+    date: date(rows, row, 1)?,
+    category: text(rows, row, 2),
+    description: text(rows, row, 3),
+    amount_chf: number(rows, row, 4)?,
+    mwst_rate: number(rows, row, 5)?,
+    receipt: text(rows, row, 7).filter(|value| value != "fehlt"),
+    note: text(rows, row, 8),
+    ref_public_id: text(rows, row, 9),
+  })
+}
+
+fn cell(rows: &SheetRows, row: u32, col: u16) -> Option<&Cell> {
+  rows.get(&row)?.get(&col)
+}
+
+fn text(rows: &SheetRows, row: u32, col: u16) -> Option<String> {
+  let value = match cell(rows, row, col)? {
+    Cell::Text(value) => value.trim().to_string(),
+    Cell::Number(value) => value.to_string(),
+  };
+  if value.is_empty() {
+    None
+  } else {
+    Some(value)
+  }
+}
+
+fn number(rows: &SheetRows, row: u32, col: u16) -> Option<f64> {
+  match cell(rows, row, col)? {
+    Cell::Number(value) => Some(*value),
+    Cell::Text(value) => value.trim().parse().ok(),
+  }
+}
+
+// Dates are stored as Excel serial numbers (days since 1899-12-30).
+fn date(rows: &SheetRows, row: u32, col: u16) -> Option<NaiveDate> {
+  match cell(rows, row, col)? {
+    Cell::Number(serial) => NaiveDate::from_ymd_opt(1899, 12, 30)?.checked_add_signed(Duration::days(serial.floor() as i64)),
+    Cell::Text(value) => NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+      .or_else(|_| NaiveDate::parse_from_str(value.trim(), "%d.%m.%Y"))
+      .ok(),
+  }
+}
+
+fn parse_shared_strings(xml: &str) -> Vec<String> {
+  elements(xml, "si")
+    .into_iter()
+    .map(|(_, body)| text_content(body))
+    .collect()
+}
+
+fn parse_sheet(xml: &str, shared: &[String]) -> SheetRows {
+  let mut rows = SheetRows::new();
+  for (attrs, body) in elements(xml, "c") {
+    let (row, col) = match attr(attrs, "r").and_then(|reference| cell_position(&reference)) {
+      Some(position) => position,
+      None => continue,
+    };
+    let value = match attr(attrs, "t").as_deref() {
+      Some("s") => first_value(body)
+        .and_then(|index| index.trim().parse::<usize>().ok())
+        .and_then(|index| shared.get(index))
+        .map(|value| Cell::Text(value.clone())),
+      Some("inlineStr") => Some(Cell::Text(text_content(body))),
+      Some("str") => first_value(body).map(|value| Cell::Text(unescape(value))),
+      _ => first_value(body)
+        .and_then(|value| value.trim().parse::<f64>().ok())
+        .map(Cell::Number),
+    };
+    if let Some(value) = value {
+      rows.entry(row).or_default().insert(col, value);
+    }
+  }
+  rows
+}
+
+fn first_value(body: &str) -> Option<&str> {
+  elements(body, "v").first().map(|(_, value)| *value)
+}
+
+fn text_content(body: &str) -> String {
+  elements(body, "t")
+    .into_iter()
+    .map(|(_, value)| unescape(value))
+    .collect()
+}
+
+// "B12" -> (11, 1), both zero-based.
+fn cell_position(reference: &str) -> Option<(u32, u16)> {
+  let split = reference.find(|ch: char| ch.is_ascii_digit())?;
+  let (letters, digits) = reference.split_at(split);
+  if letters.is_empty() {
+    return None;
+  }
+  let mut col: u32 = 0;
+  for ch in letters.chars() {
+    if !ch.is_ascii_uppercase() {
+      return None;
+    }
+    col = col * 26 + (ch as u32 - 'A' as u32 + 1);
+  }
+  let row: u32 = digits.parse().ok()?;
+  Some((row.checked_sub(1)?, u16::try_from(col - 1).ok()?))
+}
+
+// Minimal scanner for the flat elements xlsx uses (no nesting of the same tag).
+// Returns (attributes, inner content) per element.
+fn elements<'a>(xml: &'a str, tag: &str) -> Vec<(&'a str, &'a str)> {
+  let open = format!("<{tag}");
+  let close = format!("</{tag}>");
+  let mut found = Vec::new();
+  let mut rest = xml;
+  while let Some(start) = rest.find(&open) {
+    let after = &rest[start + open.len()..];
+    // Skip longer tag names sharing the prefix, e.g. <sheetData> when looking for <sheet>.
+    if !after.starts_with(|ch: char| ch.is_whitespace() || ch == '>' || ch == '/') {
+      rest = after;
+      continue;
+    }
+    let end = match after.find('>') {
+      Some(end) => end,
+      None => break,
+    };
+    let head = &after[..end];
+    if let Some(attrs) = head.strip_suffix('/') {
+      found.push((attrs, ""));
+      rest = &after[end + 1..];
+      continue;
+    }
+    let body = &after[end + 1..];
+    match body.find(&close) {
+      Some(close_at) => {
+        found.push((head, &body[..close_at]));
+        rest = &body[close_at + close.len()..];
+      }
+      None => break,
+    }
+  }
+  found
+}
+
+fn attr(attrs: &str, name: &str) -> Option<String> {
+  let key = format!(" {name}=\"");
+  let start = attrs.find(&key)? + key.len();
+  let end = attrs[start..].find('"')?;
+  Some(unescape(&attrs[start..start + end]))
+}
+
+fn unescape(value: &str) -> String {
+  if !value.contains('&') {
+    return value.to_string();
+  }
+  let mut out = String::with_capacity(value.len());
+  let mut rest = value;
+  while let Some(pos) = rest.find('&') {
+    out.push_str(&rest[..pos]);
+    let tail = &rest[pos..];
+    let decoded = tail.find(';').and_then(|end| {
+      let entity = &tail[1..end];
+      let ch = match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        _ => entity
+          .strip_prefix("#x")
+          .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+          .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+          .and_then(char::from_u32),
+      };
+      ch.map(|ch| (ch, end))
+    });
+    match decoded {
+      Some((ch, end)) => {
+        out.push(ch);
+        rest = &tail[end + 1..];
+      }
+      None => {
+        out.push('&');
+        rest = &tail[1..];
+      }
+    }
+  }
+  out.push_str(rest);
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::export::excel;
+  use rusqlite::Connection;
+  use std::fs;
+
+  fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+      "pizza_damico_{name}_{}",
+      chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  // Income with a storno, one expense with a receipt and its storno, one expense without receipt.
+  fn seed_month(conn: &Connection, year: i32, receipt: &Path) {
+    conn
+      .execute(
+        "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at)
+         VALUES (?1 || '01', ?2 || '-05-10', ?2, 5, 'INCOME', 'BAR', NULL, NULL, 100.0, 8.1, NULL, 'Kasse', NULL, '', ''),
+                (?1 || '02', ?2 || '-05-11', ?2, 5, 'INCOME', 'BAR', NULL, NULL, -100.0, 8.1, NULL, 'Storno', ?1 || '01', '', ''),
+                (?1 || '03', ?2 || '-05-12', ?2, 5, 'EXPENSE', NULL, (SELECT MIN(id) FROM categories), 'Mehl', 40.0, 2.6, ?3, NULL, NULL, '', ''),
+                (?1 || '04', ?2 || '-05-13', ?2, 5, 'EXPENSE', NULL, (SELECT MIN(id) FROM categories), 'Mehl', -40.0, 2.6, ?3, 'Storno', ?1 || '03', '', ''),
+                (?1 || '05', ?2 || '-05-14', ?2, 5, 'EXPENSE', NULL, (SELECT MIN(id) FROM categories), 'Tomaten', 12.5, 2.6, NULL, NULL, NULL, '', '')",
+        rusqlite::params![format!("{year}000"), year, receipt.to_string_lossy()],
+      )
+      .unwrap();
+  }
+
+  #[test]
+  fn month_export_round_trips() {
+    let conn = crate::db::open_test_db();
+    let dir = temp_dir("excel_round_trip");
+    let receipt = dir.join("quittung.pdf");
+    fs::write(&receipt, b"pdf").unwrap();
+    seed_month(&conn, 2024, &receipt);
+    let category: String = conn
+      .query_row("SELECT name FROM categories ORDER BY id LIMIT 1", [], |row| row.get(0))
+      .unwrap();
+    let workbook = dir.join("Export.xlsx");
+    excel::export_month(&conn, 2024, 5, &workbook, Some(&dir.join(EXPORT_RECEIPTS_DIR))).unwrap();
+
+    let sheets = read_month_sheets(&workbook).unwrap();
+    assert_eq!(sheets.len(), 1);
+    let sheet = &sheets[0];
+    assert_eq!((sheet.name.as_str(), sheet.year, sheet.month, sheet.invalid_rows), ("MAI", 2024, 5, 0));
+
+    assert_eq!(sheet.income.len(), 2);
+    let income = &sheet.income[0];
+    assert_eq!(income.public_id, "202400001");
+    assert_eq!(income.date, NaiveDate::from_ymd_opt(2024, 5, 10).unwrap());
+    assert_eq!(income.payment_method, "BAR");
+    assert_eq!((income.amount_chf, income.mwst_rate), (100.0, 8.1));
+    assert_eq!(income.note.as_deref(), Some("Kasse"));
+    assert_eq!(income.ref_public_id, None);
+    assert_eq!(sheet.income[1].amount_chf, -100.0);
+    assert_eq!(sheet.income[1].ref_public_id.as_deref(), Some("202400001"));
+
+    assert_eq!(sheet.expenses.len(), 3);
+    let expense = &sheet.expenses[0];
+    assert_eq!(expense.public_id, "202400003");
+    assert_eq!(expense.category.as_deref(), Some(category.as_str()));
+    assert_eq!(expense.description.as_deref(), Some("Mehl"));
+    assert_eq!((expense.amount_chf, expense.mwst_rate), (40.0, 2.6));
+    assert_eq!(expense.receipt.as_deref(), Some("quittung.pdf"));
+    assert_eq!(
+      resolve_receipt(&workbook, expense.date, "quittung.pdf"),
+      Some(dir.join("Belege").join("2024").join("05").join("quittung.pdf"))
+    );
+    assert_eq!(sheet.expenses[1].ref_public_id.as_deref(), Some("202400003"));
+    assert_eq!(sheet.expenses[2].receipt, None);
+    assert_eq!(sheet.expenses[2].amount_chf, 12.5);
+    let _ = fs::remove_dir_all(&dir);
+  }
+
+  #[test]
+  fn multi_year_export_keeps_years_apart() {
+    let conn = crate::db::open_test_db();
+    let dir = temp_dir("excel_years");
+    let receipt = dir.join("quittung.pdf");
+    fs::write(&receipt, b"pdf").unwrap();
+    seed_month(&conn, 2023, &receipt);
+    seed_month(&conn, 2024, &receipt);
+    let workbook = dir.join("Export.xlsx");
+    excel::export_years(&conn, &[2023, 2024], &workbook, None).unwrap();
+
+    let sheets = read_month_sheets(&workbook).unwrap();
+    assert_eq!(sheets.len(), 24);
+    for year in [2023, 2024] {
+      let may = sheets.iter().find(|sheet| sheet.name == format!("{year} MAI")).unwrap();
+      assert_eq!((may.year, may.month, may.invalid_rows), (year, 5, 0));
+      assert_eq!(may.income.len(), 2);
+      assert_eq!(may.expenses.len(), 3);
+      assert_eq!(may.income[0].public_id, format!("{year}00001"));
+      assert_eq!(may.income[1].ref_public_id, Some(format!("{year}00001")));
+    }
+    let _ = fs::remove_dir_all(&dir);
+  }
+
+  #[test]
+  fn receipt_resolves_next_to_workbook() {
+    let dir = temp_dir("excel_import");
+    let month_dir = dir.join("Belege").join("2024").join("05");
+    fs::create_dir_all(&month_dir).unwrap();
+    fs::write(month_dir.join("beleg.pdf"), b"pdf").unwrap();
+    let workbook = dir.join("Export.xlsx");
+    let date = NaiveDate::from_ymd_opt(2024, 5, 10).unwrap();
+
+    assert_eq!(resolve_receipt(&workbook, date, "beleg.pdf"), Some(month_dir.join("beleg.pdf")));
+    assert_eq!(resolve_receipt(&workbook, date, "C:/alt/beleg.pdf"), Some(month_dir.join("beleg.pdf")));
+    assert_eq!(resolve_receipt(&workbook, date, "fehlt.pdf"), None);
+    let _ = fs::remove_dir_all(&dir);
+  }
+}
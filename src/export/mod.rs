@@ -1,2 +1,70 @@
-﻿pub mod csv;
+﻿pub mod bank_csv;
+pub mod csv;
 pub mod excel;
+pub mod excel_import;
+pub mod receipts;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+
+fn temp_path_for(path: &Path) -> PathBuf {
+  let file_name = path
+    .file_name()
+    .map(|name| name.to_string_lossy().to_string())
+    .unwrap_or_else(|| "export".to_string());
+  path.with_file_name(format!(".{file_name}.tmp"))
+}
+
+pub(crate) fn write_atomic<F>(path: &Path, write: F) -> Result<(), AppError>
+where
+  F: FnOnce(&Path) -> Result<(), AppError>,
+{
+  let temp_path = temp_path_for(path);
+  let result = write(&temp_path).and_then(|_| fs::rename(&temp_path, path).map_err(|err| rename_error(path, err)));
+  if result.is_err() {
+    let _ = fs::remove_file(&temp_path);
+  }
+  result
+}
+
+// Windows reports a file held open by Excel as a sharing violation (32) or lock violation (33).
+fn rename_error(path: &Path, err: std::io::Error) -> AppError {
+  let locked = matches!(err.raw_os_error(), Some(32) | Some(33))
+    || (err.kind() == std::io::ErrorKind::PermissionDenied && path.exists());
+  if locked {
+    AppError::new(
+      "EXPORT_FILE_LOCKED",
+      format!("Datei ist geoeffnet, bitte schliessen und erneut exportieren: {}", path.display()),
+    )
+    .with_details(serde_json::json!({ "path": path.to_string_lossy() }))
+  } else {
+    AppError::from(err)
+  }
+}
+
+pub(crate) fn unique_receipt_path(base_dir: &Path, file_name: &str) -> PathBuf {
+  let mut candidate = base_dir.join(file_name);
+  if !candidate.exists() {
+    return candidate;
+  }
+  let stem = Path::new(file_name)
+    .file_stem()
+    .and_then(|value| value.to_str())
+    .unwrap_or("beleg");
+  let ext = Path::new(file_name).extension().and_then(|value| value.to_str()).unwrap_or("");
+  let mut counter = 1;
+  loop {
+    let next_name = if ext.is_empty() {
+      format!("{stem}_{counter}")
+    } else {
+      format!("{stem}_{counter}.{ext}")
+    };
+    candidate = base_dir.join(next_name);
+    if !candidate.exists() {
+      return candidate;
+    }
+    counter += 1;
+  }
+}
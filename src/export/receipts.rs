@@ -0,0 +1,62 @@
+﻿use std::fs;
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+use crate::error::AppError;
+use crate::export::unique_receipt_path;
+use crate::models::ReceiptExportSummary;
+
+pub fn export_period_receipts(
+  conn: &Connection,
+  year: i32,
+  month_from: i32,
+  month_to: i32,
+  output_dir: &Path,
+) -> Result<ReceiptExportSummary, AppError> {
+  fs::create_dir_all(output_dir)?;
+
+  let mut stmt = conn.prepare(
+    "SELECT public_id, date, receipt_path
+     FROM transactions
+     WHERE year = ?1 AND month BETWEEN ?2 AND ?3
+       AND receipt_path IS NOT NULL AND TRIM(receipt_path) <> ''
+     ORDER BY date, CAST(public_id AS INTEGER)",
+  )?;
+  let rows = stmt.query_map(params![year, month_from, month_to], |row| {
+    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+  })?;
+
+  let mut summary = ReceiptExportSummary {
+    output_dir: output_dir.to_string_lossy().to_string(),
+    exported: 0,
+    missing: 0,
+    missing_public_ids: Vec::new(),
+  };
+
+  for row in rows {
+    let (public_id, date, receipt_path) = row?;
+    let source = Path::new(receipt_path.trim());
+    if !source.is_file() {
+      summary.missing += 1;
+      summary.missing_public_ids.push(public_id);
+      continue;
+    }
+    let ext = source
+      .extension()
+      .and_then(|value| value.to_str())
+      .map(|value| value.to_ascii_lowercase())
+      .unwrap_or_default();
+    let file_name = if ext.is_empty() {
+      format!("{date}_{public_id}")
+    } else {
+      format!("{date}_{public_id}.{ext}")
+    };
+    // Never overwrite files from an earlier run into the same folder.
+    let target = unique_receipt_path(output_dir, &file_name);
+    fs::copy(source, &target)?;
+    summary.exported += 1;
+  }
+
+  Ok(summary)
+}
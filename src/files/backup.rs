@@ -3,12 +3,23 @@ use std::io::{Read, Write};
 use std::path::Path;
 
 use chrono::Utc;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
 use walkdir::WalkDir;
 use zip::write::FileOptions;
 use zip::{ZipArchive, ZipWriter};
 
+use crate::db;
 use crate::error::AppError;
 
+const MANIFEST_NAME: &str = "manifest.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupManifest {
+  pub schema_version: String,
+  pub created_at: String,
+}
+
 pub fn create_backup(
   app_dir: &Path,
   db_path: &Path,
@@ -35,6 +46,17 @@ pub fn create_backup(
   let mut zip = ZipWriter::new(file);
   let options = FileOptions::<()>::default().compression_method(zip::CompressionMethod::Deflated);
 
+  let manifest = BackupManifest {
+    schema_version: db::latest_schema_version().to_string(),
+    created_at: Utc::now().to_rfc3339(),
+  };
+  zip.start_file(MANIFEST_NAME, options)?;
+  zip.write_all(
+    serde_json::to_string_pretty(&manifest)
+      .map_err(|err| AppError::new("BACKUP", err.to_string()))?
+      .as_bytes(),
+  )?;
+
   zip.start_file("db.sqlite", options)?;
   let mut db_file = File::open(db_path)?;
   let mut buffer = Vec::new();
@@ -60,11 +82,12 @@ pub fn create_backup(
   Ok(filename)
 }
 
+// Returns the schema version the archived database had before it was verified and migrated.
 pub fn restore_backup(
   archive_path: &str,
   db_path: &Path,
   receipt_base: &Path,
-) -> Result<(), AppError> {
+) -> Result<Option<String>, AppError> {
   let file = File::open(archive_path)?;
   let mut archive = ZipArchive::new(file)?;
 
@@ -86,8 +109,17 @@ pub fn restore_backup(
     }
   }
 
+  ensure_manifest_compatible(&temp_dir.join(MANIFEST_NAME))?;
+
   let restored_db = temp_dir.join("db.sqlite");
+  let mut backup_schema_version = None;
   if restored_db.exists() {
+    // Verify the extracted copy first so an incompatible backup never replaces the live database.
+    {
+      let mut check_conn = Connection::open(&restored_db)?;
+      backup_schema_version = db::current_schema_version(&check_conn).ok().flatten();
+      db::verify_restored_db(&mut check_conn)?;
+    }
     if db_path.exists() {
       let backup_path = db_path.with_extension("bak");
       fs::copy(db_path, backup_path)?;
@@ -95,9 +127,9 @@ pub fn restore_backup(
     fs::copy(restored_db, db_path)?;
   }
 
+  fs::create_dir_all(receipt_base)?;
   let restored_receipts = temp_dir.join("receipts");
   if restored_receipts.exists() {
-    fs::create_dir_all(receipt_base)?;
     for entry in WalkDir::new(&restored_receipts).into_iter().filter_map(Result::ok) {
       if entry.file_type().is_file() {
         let rel = entry.path().strip_prefix(&restored_receipts).unwrap_or(entry.path());
@@ -110,5 +142,111 @@ pub fn restore_backup(
     }
   }
 
+  Ok(backup_schema_version)
+}
+
+// Puts the copy taken by restore_backup back in place after a failed post-restore check.
+pub fn rollback_restore(db_path: &Path) -> Result<(), AppError> {
+  let backup_path = db_path.with_extension("bak");
+  if backup_path.exists() {
+    fs::copy(&backup_path, db_path)?;
+  }
+  Ok(())
+}
+
+fn ensure_manifest_compatible(manifest_path: &Path) -> Result<(), AppError> {
+  if !manifest_path.exists() {
+    return Ok(());
+  }
+  let content = fs::read_to_string(manifest_path)?;
+  let manifest: BackupManifest = serde_json::from_str(&content)
+    .map_err(|err| AppError::new("BACKUP_MANIFEST", format!("manifest.json ungueltig: {err}")))?;
+  if !db::is_known_schema_version(&manifest.schema_version) {
+    return Err(AppError::new(
+      "BACKUP_TOO_NEW",
+      format!(
+        "Backup wurde mit neuerem Schema erstellt ({}), App kennt {}",
+        manifest.schema_version,
+        db::latest_schema_version()
+      ),
+    ));
+  }
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+      "pizza_damico_{name}_{}",
+      Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  fn write_manifest(name: &str, schema_version: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!(
+      "pizza_damico_{name}_{}.json",
+      Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+    let manifest = BackupManifest {
+      schema_version: schema_version.to_string(),
+      created_at: Utc::now().to_rfc3339(),
+    };
+    fs::write(&path, serde_json::to_string(&manifest).unwrap()).unwrap();
+    path
+  }
+
+  #[test]
+  fn manifest_with_older_schema_is_accepted() {
+    let path = write_manifest("manifest_old", "001_init");
+    assert!(ensure_manifest_compatible(&path).is_ok());
+    let _ = fs::remove_file(path);
+  }
+
+  #[test]
+  fn manifest_with_newer_schema_is_rejected() {
+    let path = write_manifest("manifest_new", "999_future");
+    let err = ensure_manifest_compatible(&path).err().unwrap();
+    assert_eq!(err.code, "BACKUP_TOO_NEW");
+    let _ = fs::remove_file(path);
+  }
+
+  #[test]
+  fn restore_into_empty_dir_accepts_new_transaction() {
+    let source = temp_dir("restore_source");
+    let source_db = source.join("db.sqlite");
+    {
+      let mut conn = Connection::open(&source_db).unwrap();
+      db::run_migrations(&mut conn).unwrap();
+      db::seed_defaults(&conn, &source.join("Belege")).unwrap();
+    }
+    let archive = create_backup(&source, &source_db, &source.join("Belege"), false, None).unwrap();
+
+    let target = temp_dir("restore_target");
+    let target_db = target.join("db.sqlite");
+    let receipt_base = target.join("Belege");
+    restore_backup(&archive, &target_db, &receipt_base).unwrap();
+    assert!(receipt_base.is_dir());
+
+    let mut conn = Connection::open(&target_db).unwrap();
+    let check = db::verify_restored_db(&mut conn).unwrap();
+    assert_eq!(check.schema_version.as_deref(), Some(db::latest_schema_version()));
+    conn
+      .execute(
+        "INSERT INTO transactions (public_id, date, year, month, type, payment_method, amount_chf, mwst_rate, created_at, updated_at)
+         VALUES ('000001', '2024-05-10', 2024, 5, 'INCOME', 'BAR', 120.0, 2.6, ?1, ?1)",
+        [Utc::now().to_rfc3339()],
+      )
+      .unwrap();
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM transactions", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, 1);
+
+    drop(conn);
+    let _ = fs::remove_dir_all(source);
+    let _ = fs::remove_dir_all(target);
+  }
+}
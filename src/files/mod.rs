@@ -1,2 +1,3 @@
 ﻿pub mod backup;
 pub mod receipts;
+pub mod reveal;
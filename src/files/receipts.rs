@@ -1,7 +1,26 @@
 ﻿use std::fs;
+use std::io::Cursor;
 use std::path::{Path, PathBuf};
 
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use walkdir::WalkDir;
+
 use crate::error::AppError;
+use crate::models::ReceiptInfo;
+
+pub const RECEIPT_MAX_EDGE: u32 = 2000;
+pub const DEFAULT_RECEIPT_NAMING_PATTERN: &str = "Beleg_{id}.{ext}";
+pub const DEFAULT_RECEIPT_MAX_BYTES: u64 = 12 * 1024 * 1024;
+pub const DEFAULT_RECEIPT_EXTENSIONS: &[&str] = &["pdf", "png", "jpg", "jpeg"];
+
+pub struct ReceiptTarget<'a> {
+  pub year: i32,
+  pub month: i32,
+  pub public_id: &'a str,
+  pub date: &'a str,
+  pub category: Option<&'a str>,
+}
 
 pub fn ensure_receipt_base(app_dir: &Path) -> Result<PathBuf, AppError> {
   let receipt_dir = app_dir.join("Belege");
@@ -12,20 +31,21 @@ pub fn ensure_receipt_base(app_dir: &Path) -> Result<PathBuf, AppError> {
 pub fn copy_receipt(
   source_path: &str,
   receipt_base: &Path,
-  year: i32,
-  month: i32,
-  public_id: &str,
+  target: &ReceiptTarget,
+  naming_pattern: &str,
+  max_edge: Option<u32>,
+  max_bytes: u64,
+  allowed_extensions: &[String],
 ) -> Result<String, AppError> {
   let source = Path::new(source_path);
-  if !source.exists() {
-    return Err(AppError::new("RECEIPT_NOT_FOUND", "Belegdatei nicht gefunden"));
-  }
+  validate_receipt(source, max_bytes, allowed_extensions)?;
 
-  let month_dir = receipt_base.join(format!("{year}")).join(format!("{month:02}"));
-  fs::create_dir_all(&month_dir)?;
+  let month_dir = receipt_base
+    .join(format!("{}", target.year))
+    .join(format!("{:02}", target.month));
 
   let ext = source.extension().and_then(|v| v.to_str()).unwrap_or("bin");
-  let base_name = format!("Beleg_{public_id}");
+  let base_name = render_receipt_name(naming_pattern, target, ext);
   let mut candidate = month_dir.join(format!("{base_name}.{ext}"));
   let mut counter = 1;
   while candidate.exists() {
@@ -33,10 +53,185 @@ pub fn copy_receipt(
     counter += 1;
   }
 
-  fs::copy(source, &candidate)?;
+  let bytes = receipt_bytes(source, max_edge)?;
+  if let Some(existing) = find_identical_receipt(receipt_base, &bytes)? {
+    return Ok(existing.to_string_lossy().to_string());
+  }
+  fs::create_dir_all(&month_dir)?;
+  fs::write(&candidate, &bytes)?;
   Ok(candidate.to_string_lossy().to_string())
 }
 
+pub fn validate_receipt(path: &Path, max_bytes: u64, allowed_extensions: &[String]) -> Result<ReceiptInfo, AppError> {
+  if !path.is_file() {
+    return Err(AppError::new("RECEIPT_NOT_FOUND", "Belegdatei nicht gefunden"));
+  }
+  let extension = ensure_receipt_allowed(path, max_bytes, allowed_extensions)?;
+  Ok(ReceiptInfo {
+    path: path.to_string_lossy().to_string(),
+    content_type: content_type_for(&extension).to_string(),
+    size_bytes: fs::metadata(path)?.len(),
+    extension,
+  })
+}
+
+pub fn content_type_for(extension: &str) -> &'static str {
+  match extension {
+    "pdf" => "application/pdf",
+    "png" => "image/png",
+    "jpg" | "jpeg" => "image/jpeg",
+    "tif" | "tiff" => "image/tiff",
+    "gif" => "image/gif",
+    "webp" => "image/webp",
+    "heic" => "image/heic",
+    _ => "application/octet-stream",
+  }
+}
+
+pub fn ensure_receipt_allowed(path: &Path, max_bytes: u64, allowed_extensions: &[String]) -> Result<String, AppError> {
+  let ext = path
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .unwrap_or("")
+    .to_lowercase();
+  let allowed = normalize_extensions(allowed_extensions);
+  if ext.is_empty() || !allowed.contains(&ext) {
+    return Err(AppError::new(
+      "RECEIPT_TYPE",
+      format!("Dateiformat nicht unterstuetzt (erlaubt: {})", allowed.join(", ")),
+    ));
+  }
+  let max_bytes = if max_bytes == 0 { DEFAULT_RECEIPT_MAX_BYTES } else { max_bytes };
+  if fs::metadata(path)?.len() > max_bytes {
+    return Err(AppError::new(
+      "RECEIPT_SIZE",
+      format!("Datei ist zu gross (maximal {})", format_megabytes(max_bytes)),
+    ));
+  }
+  Ok(ext)
+}
+
+pub fn normalize_extensions(extensions: &[String]) -> Vec<String> {
+  let mut normalized: Vec<String> = Vec::new();
+  for ext in extensions {
+    let value = ext.trim().trim_start_matches('.').to_lowercase();
+    if !value.is_empty() && !normalized.contains(&value) {
+      normalized.push(value);
+    }
+  }
+  if normalized.is_empty() {
+    DEFAULT_RECEIPT_EXTENSIONS.iter().map(|ext| ext.to_string()).collect()
+  } else {
+    normalized
+  }
+}
+
+pub fn format_megabytes(bytes: u64) -> String {
+  let mb = bytes as f64 / (1024.0 * 1024.0);
+  if mb.fract() == 0.0 {
+    format!("{mb:.0} MB")
+  } else {
+    format!("{mb:.1} MB")
+  }
+}
+
+fn render_receipt_name(pattern: &str, target: &ReceiptTarget, ext: &str) -> String {
+  let pattern = if pattern.trim().is_empty() {
+    DEFAULT_RECEIPT_NAMING_PATTERN
+  } else {
+    pattern.trim()
+  };
+  let stem_pattern = pattern.strip_suffix(".{ext}").unwrap_or(pattern);
+  let rendered = stem_pattern
+    .replace("{id}", &sanitize_file_part(target.public_id))
+    .replace("{date}", &sanitize_file_part(target.date))
+    .replace("{year}", &target.year.to_string())
+    .replace("{month}", &format!("{:02}", target.month))
+    .replace("{category}", &sanitize_file_part(target.category.unwrap_or("")))
+    .replace("{ext}", &sanitize_file_part(ext));
+  let name = sanitize_file_part(&rendered);
+  if name.is_empty() {
+    format!("Beleg_{}", sanitize_file_part(target.public_id))
+  } else {
+    name
+  }
+}
+
+fn sanitize_file_part(value: &str) -> String {
+  let cleaned: String = value
+    .chars()
+    .map(|c| {
+      if c.is_control() || matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*') {
+        '_'
+      } else {
+        c
+      }
+    })
+    .collect();
+  cleaned.trim_matches(|c: char| c == ' ' || c == '.').to_string()
+}
+
+// The whole receipt base is searched, so a receipt attached in several months is stored once.
+// Files are only read when their size matches, which keeps the scan cheap.
+fn find_identical_receipt(receipt_base: &Path, content: &[u8]) -> Result<Option<PathBuf>, AppError> {
+  let size = content.len() as u64;
+  for entry in WalkDir::new(receipt_base).into_iter().filter_map(Result::ok) {
+    if !entry.file_type().is_file() {
+      continue;
+    }
+    let matches_size = entry.metadata().map(|meta| meta.len() == size).unwrap_or(false);
+    if !matches_size {
+      continue;
+    }
+    match fs::read(entry.path()) {
+      Ok(other) if other == content => return Ok(Some(entry.path().to_path_buf())),
+      _ => continue,
+    }
+  }
+  Ok(None)
+}
+
+// The bytes that end up in the receipt base: the downscaled image if compression applies, else the file as is.
+fn receipt_bytes(source: &Path, max_edge: Option<u32>) -> Result<Vec<u8>, AppError> {
+  if let (Some(max_edge), Some(format)) = (max_edge, image_format(source)) {
+    if let Some(bytes) = downscale_image(source, format, max_edge)? {
+      return Ok(bytes);
+    }
+  }
+  Ok(fs::read(source)?)
+}
+
+fn image_format(path: &Path) -> Option<ImageFormat> {
+  let ext = path.extension().and_then(|v| v.to_str())?.to_lowercase();
+  match ext.as_str() {
+    "jpg" | "jpeg" => Some(ImageFormat::Jpeg),
+    "png" => Some(ImageFormat::Png),
+    _ => None,
+  }
+}
+
+fn downscale_image(source: &Path, format: ImageFormat, max_edge: u32) -> Result<Option<Vec<u8>>, AppError> {
+  let img = match image::open(source) {
+    Ok(img) => img,
+    Err(_) => return Ok(None),
+  };
+  if img.width() <= max_edge && img.height() <= max_edge {
+    return Ok(None);
+  }
+
+  let resized = img.resize(max_edge, max_edge, FilterType::Lanczos3);
+  let output = if format == ImageFormat::Jpeg {
+    DynamicImage::ImageRgb8(resized.to_rgb8())
+  } else {
+    resized
+  };
+  let mut bytes = Vec::new();
+  output
+    .write_to(&mut Cursor::new(&mut bytes), format)
+    .map_err(|err| AppError::new("RECEIPT_IMAGE", err.to_string()))?;
+  Ok(Some(bytes))
+}
+
 pub fn open_receipt(path: &str) -> Result<(), AppError> {
   if path.trim().is_empty() {
     return Err(AppError::new("RECEIPT_PATH_EMPTY", "Belegpfad fehlt"));
@@ -44,3 +239,35 @@ pub fn open_receipt(path: &str) -> Result<(), AppError> {
   open::that(path).map_err(|err| AppError::new("RECEIPT_OPEN", err.to_string()))?;
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn identical_receipt_in_another_month_is_reused() {
+    let dir = std::env::temp_dir().join(format!(
+      "pizza_damico_receipts_{}",
+      chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+    let base = dir.join("Belege");
+    fs::create_dir_all(&base).unwrap();
+    let source = dir.join("scan.pdf");
+    fs::write(&source, b"%PDF-1.4 beleg").unwrap();
+    let allowed = vec!["pdf".to_string()];
+    let target = |month| ReceiptTarget {
+      year: 2024,
+      month,
+      public_id: "000001",
+      date: "2024-05-10",
+      category: None,
+    };
+
+    let first = copy_receipt(&source.to_string_lossy(), &base, &target(5), "", None, 0, &allowed).unwrap();
+    let second = copy_receipt(&source.to_string_lossy(), &base, &target(6), "", None, 0, &allowed).unwrap();
+
+    assert_eq!(first, second);
+    assert!(!base.join("2024").join("06").exists());
+    let _ = fs::remove_dir_all(&dir);
+  }
+}
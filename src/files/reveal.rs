@@ -0,0 +1,39 @@
+﻿use std::path::Path;
+use std::process::Command;
+
+use crate::error::AppError;
+
+pub fn reveal_in_folder(path: &str) -> Result<(), AppError> {
+  let target = Path::new(path);
+  if path.trim().is_empty() || !target.exists() {
+    return Err(AppError::new("PATH_NOT_FOUND", "Pfad nicht gefunden"));
+  }
+
+  reveal(target).map_err(|err| AppError::new("REVEAL_FAILED", err.to_string()))
+}
+
+#[cfg(target_os = "windows")]
+fn reveal(target: &Path) -> std::io::Result<()> {
+  use std::os::windows::process::CommandExt;
+
+  Command::new("explorer")
+    .raw_arg(format!("/select,\"{}\"", target.display()))
+    .spawn()?;
+  Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn reveal(target: &Path) -> std::io::Result<()> {
+  Command::new("open").arg("-R").arg(target).spawn()?;
+  Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn reveal(target: &Path) -> std::io::Result<()> {
+  let folder = if target.is_dir() {
+    target
+  } else {
+    target.parent().unwrap_or(target)
+  };
+  open::that(folder)
+}
@@ -4,20 +4,43 @@ import { open, save } from "@tauri-apps/plugin-dialog";
 import type {
   AuditLogEntry,
   BackupRequest,
+  BankCsvParseRequest,
+  BankImportRequest,
+  BankImportRow,
+  BankImportSummary,
+  BrokenReceipt,
   Category,
   CategoryInput,
+  CategoryStat,
   CategoryUpdateInput,
+  Diagnostics,
+  EditPermission,
+  ExcelImportSummary,
+  ExportInfo,
   ExportRequest,
+  MonthAmountPoint,
   MonthCharts,
+  MonthDashboard,
   MonthKpis,
+  MonthSeriesPoint,
+  MockSeedSummary,
   MonthStatus,
+  MwstComparison,
+  MwstModePreview,
   Paginated,
+  ReceiptExportSummary,
+  ReceiptInfo,
+  ReceiptRelinkSummary,
   RestoreRequest,
+  RestoreResult,
   Settings,
+  SyncLogEntry,
   SyncStatus,
+  TicketStats,
   TransactionListItem,
   TwintImportRequest,
   TwintImportSummary,
+  TxRelations,
   YearCharts,
   YearKpis,
 } from "./types";
@@ -25,6 +48,8 @@ import type {
 export interface InvokeError {
   code?: string;
   message: string;
+  details?: unknown;
+  field?: string;
 }
 
 export function parseInvokeError(error: unknown): InvokeError {
@@ -34,7 +59,12 @@ export function parseInvokeError(error: unknown): InvokeError {
   if (error && typeof error === "object") {
     const err = error as Record<string, unknown>;
     if (typeof err.code === "string" && typeof err.message === "string") {
-      return { code: err.code, message: err.message };
+      return {
+        code: err.code,
+        message: err.message,
+        details: err.details,
+        field: typeof err.field === "string" ? err.field : undefined,
+      };
     }
     if (typeof err.message === "string") {
       try {
@@ -60,10 +90,26 @@ export const api = {
     return invoke("update_settings", { settings_input: payload, settingsInput: payload });
   },
 
+  async formatTimestamps(timestamps: string[]): Promise<string[]> {
+    return invoke("format_timestamps", { timestamps });
+  },
+
+  async setLastViewedYear(year: number): Promise<void> {
+    return invoke("set_last_viewed_year", { year });
+  },
+
+  async rolloverYear(): Promise<number> {
+    return invoke("rollover_year");
+  },
+
   async listCategories(): Promise<Category[]> {
     return invoke("list_categories");
   },
 
+  async listCategoriesWithStats(): Promise<CategoryStat[]> {
+    return invoke("list_categories_with_stats");
+  },
+
   async createCategory(payload: CategoryInput): Promise<Category> {
     return invoke("create_category", { input: payload });
   },
@@ -72,6 +118,10 @@ export const api = {
     return invoke("update_category", { input: payload });
   },
 
+  async importCategoriesFromBackup(archive_path: string): Promise<number> {
+    return invoke("import_categories_from_backup", { archive_path, archivePath: archive_path });
+  },
+
   async deactivateCategory(id: number): Promise<void> {
     return invoke("deactivate_category", { id });
   },
@@ -91,6 +141,7 @@ export const api = {
     date: string;
     category_id: number;
     description?: string;
+    payment_method?: "BAR" | "TWINT" | null;
     amount_chf: number;
     mwst_rate?: number;
     receipt_source_path?: string | null;
@@ -105,6 +156,7 @@ export const api = {
     date: string;
     amount_chf?: number;
     reason: string;
+    allow_cross_period?: boolean;
   }): Promise<TransactionListItem> {
     return invoke("create_storno", { input: payload });
   },
@@ -113,9 +165,50 @@ export const api = {
     return invoke("delete_transaction", { public_id, publicId: public_id });
   },
 
+  async updateNote(public_id: string, note: string | null): Promise<TransactionListItem> {
+    return invoke("update_note", { public_id, publicId: public_id, note });
+  },
+
+  async updatePaymentMethod(public_id: string, payment_method: "BAR" | "TWINT"): Promise<TransactionListItem> {
+    return invoke("update_payment_method", {
+      public_id,
+      publicId: public_id,
+      payment_method,
+      paymentMethod: payment_method,
+    });
+  },
+
+  async setReceiptNotRequired(public_id: string, notRequired: boolean): Promise<TransactionListItem> {
+    return invoke("set_receipt_not_required", {
+      public_id,
+      publicId: public_id,
+      not_required: notRequired,
+      notRequired,
+    });
+  },
+
+  async relinkReceipts(oldBase: string, newBase: string, moveFiles = false): Promise<ReceiptRelinkSummary> {
+    return invoke("relink_receipts", {
+      old_base: oldBase,
+      oldBase,
+      new_base: newBase,
+      newBase,
+      move_files: moveFiles,
+      moveFiles,
+    });
+  },
+
+
+  async searchTransactions(query: string, limit = 12, field?: string, exact?: boolean): Promise<TransactionListItem[]> {
+    return invoke("search_transactions", { query, limit, field, exact });
+  },
+
+  async getTransaction(public_id: string): Promise<TransactionListItem> {
+    return invoke("get_transaction", { public_id, publicId: public_id });
+  },
 
-  async searchTransactions(query: string, limit = 12): Promise<TransactionListItem[]> {
-    return invoke("search_transactions", { query, limit });
+  async getTransactionRelations(public_id: string): Promise<TxRelations> {
+    return invoke("get_transaction_relations", { public_id, publicId: public_id });
   },
 
   async searchTransactionsPaged(query: string, page: number, pageSize: number): Promise<Paginated<TransactionListItem>> {
@@ -129,6 +222,13 @@ export const api = {
     page: number;
     page_size: number;
     search?: string;
+    ignore_period?: boolean;
+    min_amount?: number;
+    max_amount?: number;
+    date_from?: string;
+    date_to?: string;
+    sort_by?: "date" | "amount" | "id" | "category";
+    sort_dir?: "asc" | "desc";
   }): Promise<Paginated<TransactionListItem>> {
     return invoke("list_transactions", { filter: payload });
   },
@@ -141,52 +241,153 @@ export const api = {
     return invoke("get_year_kpis", { year });
   },
 
+  async getMwstComparison(year: number): Promise<MwstComparison> {
+    return invoke("get_mwst_comparison", { year });
+  },
+
+  async previewMwstMode(year: number, mode: "EFFEKTIV" | "SALDO"): Promise<MwstModePreview> {
+    return invoke("preview_mwst_mode", { year, mode });
+  },
+
+  async getFiscalYearKpis(fiscalYear: number): Promise<YearKpis> {
+    return invoke("get_fiscal_year_kpis", { fiscalYear });
+  },
+
+  async getDaterangeKpis(dateFrom: string, dateTo: string): Promise<YearKpis> {
+    return invoke("get_daterange_kpis", { date_from: dateFrom, dateFrom, date_to: dateTo, dateTo });
+  },
+
+  async getFiscalMonthSeries(fiscalYear: number): Promise<MonthSeriesPoint[]> {
+    return invoke("get_fiscal_month_series", { fiscalYear });
+  },
+
+  async getCategoryTrend(year: number, categoryId: number): Promise<MonthAmountPoint[]> {
+    return invoke("get_category_trend", { year, category_id: categoryId, categoryId });
+  },
+
   async getMonthCharts(year: number, month: number): Promise<MonthCharts> {
     return invoke("get_month_charts", { year, month });
   },
 
+  async getMonthDashboard(year: number, month: number): Promise<MonthDashboard> {
+    return invoke("get_month_dashboard", { year, month });
+  },
+
   async getYearCharts(year: number): Promise<YearCharts> {
     return invoke("get_year_charts", { year });
   },
 
+  async getLargestTransactions(
+    year: number,
+    month?: number | null,
+    txType?: "INCOME" | "EXPENSE" | null,
+    limit = 10
+  ): Promise<TransactionListItem[]> {
+    return invoke("get_largest_transactions", {
+      year,
+      month: month ?? null,
+      txType: txType ?? null,
+      tx_type: txType ?? null,
+      limit,
+    });
+  },
+
+  async getTicketStats(year: number, month?: number | null): Promise<TicketStats> {
+    return invoke("get_ticket_stats", { year, month: month ?? null });
+  },
+
+  async listBrokenReceipts(): Promise<BrokenReceipt[]> {
+    return invoke("list_broken_receipts");
+  },
+
   async getMonthStatus(year: number, month: number): Promise<MonthStatus> {
     return invoke("get_month_status", { year, month });
   },
 
-  async closeMonth(year: number, month: number): Promise<void> {
-    return invoke("close_month", { year, month });
+  async canEditPeriod(year: number, month: number): Promise<EditPermission> {
+    return invoke("can_edit_period", { year, month });
+  },
+
+  async closeMonth(year: number, month: number, force = false, comment?: string | null): Promise<void> {
+    return invoke("close_month", { year, month, force, comment: comment ?? null });
   },
 
   async openMonth(year: number, month: number): Promise<void> {
     return invoke("open_month", { year, month });
   },
 
+  async closeMonths(year: number, months: number[], force = false): Promise<number[]> {
+    return invoke("close_months", { year, months, force });
+  },
+
+  async openMonths(year: number, months: number[]): Promise<number[]> {
+    return invoke("open_months", { year, months });
+  },
+
   async listAuditLog(page: number, pageSize: number): Promise<Paginated<AuditLogEntry>> {
     return invoke("list_audit_log", { page, pageSize, page_size: pageSize });
   },
 
+  async purgeAuditLog(olderThanDays: number): Promise<number> {
+    return invoke("purge_audit_log", { olderThanDays, older_than_days: olderThanDays });
+  },
+
+  async getTransactionAudit(public_id: string): Promise<AuditLogEntry[]> {
+    return invoke("get_transaction_audit", { public_id, publicId: public_id });
+  },
+
 
-  async seedMockData(count: number): Promise<number> {
-    return invoke("seed_mock_data", { count });
+  async factoryReset(confirm: string, wipeReceipts = false): Promise<void> {
+    return invoke("factory_reset", { confirm, wipe_receipts: wipeReceipts, wipeReceipts });
+  },
+
+  async seedMockData(count: number, seed?: number, dryRun = false): Promise<MockSeedSummary> {
+    return invoke("seed_mock_data", { count, seed: seed ?? null, dry_run: dryRun, dryRun });
   },
 
-  async clearDemoData(): Promise<number> {
-    return invoke("clear_demo_data");
+  async clearDemoData(includeLegacy = false): Promise<number> {
+    return invoke("clear_demo_data", { include_legacy: includeLegacy, includeLegacy });
   },
 
   async exportExcel(payload: ExportRequest): Promise<string> {
     return invoke("export_excel", { request: payload });
   },
 
-  async exportCsv(year: number, output_path?: string | null): Promise<string> {
-    return invoke("export_csv", { year, output_path, outputPath: output_path });
+  async exportCsv(year: number, output_path?: string | null, withSubtotals = false): Promise<string> {
+    return invoke("export_csv", {
+      year,
+      output_path,
+      outputPath: output_path,
+      with_subtotals: withSubtotals,
+      withSubtotals,
+    });
+  },
+
+  async getExportStatus(year: number, month: number): Promise<ExportInfo | null> {
+    return invoke("get_export_status", { year, month });
+  },
+
+  async exportAuditLog(output_path?: string | null): Promise<string> {
+    return invoke("export_audit_log", { output_path, outputPath: output_path });
+  },
+
+  async exportReceipts(year: number, monthFrom: number, monthTo: number, outputDir: string): Promise<ReceiptExportSummary> {
+    return invoke("export_receipts", {
+      year,
+      month_from: monthFrom,
+      monthFrom,
+      month_to: monthTo,
+      monthTo,
+      output_dir: outputDir,
+      outputDir,
+    });
   },
 
   async createBackup(payload: BackupRequest): Promise<string> {
     return invoke("create_backup", { request: payload });
   },
 
-  async restoreBackup(payload: RestoreRequest): Promise<void> {
+  async restoreBackup(payload: RestoreRequest): Promise<RestoreResult> {
     return invoke("restore_backup", { request: payload });
   },
 
@@ -194,10 +395,18 @@ export const api = {
     return invoke("open_receipt", { path });
   },
 
+  async revealInFolder(path: string): Promise<void> {
+    return invoke("reveal_in_folder", { path });
+  },
+
   async readReceiptFile(path: string): Promise<{ data_base64: string; content_type: string }> {
     return invoke("read_receipt_file", { path });
   },
 
+  async validateReceipt(path: string): Promise<ReceiptInfo> {
+    return invoke("validate_receipt", { path });
+  },
+
   async readTextFile(path: string): Promise<string> {
     return invoke("read_text_file", { path });
   },
@@ -206,18 +415,66 @@ export const api = {
     return invoke("import_twint", { request });
   },
 
+  async parseBankCsv(request: BankCsvParseRequest): Promise<BankImportRow[]> {
+    return invoke("parse_bank_csv", { request });
+  },
+
+  async importBankCsv(request: BankImportRequest): Promise<BankImportSummary> {
+    return invoke("import_bank_csv", { request });
+  },
+
+  async importExcel(path: string): Promise<ExcelImportSummary> {
+    return invoke("import_excel", { path });
+  },
+
+  async getDiagnostics(): Promise<Diagnostics> {
+    return invoke("get_diagnostics");
+  },
+
+  async getSchemaVersion(): Promise<string> {
+    return invoke("get_schema_version");
+  },
+
+  async runPendingMigrations(): Promise<string[]> {
+    return invoke("run_pending_migrations");
+  },
+
   async getSyncStatus(): Promise<SyncStatus> {
     return invoke("get_sync_status");
   },
 
-  async resolveSyncConflict(action: "KEEP_LOCAL" | "USE_REMOTE" | "MERGE"): Promise<SyncStatus> {
+  async getSyncChanges(deviceId: string, limit?: number): Promise<AuditLogEntry[]> {
+    return invoke("get_sync_changes", { device_id: deviceId, deviceId, limit });
+  },
+
+  async listSyncLog(page: number, pageSize: number): Promise<Paginated<SyncLogEntry>> {
+    return invoke("list_sync_log", { page, pageSize, page_size: pageSize });
+  },
+
+  async syncPairWith(peerIp: string, code: string, port?: number): Promise<SyncStatus> {
+    return invoke("sync_pair_with", { peer_ip: peerIp, peerIp, code, port });
+  },
+
+  async syncPushTo(peerIp: string, port?: number): Promise<SyncStatus> {
+    return invoke("sync_push_to", { peer_ip: peerIp, peerIp, port });
+  },
+
+  async resolveSyncConflict(action: "KEEP_LOCAL" | "USE_REMOTE" | "MERGE" | "DISMISS"): Promise<SyncStatus> {
     return invoke("resolve_sync_conflict", { action });
   },
 
+  async restartSyncServer(port: number): Promise<SyncStatus> {
+    return invoke("restart_sync_server", { port });
+  },
+
   async pickReceipt(): Promise<string | null> {
+    const settings = await invoke<Settings>("get_settings").catch(() => null);
+    const extensions = settings?.receipt_allowed_extensions?.length
+      ? settings.receipt_allowed_extensions
+      : ["pdf", "png", "jpg", "jpeg"];
     const selected = await open({
       multiple: false,
-      filters: [{ name: "Belege", extensions: ["pdf", "png", "jpg", "jpeg"] }],
+      filters: [{ name: "Belege", extensions }],
     });
     if (Array.isArray(selected)) return selected[0] ?? null;
     return selected ?? null;
@@ -241,6 +498,15 @@ export const api = {
     return selected ?? null;
   },
 
+  async pickExcelFile(): Promise<string | null> {
+    const selected = await open({
+      multiple: false,
+      filters: [{ name: "Excel", extensions: ["xlsx"] }],
+    });
+    if (Array.isArray(selected)) return selected[0] ?? null;
+    return selected ?? null;
+  },
+
   async pickFolder(): Promise<string | null> {
     const selected = await open({ directory: true, multiple: false });
     if (Array.isArray(selected)) return selected[0] ?? null;
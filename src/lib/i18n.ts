@@ -53,6 +53,9 @@ const messages: Messages = {
     "actions.syncKeepLocal": "Laptop behalten",
     "actions.syncUseRemote": "Handy uebernehmen",
     "actions.syncMerge": "Mergen",
+    "actions.syncDismiss": "Spaeter",
+    "actions.receiptNotRequired": "Kein Beleg noetig",
+    "actions.syncRestart": "Port uebernehmen",
     "labels.activePeriod": "Aktiver Zeitraum",
     "labels.search": "Suche",
     "labels.searchPlaceholder": "Suchen...",
@@ -139,6 +142,7 @@ const messages: Messages = {
     "labels.demoDataConfirmAction": "Jetzt erzeugen",
     "labels.demoDataCreated": "Demo-Daten erstellt",
     "labels.demoDataCreatedCount": "{count} Buchungen wurden erzeugt.",
+    "labels.demoDataSkippedClosed": "{count} uebersprungen (Monat abgeschlossen).",
     "labels.demoDataFailed": "Demo-Daten fehlgeschlagen",
     "labels.demoDeleteHint": "Loescht alle Demo-Buchungen und Demo-Belege.",
     "labels.demoDeleteAction": "Demo-Daten loeschen",
@@ -149,6 +153,19 @@ const messages: Messages = {
     "labels.demoDeleteSuccessCount": "{count} Buchungen wurden geloescht.",
     "labels.demoDeleteFailed": "Loeschen fehlgeschlagen",
     "labels.receiptsFolder": "Beleg Basisordner",
+    "labels.exportFolder": "Export Ordner",
+    "labels.vatRounding": "MWST-Rundung",
+    "labels.vatRoundingPerLine": "Pro Buchung",
+    "labels.vatRoundingAggregate": "Pro Satz auf Gesamtumsatz",
+    "labels.currencyCode": "Waehrung (Export)",
+    "labels.timezone": "Zeitzone (Anzeige)",
+    "labels.auditRetentionDays": "Protokoll aufbewahren (Tage, 0 = unbegrenzt)",
+    "labels.allowNoteEditWhenClosed": "Notizen auch in abgeschlossenen Monaten bearbeiten",
+    "labels.localSyncPort": "Sync-Port",
+    "labels.localSyncRestartFailed": "Sync-Server konnte nicht neu gestartet werden",
+    "labels.receiptMaxSize": "Maximale Belegdatei (MB)",
+    "labels.receiptExtensions": "Erlaubte Belegformate",
+    "labels.receiptNotRequiredFailed": "Markierung fehlgeschlagen",
     "labels.chooseFolder": "Ordner auswählen",
     "labels.chooseBackup": "Backup auswählen",
     "labels.receipts": "Belege",
@@ -199,6 +216,7 @@ const messages: Messages = {
     "labels.importMwstIncome": "MWST Satz Einnahmen",
     "labels.importMwstFee": "MWST Satz Gebuehren",
     "labels.importSkipDuplicates": "Doppelte Buchungen ueberspringen",
+    "labels.importBookRefunds": "Rueckerstattungen als negative Einnahme buchen",
     "labels.importReady": "{count} Buchungen bereit fuer den Import.",
     "labels.importRunning": "Import laeuft...",
     "labels.importStart": "Import starten",
@@ -207,6 +225,7 @@ const messages: Messages = {
     "labels.importIncomeCount": "Einnahmen",
     "labels.importFeeCount": "Gebuehren",
     "labels.importSkipCount": "Uebersprungen",
+    "labels.importRefundCount": "Rueckerstattungen",
     "labels.importStartOver": "Neuen Import starten",
     "labels.importEmpty": "Keine gueltigen Zeilen gefunden",
     "labels.importFailed": "Import fehlgeschlagen",
@@ -290,6 +309,9 @@ const messages: Messages = {
     "labels.taxRate": "MWST %",
     "labels.taxRateOptional": "MWST % (optional)",
     "labels.taxRateStandard": "MWST Standard",
+    "labels.categoryDefaultDescription": "Standard-Beschreibung",
+    "labels.categoryDefaultPayment": "Standard-Zahlungsart",
+    "labels.categoryNoDefault": "Keine Vorgabe",
     "labels.taxFromCategory": "Kategorie-Standard",
     "labels.taxMode": "MWST-Modus",
     "labels.taxSaldoRate": "MWST-Saldo-Satz %",
@@ -316,6 +338,7 @@ const messages: Messages = {
     "labels.noMissingReceipts": "Keine fehlenden Belege in diesem Monat.",
     "labels.monthTitle": "Monatsübersicht",
     "labels.monthSubtitle": "KPIs, Trends und Buchungen des Monats.",
+    "labels.lastExport": "Zuletzt exportiert am {date}",
     "labels.yearTitle": "Jahresübersicht",
     "labels.yearSubtitle": "Performance, MWST und Monatsstatus.",
     "labels.monthTrend": "Tagesverlauf",
@@ -377,10 +400,19 @@ const messages: Messages = {
     "labels.exportMonthDone": "Monat exportiert",
     "labels.exportYearDone": "Excel Export erstellt",
     "labels.exportCsvDone": "CSV Export erstellt",
+    "labels.exportCsvSubtotals": "CSV mit Monatssummen",
     "labels.backupCreated": "Backup erstellt",
     "labels.backupFailed": "Backup fehlgeschlagen",
     "labels.restoreDone": "Restore abgeschlossen",
     "labels.restoreFailed": "Restore fehlgeschlagen",
+    "labels.restoreSummary": "Buchungen vorher {before}, nachher {after} (Jahre {years})",
+    "labels.excelImport": "Excel-Export wieder einlesen",
+    "labels.excelImportHint": "Liest die Monatsblaetter eines App-Exports ein. Bereits vorhandene Buchungen werden uebersprungen.",
+    "labels.excelImportChoose": "Excel-Datei waehlen",
+    "labels.excelImportDone": "Excel-Import abgeschlossen",
+    "labels.excelImportFailed": "Excel-Import fehlgeschlagen",
+    "labels.excelImportSummary": "{imported} importiert, {skipped} uebersprungen ({sheets} Blaetter)",
+    "labels.excelImportConflicts": "Nicht importiert, ID gehoert zu einer anderen Buchung: {ids}",
     "labels.monthClosed": "Monat abgeschlossen",
     "labels.monthCloseFailed": "Abschluss fehlgeschlagen",
     "labels.monthOpenDone": "Monat geöffnet",
@@ -483,6 +515,9 @@ const messages: Messages = {
     "actions.syncKeepLocal": "Mantieni laptop",
     "actions.syncUseRemote": "Usa telefono",
     "actions.syncMerge": "Unisci",
+    "actions.syncDismiss": "Piu tardi",
+    "actions.receiptNotRequired": "Ricevuta non necessaria",
+    "actions.syncRestart": "Applica porta",
     "labels.activePeriod": "Periodo attivo",
     "labels.search": "Ricerca",
     "labels.searchPlaceholder": "Cerca...",
@@ -569,6 +604,7 @@ const messages: Messages = {
     "labels.demoDataConfirmAction": "Crea ora",
     "labels.demoDataCreated": "Dati demo creati",
     "labels.demoDataCreatedCount": "{count} registrazioni create.",
+    "labels.demoDataSkippedClosed": "{count} saltate (mese chiuso).",
     "labels.demoDataFailed": "Creazione dati demo non riuscita",
     "labels.demoDeleteHint": "Elimina tutte le registrazioni demo e le ricevute demo.",
     "labels.demoDeleteAction": "Cancella dati demo",
@@ -579,6 +615,19 @@ const messages: Messages = {
     "labels.demoDeleteSuccessCount": "{count} registrazioni eliminate.",
     "labels.demoDeleteFailed": "Cancellazione non riuscita",
     "labels.receiptsFolder": "Cartella base ricevute",
+    "labels.exportFolder": "Cartella esportazioni",
+    "labels.vatRounding": "Arrotondamento IVA",
+    "labels.vatRoundingPerLine": "Per registrazione",
+    "labels.vatRoundingAggregate": "Per aliquota sul totale",
+    "labels.currencyCode": "Valuta (esportazione)",
+    "labels.timezone": "Fuso orario (visualizzazione)",
+    "labels.auditRetentionDays": "Conserva registro (giorni, 0 = illimitato)",
+    "labels.allowNoteEditWhenClosed": "Modifica note anche nei mesi chiusi",
+    "labels.localSyncPort": "Porta sync",
+    "labels.localSyncRestartFailed": "Impossibile riavviare il server sync",
+    "labels.receiptMaxSize": "Dimensione massima ricevuta (MB)",
+    "labels.receiptExtensions": "Formati ricevuta consentiti",
+    "labels.receiptNotRequiredFailed": "Marcatura non riuscita",
     "labels.chooseFolder": "Scegli cartella",
     "labels.chooseBackup": "Seleziona backup",
     "labels.receipts": "Ricevute",
@@ -629,6 +678,7 @@ const messages: Messages = {
     "labels.importMwstIncome": "IVA entrate",
     "labels.importMwstFee": "IVA commissioni",
     "labels.importSkipDuplicates": "Salta duplicati",
+    "labels.importBookRefunds": "Registra i rimborsi come entrata negativa",
     "labels.importReady": "{count} registrazioni pronte per l'import.",
     "labels.importRunning": "Import in corso...",
     "labels.importStart": "Avvia import",
@@ -637,6 +687,7 @@ const messages: Messages = {
     "labels.importIncomeCount": "Entrate",
     "labels.importFeeCount": "Commissioni",
     "labels.importSkipCount": "Saltati",
+    "labels.importRefundCount": "Rimborsi",
     "labels.importStartOver": "Nuovo import",
     "labels.importEmpty": "Nessuna riga valida trovata",
     "labels.importFailed": "Import non riuscito",
@@ -720,6 +771,9 @@ const messages: Messages = {
     "labels.taxRate": "IVA %",
     "labels.taxRateOptional": "IVA % (opzionale)",
     "labels.taxRateStandard": "IVA standard",
+    "labels.categoryDefaultDescription": "Descrizione predefinita",
+    "labels.categoryDefaultPayment": "Pagamento predefinito",
+    "labels.categoryNoDefault": "Nessun predefinito",
     "labels.taxFromCategory": "Standard categoria",
     "labels.taxMode": "Modalità IVA",
     "labels.taxSaldoRate": "Aliquota saldo IVA %",
@@ -746,6 +800,7 @@ const messages: Messages = {
     "labels.noMissingReceipts": "Nessuna ricevuta mancante in questo mese.",
     "labels.monthTitle": "Panoramica mese",
     "labels.monthSubtitle": "KPI, trend e registrazioni del mese.",
+    "labels.lastExport": "Ultima esportazione il {date}",
     "labels.yearTitle": "Panoramica anno",
     "labels.yearSubtitle": "Performance, IVA e stato mensile.",
     "labels.monthTrend": "Andamento giornaliero",
@@ -807,10 +862,19 @@ const messages: Messages = {
     "labels.exportMonthDone": "Mese esportato",
     "labels.exportYearDone": "Export Excel creato",
     "labels.exportCsvDone": "Export CSV creato",
+    "labels.exportCsvSubtotals": "CSV con totali mensili",
     "labels.backupCreated": "Backup creato",
     "labels.backupFailed": "Backup non riuscito",
     "labels.restoreDone": "Ripristino completato",
     "labels.restoreFailed": "Ripristino non riuscito",
+    "labels.restoreSummary": "Registrazioni prima {before}, dopo {after} (anni {years})",
+    "labels.excelImport": "Reimporta export Excel",
+    "labels.excelImportHint": "Legge i fogli mensili di un export dell'app. Le registrazioni gia presenti vengono saltate.",
+    "labels.excelImportChoose": "Scegli file Excel",
+    "labels.excelImportDone": "Import Excel completato",
+    "labels.excelImportFailed": "Import Excel non riuscito",
+    "labels.excelImportSummary": "{imported} importate, {skipped} saltate ({sheets} fogli)",
+    "labels.excelImportConflicts": "Non importate, ID appartiene a un'altra registrazione: {ids}",
     "labels.monthClosed": "Mese chiuso",
     "labels.monthCloseFailed": "Chiusura non riuscita",
     "labels.monthOpenDone": "Mese aperto",
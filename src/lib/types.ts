@@ -3,6 +3,52 @@ export interface Settings {
   mwst_mode: "EFFEKTIV" | "SALDO";
   mwst_saldo_rate: number;
   receipt_base_folder: string;
+  compress_receipts?: boolean;
+  last_viewed_year?: number;
+  receipt_naming_pattern?: string;
+  fiscal_year_start_month?: number;
+  export_base_folder?: string;
+  default_page_size?: number;
+  receipt_max_bytes?: number;
+  receipt_allowed_extensions?: string[];
+  vat_rounding_mode?: "PER_LINE" | "AGGREGATE";
+  currency_code?: string;
+  allow_note_edit_when_closed?: boolean;
+  timezone?: string;
+  audit_retention_days?: number;
+}
+
+export interface Diagnostics {
+  app_version: string;
+  data_dir: string;
+  db_path: string;
+  schema_version: string | null;
+  latest_schema_version: string;
+  db_writable: boolean;
+  sync_active: boolean;
+  sync_port: number;
+  sync_last_error: string | null;
+  receipt_folder: string;
+  receipt_folder_exists: boolean;
+}
+
+export interface EditPermission {
+  year: number;
+  month: number;
+  allowed: boolean;
+  reason: "OK" | "MONTH_CLOSED" | "YEAR_CLOSED";
+}
+
+export interface SyncLogEntry {
+  id: number;
+  ts: string;
+  event: "PULL" | "PUSH" | "MERGE" | "RESOLVE_CONFLICT";
+  direction: "OUTGOING" | "INCOMING" | null;
+  peer_device_id: string | null;
+  peer_device_name: string | null;
+  bytes: number;
+  outcome: "OK" | "FAILED" | "CONFLICT" | "SKIPPED";
+  message: string | null;
 }
 
 export interface SyncStatus {
@@ -13,6 +59,25 @@ export interface SyncStatus {
   last_change: string;
   paired_devices: SyncDeviceInfo[];
   pending_conflict?: SyncConflictInfo | null;
+  last_error?: string | null;
+}
+
+export interface DataChanged {
+  entity: "TRANSACTION" | "CATEGORY" | "SETTINGS" | "MONTH" | "YEAR" | "ALL";
+  year?: number | null;
+  month?: number | null;
+}
+
+export type SyncProgressDirection = "OUTGOING" | "INCOMING";
+
+export type SyncProgressPhase = "CREATING_BACKUP" | "TRANSFERRING" | "APPLYING" | "DONE" | "FAILED";
+
+export interface SyncProgress {
+  direction: SyncProgressDirection;
+  phase: SyncProgressPhase;
+  device_name?: string | null;
+  bytes_done: number;
+  bytes_total?: number | null;
 }
 
 export interface SyncDeviceInfo {
@@ -53,12 +118,21 @@ export interface Category {
   description?: string | null;
   default_mwst_rate: number;
   is_active: boolean;
+  default_description?: string | null;
+  default_payment_method?: "BAR" | "TWINT" | null;
+}
+
+export interface CategoryStat extends Category {
+  tx_count: number;
+  total_amount: number;
 }
 
 export interface CategoryInput {
   name: string;
   description?: string | null;
   default_mwst_rate: number;
+  default_description?: string | null;
+  default_payment_method?: "BAR" | "TWINT" | null;
 }
 
 export interface CategoryUpdateInput {
@@ -67,6 +141,15 @@ export interface CategoryUpdateInput {
   description?: string | null;
   default_mwst_rate: number;
   is_active: boolean;
+  default_description?: string | null;
+  default_payment_method?: "BAR" | "TWINT" | null;
+}
+
+export interface DuplicateMatch {
+  public_id: string;
+  date: string;
+  amount_chf: number;
+  note?: string | null;
 }
 
 export interface TransactionListItem {
@@ -82,12 +165,23 @@ export interface TransactionListItem {
   description?: string | null;
   amount_chf: number;
   mwst_rate: number;
+  mwst_chf: number;
   receipt_path?: string | null;
   note?: string | null;
   ref_public_id?: string | null;
   created_at: string;
   updated_at: string;
   is_stornoed: boolean;
+  created_by?: string | null;
+  updated_by?: string | null;
+  receipt_not_required: boolean;
+}
+
+export interface TxRelations {
+  public_id: string;
+  ref_public_id?: string | null;
+  original?: TransactionListItem | null;
+  stornos: TransactionListItem[];
 }
 
 export interface MonthKpis {
@@ -102,6 +196,8 @@ export interface MonthKpis {
   mwst_due: number;
   missing_receipts_count: number;
   missing_receipts_sum: number;
+  income_count: number;
+  expense_count: number;
 }
 
 export type YearKpis = MonthKpis;
@@ -122,6 +218,11 @@ export interface CategorySplit {
   amount: number;
 }
 
+export interface MonthAmountPoint {
+  month: number;
+  amount: number;
+}
+
 export interface MonthSeriesPoint {
   month: number;
   income: number;
@@ -135,18 +236,59 @@ export interface MonthCharts {
   categories: CategorySplit[];
 }
 
+export interface MonthDashboard {
+  kpis: MonthKpis;
+  charts: MonthCharts;
+  status: MonthStatus;
+}
+
 export interface YearCharts {
   monthly: MonthSeriesPoint[];
   payments: PaymentSplit[];
   categories: CategorySplit[];
 }
 
+export interface MwstComparison {
+  year: number;
+  active_mode: "EFFEKTIV" | "SALDO";
+  saldo_rate: number;
+  effective_due: number;
+  saldo_due: number;
+  difference: number;
+  cheaper_mode: "EFFEKTIV" | "SALDO";
+}
+
+export interface MwstModePreview {
+  year: number;
+  current_mode: "EFFEKTIV" | "SALDO";
+  current_mwst_due: number;
+  proposed_mode: "EFFEKTIV" | "SALDO";
+  proposed_mwst_due: number;
+  difference: number;
+}
+
+export interface TicketStats {
+  count: number;
+  average: number;
+  median: number;
+  min: number;
+  max: number;
+}
+
+export interface BrokenReceipt {
+  public_id: string;
+  date: string;
+  receipt_path: string;
+}
+
 export interface MonthStatus {
   year: number;
   month: number;
   is_closed: boolean;
   closed_at?: string | null;
   closed_by?: string | null;
+  comment?: string | null;
+  year_closed: boolean;
 }
 
 export interface AuditLogEntry {
@@ -166,11 +308,21 @@ export interface Paginated<T> {
   items: T[];
 }
 
+export interface ExportInfo {
+  year: number;
+  month: number;
+  kind: "EXCEL" | "CSV";
+  output_path: string;
+  exported_at: string;
+  exported_by?: string | null;
+}
+
 export interface ExportRequest {
   year: number;
   month?: number | null;
   month_from?: number | null;
   month_to?: number | null;
+  years?: number[] | null;
   output_path?: string | null;
   actor?: string | null;
 }
@@ -186,6 +338,15 @@ export interface RestoreRequest {
   actor?: string | null;
 }
 
+export interface RestoreResult {
+  tx_count_before: number;
+  tx_count_after: number;
+  year_min: number | null;
+  year_max: number | null;
+  backup_schema_version: string | null;
+  schema_version: string | null;
+}
+
 export interface TwintImportRow {
   date: string;
   amount_chf: number;
@@ -199,11 +360,90 @@ export interface TwintImportRequest {
   income_mwst_rate: number;
   fee_mwst_rate: number;
   skip_duplicates?: boolean;
+  book_refunds?: boolean;
+  actor?: string | null;
+}
+
+export interface MockSeedSummary {
+  count: number;
+  income_count: number;
+  expense_count: number;
+  receipt_count: number;
+  skipped_closed?: number;
+  seed: number;
+  dry_run: boolean;
+}
+
+export interface BankImportRow {
+  date: string;
+  amount_chf: number;
+  description?: string;
+}
+
+export interface BankCsvParseRequest {
+  path: string;
+  date_column: string;
+  amount_column: string;
+  description_column?: string | null;
+}
+
+export interface BankImportRequest {
+  rows: BankImportRow[];
+  category_id: number;
+  income_payment_method?: "BAR" | "TWINT";
+  income_mwst_rate?: number;
+  expense_mwst_rate?: number;
+  skip_duplicates?: boolean;
   actor?: string | null;
 }
 
+export interface BankImportSummary {
+  income_created: number;
+  expense_created: number;
+  skipped_duplicates: number;
+}
+
+export interface ExcelImportSheetSummary {
+  sheet: string;
+  year: number;
+  month: number;
+  imported: number;
+  skipped: number;
+  closed: boolean;
+}
+
+export interface ExcelImportSummary {
+  imported: number;
+  skipped: number;
+  conflicts: string[];
+  sheets: ExcelImportSheetSummary[];
+}
+
 export interface TwintImportSummary {
   income_created: number;
   fee_created: number;
   skipped_duplicates: number;
+  refunds_skipped: number;
+  refunds_booked: number;
+}
+
+export interface ReceiptRelinkSummary {
+  relinked: number;
+  moved: number;
+  broken: number;
+  broken_public_ids: string[];
+}
+
+export interface ReceiptInfo {
+  path: string;
+  extension: string;
+  content_type: string;
+  size_bytes: number;
+}
+
+export interface ReceiptExportSummary {
+  output_dir: string;
+  exported: number;
+  missing: number;
+  missing_public_ids: string[];
 }
@@ -5,6 +5,7 @@ mod commands;
 mod db;
 mod domain;
 mod error;
+mod events;
 mod export;
 mod files;
 mod models;
@@ -25,9 +26,20 @@ pub struct AppState {
 }
 
 fn main() {
-  let app_dir = db::resolve_app_dir().expect("Failed to resolve app data directory");
+  let app_dir = db::resolve_app_dir_with_args(std::env::args().skip(1)).expect("Failed to resolve app data directory");
   let sync_dir = app_dir.clone();
   let (db, receipt_base) = db::init_db(&app_dir).expect("Failed to initialize database");
+  let _ = db::with_conn(&db, |conn| {
+    let days = settings::get_audit_retention_days(conn)?;
+    if days > 0 {
+      audit::log::purge_audit_log(conn, days, Some("system".to_string()))?;
+    }
+    Ok(())
+  });
+  let sync_port = db::with_conn(&db, |conn| settings::get_sync_port(conn))
+    .ok()
+    .flatten()
+    .unwrap_or(48080);
 
   tauri::Builder::default()
     .plugin(tauri_plugin_dialog::init())
@@ -35,7 +47,7 @@ fn main() {
       db,
       app_dir,
       receipt_base,
-      sync: SyncState::new(48080, sync_dir),
+      sync: SyncState::new(sync_port, sync_dir),
     })
     .setup(|app| {
       sync::start_sync_server(app.handle().clone());
@@ -44,37 +56,87 @@ fn main() {
     .invoke_handler(tauri::generate_handler![
       commands::get_settings,
       commands::update_settings,
+      commands::format_timestamps,
+      commands::set_last_viewed_year,
       commands::list_categories,
+      commands::list_categories_with_stats,
       commands::create_category,
       commands::update_category,
       commands::deactivate_category,
+      commands::import_categories_from_backup,
+      commands::reassign_category,
+      commands::get_category_default_mwst,
       commands::create_income,
       commands::create_expense,
       commands::create_storno,
       commands::delete_transaction,
+      commands::update_note,
+      commands::update_payment_method,
+      commands::set_receipt_not_required,
+      commands::relink_receipts,
       commands::list_transactions,
       commands::search_transactions,
+      commands::get_transaction,
+      commands::get_transaction_relations,
       commands::search_transactions_paginated,
       commands::get_month_kpis,
       commands::get_year_kpis,
+      commands::get_fiscal_year_kpis,
+      commands::get_daterange_kpis,
+      commands::get_mwst_comparison,
+      commands::preview_mwst_mode,
+      commands::get_fiscal_month_series,
+      commands::get_category_trend,
       commands::get_month_charts,
+      commands::get_month_dashboard,
       commands::get_year_charts,
+      commands::get_storno_report,
+      commands::get_largest_transactions,
+      commands::get_ticket_stats,
+      commands::list_broken_receipts,
       commands::get_month_status,
+      commands::can_edit_period,
       commands::close_month,
       commands::open_month,
+      commands::close_months,
+      commands::open_months,
+      commands::close_year,
+      commands::open_year,
+      commands::rollover_year,
       commands::list_audit_log,
+      commands::purge_audit_log,
+      commands::get_transaction_audit,
       commands::seed_mock_data,
       commands::clear_demo_data,
+      commands::factory_reset,
       commands::export_excel,
       commands::export_csv,
+      commands::get_export_status,
+      commands::export_audit_log,
+      commands::export_receipts,
       commands::create_backup,
       commands::restore_backup,
       commands::open_receipt,
+      commands::reveal_in_folder,
+      commands::attach_receipts_from_folder,
       commands::read_receipt_file,
+      commands::validate_receipt,
       commands::read_text_file,
       commands::import_twint,
+      commands::parse_bank_csv,
+      commands::import_bank_csv,
+      commands::import_excel,
+      commands::get_storage_stats,
+      commands::get_diagnostics,
+      commands::get_schema_version,
+      commands::run_pending_migrations,
       commands::get_sync_status,
+      commands::get_sync_changes,
+      commands::list_sync_log,
+      commands::sync_pair_with,
+      commands::sync_push_to,
       commands::resolve_sync_conflict,
+      commands::restart_sync_server,
     ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
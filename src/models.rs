@@ -6,6 +6,32 @@ pub struct Settings {
   pub mwst_mode: String,
   pub mwst_saldo_rate: f64,
   pub receipt_base_folder: String,
+  #[serde(default)]
+  pub compress_receipts: bool,
+  #[serde(default)]
+  pub last_viewed_year: i32,
+  #[serde(default)]
+  pub receipt_naming_pattern: String,
+  #[serde(default)]
+  pub fiscal_year_start_month: i32,
+  #[serde(default)]
+  pub export_base_folder: String,
+  #[serde(default)]
+  pub default_page_size: i64,
+  #[serde(default)]
+  pub receipt_max_bytes: u64,
+  #[serde(default)]
+  pub receipt_allowed_extensions: Vec<String>,
+  #[serde(default)]
+  pub vat_rounding_mode: String,
+  #[serde(default)]
+  pub currency_code: String,
+  #[serde(default)]
+  pub allow_note_edit_when_closed: bool,
+  #[serde(default)]
+  pub timezone: String,
+  #[serde(default)]
+  pub audit_retention_days: i64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -17,6 +43,23 @@ pub struct SyncStatus {
   pub last_change: String,
   pub paired_devices: Vec<SyncDeviceInfo>,
   pub pending_conflict: Option<SyncConflictInfo>,
+  pub last_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DataChanged {
+  pub entity: String,
+  pub year: Option<i32>,
+  pub month: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncProgress {
+  pub direction: String,
+  pub phase: String,
+  pub device_name: Option<String>,
+  pub bytes_done: u64,
+  pub bytes_total: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -28,6 +71,19 @@ pub struct SyncDeviceInfo {
   pub last_known_ip: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncLogEntry {
+  pub id: i64,
+  pub ts: String,
+  pub event: String,
+  pub direction: Option<String>,
+  pub peer_device_id: Option<String>,
+  pub peer_device_name: Option<String>,
+  pub bytes: i64,
+  pub outcome: String,
+  pub message: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SyncConflictItem {
   pub date: String,
@@ -62,6 +118,19 @@ pub struct Category {
   pub description: Option<String>,
   pub default_mwst_rate: f64,
   pub is_active: bool,
+  pub default_description: Option<String>,
+  pub default_payment_method: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CategoryStat {
+  pub id: i64,
+  pub name: String,
+  pub description: Option<String>,
+  pub default_mwst_rate: f64,
+  pub is_active: bool,
+  pub tx_count: i64,
+  pub total_amount: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -69,6 +138,8 @@ pub struct CategoryInput {
   pub name: String,
   pub description: Option<String>,
   pub default_mwst_rate: f64,
+  pub default_description: Option<String>,
+  pub default_payment_method: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -78,6 +149,8 @@ pub struct CategoryUpdateInput {
   pub description: Option<String>,
   pub default_mwst_rate: f64,
   pub is_active: bool,
+  pub default_description: Option<String>,
+  pub default_payment_method: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -95,6 +168,7 @@ pub struct NewExpenseInput {
   pub date: String,
   pub category_id: i64,
   pub description: Option<String>,
+  pub payment_method: Option<String>,
   pub amount_chf: f64,
   pub mwst_rate: Option<f64>,
   pub receipt_source_path: Option<String>,
@@ -102,12 +176,21 @@ pub struct NewExpenseInput {
   pub allow_duplicate: Option<bool>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DuplicateMatch {
+  pub public_id: String,
+  pub date: String,
+  pub amount_chf: f64,
+  pub note: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StornoInput {
   pub public_id: String,
   pub date: String,
   pub amount_chf: Option<f64>,
   pub reason: String,
+  pub allow_cross_period: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -125,12 +208,16 @@ pub struct TransactionListItem {
   pub description: Option<String>,
   pub amount_chf: f64,
   pub mwst_rate: f64,
+  pub mwst_chf: f64,
   pub receipt_path: Option<String>,
   pub note: Option<String>,
   pub ref_public_id: Option<String>,
   pub created_at: String,
   pub updated_at: String,
   pub is_stornoed: bool,
+  pub created_by: Option<String>,
+  pub updated_by: Option<String>,
+  pub receipt_not_required: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -141,6 +228,13 @@ pub struct TransactionFilter {
   pub page: i64,
   pub page_size: i64,
   pub search: Option<String>,
+  pub ignore_period: Option<bool>,
+  pub min_amount: Option<f64>,
+  pub max_amount: Option<f64>,
+  pub date_from: Option<String>,
+  pub date_to: Option<String>,
+  pub sort_by: Option<String>,
+  pub sort_dir: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -150,7 +244,7 @@ pub struct Paginated<T> {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct MonthKpis {
+pub struct Kpis {
   pub income_total: f64,
   pub income_bar: f64,
   pub income_twint: f64,
@@ -162,22 +256,12 @@ pub struct MonthKpis {
   pub mwst_due: f64,
   pub missing_receipts_count: i64,
   pub missing_receipts_sum: f64,
+  pub income_count: i64,
+  pub expense_count: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct YearKpis {
-  pub income_total: f64,
-  pub income_bar: f64,
-  pub income_twint: f64,
-  pub expense_total: f64,
-  pub result: f64,
-  pub margin: f64,
-  pub mwst_income: f64,
-  pub mwst_expense: f64,
-  pub mwst_due: f64,
-  pub missing_receipts_count: i64,
-  pub missing_receipts_sum: f64,
-}
+pub type MonthKpis = Kpis;
+pub type YearKpis = Kpis;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DailySeriesPoint {
@@ -198,6 +282,12 @@ pub struct CategorySplit {
   pub amount: f64,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MonthAmountPoint {
+  pub month: i32,
+  pub amount: f64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MonthSeriesPoint {
   pub month: i32,
@@ -206,6 +296,65 @@ pub struct MonthSeriesPoint {
   pub result: f64,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MwstComparison {
+  pub year: i32,
+  pub active_mode: String,
+  pub saldo_rate: f64,
+  pub effective_due: f64,
+  pub saldo_due: f64,
+  pub difference: f64,
+  pub cheaper_mode: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MwstModePreview {
+  pub year: i32,
+  pub current_mode: String,
+  pub current_mwst_due: f64,
+  pub proposed_mode: String,
+  pub proposed_mwst_due: f64,
+  pub difference: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TicketStats {
+  pub count: i64,
+  pub average: f64,
+  pub median: f64,
+  pub min: f64,
+  pub max: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BrokenReceipt {
+  pub public_id: String,
+  pub date: String,
+  pub receipt_path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TxRelations {
+  pub public_id: String,
+  pub ref_public_id: Option<String>,
+  pub original: Option<TransactionListItem>,
+  pub stornos: Vec<TransactionListItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StornoPair {
+  pub storno_public_id: String,
+  pub storno_date: String,
+  pub tx_type: String,
+  pub storno_amount: f64,
+  pub original_public_id: String,
+  pub original_date: Option<String>,
+  pub original_amount: Option<f64>,
+  pub reason: Option<String>,
+  pub has_original: bool,
+  pub storno_count: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MonthStatus {
   pub year: i32,
@@ -213,6 +362,16 @@ pub struct MonthStatus {
   pub is_closed: bool,
   pub closed_at: Option<String>,
   pub closed_by: Option<String>,
+  pub comment: Option<String>,
+  pub year_closed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EditPermission {
+  pub year: i32,
+  pub month: i32,
+  pub allowed: bool,
+  pub reason: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -222,6 +381,13 @@ pub struct MonthCharts {
   pub categories: Vec<CategorySplit>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MonthDashboard {
+  pub kpis: MonthKpis,
+  pub charts: MonthCharts,
+  pub status: MonthStatus,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct YearCharts {
   pub monthly: Vec<MonthSeriesPoint>,
@@ -229,6 +395,37 @@ pub struct YearCharts {
   pub categories: Vec<CategorySplit>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct YearTransactionCount {
+  pub year: i32,
+  pub count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Diagnostics {
+  pub app_version: String,
+  pub data_dir: String,
+  pub db_path: String,
+  pub schema_version: Option<String>,
+  pub latest_schema_version: String,
+  pub db_writable: bool,
+  pub sync_active: bool,
+  pub sync_port: u16,
+  pub sync_last_error: Option<String>,
+  pub receipt_folder: String,
+  pub receipt_folder_exists: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StorageStats {
+  pub db_bytes: u64,
+  pub wal_bytes: u64,
+  pub receipt_bytes: u64,
+  pub receipt_files: i64,
+  pub transaction_count: i64,
+  pub per_year: Vec<YearTransactionCount>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AuditLogEntry {
   pub id: i64,
@@ -243,11 +440,23 @@ pub struct AuditLogEntry {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+pub struct ExportInfo {
+  pub year: i32,
+  pub month: i32,
+  pub kind: String,
+  pub output_path: String,
+  pub exported_at: String,
+  pub exported_by: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ExportRequest {
   pub year: i32,
   pub month: Option<i32>,
   pub month_from: Option<i32>,
   pub month_to: Option<i32>,
+  #[serde(default)]
+  pub years: Option<Vec<i32>>,
   pub output_path: Option<String>,
   pub actor: Option<String>,
 }
@@ -265,6 +474,16 @@ pub struct RestoreRequest {
   pub actor: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RestoreResult {
+  pub tx_count_before: i64,
+  pub tx_count_after: i64,
+  pub year_min: Option<i32>,
+  pub year_max: Option<i32>,
+  pub backup_schema_version: Option<String>,
+  pub schema_version: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TwintImportRow {
   pub date: String,
@@ -280,12 +499,112 @@ pub struct TwintImportRequest {
   pub income_mwst_rate: f64,
   pub fee_mwst_rate: f64,
   pub skip_duplicates: Option<bool>,
+  pub book_refunds: Option<bool>,
+  pub actor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MockSeedSummary {
+  pub count: i64,
+  pub income_count: i64,
+  pub expense_count: i64,
+  pub receipt_count: i64,
+  #[serde(default)]
+  pub skipped_closed: i64,
+  pub seed: u64,
+  pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BankImportRow {
+  pub date: String,
+  pub amount_chf: f64,
+  pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BankCsvParseRequest {
+  pub path: String,
+  pub date_column: String,
+  pub amount_column: String,
+  pub description_column: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BankImportRequest {
+  pub rows: Vec<BankImportRow>,
+  pub category_id: i64,
+  pub income_payment_method: Option<String>,
+  pub income_mwst_rate: Option<f64>,
+  pub expense_mwst_rate: Option<f64>,
+  pub skip_duplicates: Option<bool>,
   pub actor: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BankImportSummary {
+  pub income_created: i64,
+  pub expense_created: i64,
+  pub skipped_duplicates: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExcelImportSheetSummary {
+  pub sheet: String,
+  pub year: i32,
+  pub month: i32,
+  pub imported: i64,
+  pub skipped: i64,
+  pub closed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExcelImportSummary {
+  pub imported: i64,
+  pub skipped: i64,
+  // IDs held by a different booking than the imported row; those rows are not imported.
+  pub conflicts: Vec<String>,
+  pub sheets: Vec<ExcelImportSheetSummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceiptAttachSummary {
+  pub attached: i64,
+  pub matched: Vec<String>,
+  pub unmatched: Vec<String>,
+  pub skipped_closed: Vec<String>,
+  pub skipped_existing: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TwintImportSummary {
   pub income_created: i64,
   pub fee_created: i64,
   pub skipped_duplicates: i64,
+  pub refunds_skipped: i64,
+  pub refunds_booked: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceiptInfo {
+  pub path: String,
+  pub extension: String,
+  pub content_type: String,
+  pub size_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceiptExportSummary {
+  pub output_dir: String,
+  pub exported: i64,
+  pub missing: i64,
+  pub missing_public_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceiptRelinkSummary {
+  pub relinked: i64,
+  pub moved: i64,
+  pub broken: i64,
+  pub broken_public_ids: Vec<String>,
 }
@@ -10,6 +10,7 @@ import { Card, CardBody, CardHeader, CardTitle } from "../components/ui/Card";
 import { ConfirmDialog } from "../components/ui/ConfirmDialog";
 import { Input } from "../components/ui/Input";
 import { Modal } from "../components/ui/Modal";
+import { Select } from "../components/ui/Select";
 import { Table, TableCell, TableHead, TableHeaderCell, TableRow } from "../components/ui/Table";
 import { parseDecimalInput } from "../lib/parse";
 
@@ -19,6 +20,8 @@ const emptyCategory: Category = {
   description: "",
   default_mwst_rate: 0,
   is_active: true,
+  default_description: "",
+  default_payment_method: null,
 };
 
 export function CategoriesPage() {
@@ -50,6 +53,8 @@ export function CategoriesPage() {
           name: editing.name,
           description: editing.description,
           default_mwst_rate: editing.default_mwst_rate,
+          default_description: editing.default_description,
+          default_payment_method: editing.default_payment_method,
         });
         addToast({ title: t("labels.categoryCreateSuccess"), variant: "success" });
       } else {
@@ -59,6 +64,8 @@ export function CategoriesPage() {
           description: editing.description,
           default_mwst_rate: editing.default_mwst_rate,
           is_active: editing.is_active,
+          default_description: editing.default_description,
+          default_payment_method: editing.default_payment_method,
         });
         addToast({ title: t("labels.categoryUpdateSuccess"), variant: "success" });
       }
@@ -213,6 +220,25 @@ export function CategoriesPage() {
               setEditing({ ...editing, default_mwst_rate: parsed ?? 0 });
             }}
           />
+          <Input
+            label={t("labels.categoryDefaultDescription")}
+            value={editing.default_description ?? ""}
+            onChange={(event) => setEditing({ ...editing, default_description: event.target.value })}
+          />
+          <Select
+            label={t("labels.categoryDefaultPayment")}
+            value={editing.default_payment_method ?? ""}
+            onChange={(event) =>
+              setEditing({
+                ...editing,
+                default_payment_method: (event.target.value || null) as Category["default_payment_method"],
+              })
+            }
+          >
+            <option value="">{t("labels.categoryNoDefault")}</option>
+            <option value="BAR">{t("labels.paymentBar")}</option>
+            <option value="TWINT">{t("labels.paymentTwint")}</option>
+          </Select>
           <div className="flex items-center gap-2">
             <input
               type="checkbox"
@@ -170,6 +170,9 @@ export function ExpensePage() {
       const selected = categories.find((category) => category.id === nextId);
       if (selected) {
         setValue("mwst_rate", String(selected.default_mwst_rate));
+        if (selected.default_description && !getValues("description")) {
+          setValue("description", selected.default_description);
+        }
       }
     },
   });
@@ -374,7 +377,9 @@ export function ExpensePage() {
                   onClick={async () => {
                     try {
                       const selected = await api.pickReceipt();
-                      if (selected) setReceiptPath(selected);
+                      if (!selected) return;
+                      await api.validateReceipt(selected);
+                      setReceiptPath(selected);
                     } catch (error) {
                       const parsed = parseInvokeError(error);
                       addToast({
@@ -15,6 +15,7 @@ export function ExportPage() {
   const { t, monthNamesLong } = useI18n();
   const addToast = useToastStore((state) => state.addToast);
   const [includeReceipts, setIncludeReceipts] = useState(true);
+  const [csvSubtotals, setCsvSubtotals] = useState(false);
   const [restorePath, setRestorePath] = useState<string | null>(null);
   const [confirmRestore, setConfirmRestore] = useState(false);
   const [rangeFrom, setRangeFrom] = useState(month);
@@ -54,7 +55,7 @@ export function ExportPage() {
     try {
       const savePath = await api.pickSavePath(`export_${year}.csv`);
       if (!savePath) return;
-      const path = await api.exportCsv(year, savePath);
+      const path = await api.exportCsv(year, savePath, csvSubtotals);
       addToast({ title: t("labels.exportCsvDone"), description: path, variant: "success" });
     } catch (error) {
       const parsed = parseInvokeError(error);
@@ -101,6 +102,14 @@ export function ExportPage() {
               {t("labels.exportCsvYear", { year })}
             </Button>
           </div>
+          <div className="flex items-center gap-2 text-sm">
+            <input
+              type="checkbox"
+              checked={csvSubtotals}
+              onChange={(event) => setCsvSubtotals(event.target.checked)}
+            />
+            <span>{t("labels.exportCsvSubtotals")}</span>
+          </div>
           <div className="grid items-end gap-3 md:grid-cols-[1fr_1fr_auto]">
             <Select
               label={t("labels.exportRangeFrom")}
@@ -183,6 +192,46 @@ export function ExportPage() {
         </CardBody>
       </Card>
 
+      <Card>
+        <CardHeader>
+          <CardTitle>{t("labels.excelImport")}</CardTitle>
+        </CardHeader>
+        <CardBody className="space-y-3">
+          <div className="text-sm text-app-neutral">{t("labels.excelImportHint")}</div>
+          <Button
+            variant="secondary"
+            onClick={async () => {
+              const path = await api.pickExcelFile();
+              if (!path) return;
+              try {
+                const summary = await api.importExcel(path);
+                addToast({
+                  title: t("labels.excelImportDone"),
+                  description: t("labels.excelImportSummary", {
+                    imported: summary.imported,
+                    skipped: summary.skipped,
+                    sheets: summary.sheets.length,
+                  }),
+                  variant: "success",
+                });
+                if (summary.conflicts.length > 0) {
+                  addToast({
+                    title: t("labels.excelImport"),
+                    description: t("labels.excelImportConflicts", { ids: summary.conflicts.join(", ") }),
+                    variant: "warning",
+                  });
+                }
+              } catch (error) {
+                const parsed = parseInvokeError(error);
+                addToast({ title: t("labels.excelImportFailed"), description: parsed.message, variant: "danger" });
+              }
+            }}
+          >
+            {t("labels.excelImportChoose")}
+          </Button>
+        </CardBody>
+      </Card>
+
       <ConfirmDialog
         open={confirmRestore}
         title={t("labels.backupRestore")}
@@ -191,8 +240,22 @@ export function ExportPage() {
         onConfirm={async () => {
           if (!restorePath) return;
           try {
-            await api.restoreBackup({ archive_path: restorePath });
-            addToast({ title: t("labels.restoreDone"), variant: "success" });
+            const result = await api.restoreBackup({ archive_path: restorePath });
+            const years =
+              result.year_min !== null && result.year_max !== null
+                ? result.year_min === result.year_max
+                  ? String(result.year_min)
+                  : `${result.year_min}-${result.year_max}`
+                : "-";
+            addToast({
+              title: t("labels.restoreDone"),
+              description: t("labels.restoreSummary", {
+                before: result.tx_count_before,
+                after: result.tx_count_after,
+                years,
+              }),
+              variant: "success",
+            });
             setConfirmRestore(false);
             setRestorePath(null);
           } catch (error) {
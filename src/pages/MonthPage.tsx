@@ -20,7 +20,7 @@ import {
 import { api, parseInvokeError } from "../lib/api";
 import { formatCHF, formatDate, formatIsoDate, formatPercent } from "../lib/format";
 import { useI18n } from "../lib/i18n";
-import type { MonthCharts, MonthKpis, Paginated, TransactionListItem } from "../lib/types";
+import type { ExportInfo, MonthCharts, MonthKpis, Paginated, TransactionListItem } from "../lib/types";
 import { useAppStore } from "../state/appStore";
 import { useToastStore } from "../state/toastStore";
 import { Badge } from "../components/ui/Badge";
@@ -52,12 +52,13 @@ export function MonthPage() {
   const [expenseData, setExpenseData] = useState<Paginated<TransactionListItem> | null>(null);
   const [selectedTx, setSelectedTx] = useState<TransactionListItem | null>(null);
   const [confirmStorno, setConfirmStorno] = useState(false);
+  const [stornoWarning, setStornoWarning] = useState<string | null>(null);
   const [confirmDelete, setConfirmDelete] = useState(false);
+  const [exportInfo, setExportInfo] = useState<ExportInfo | null>(null);
 
   const reload = useCallback((search: string) => {
     Promise.all([
-      api.getMonthKpis(year, month),
-      api.getMonthCharts(year, month),
+      api.getMonthDashboard(year, month),
       api.listTransactions({
         year,
         month,
@@ -74,12 +75,14 @@ export function MonthPage() {
         page_size: 200,
         search,
       }),
+      api.getExportStatus(year, month),
     ])
-      .then(([kpiData, chartData, incomeList, expenseList]) => {
-        setKpis(kpiData);
-        setCharts(chartData);
+      .then(([dashboard, incomeList, expenseList, exportStatus]) => {
+        setKpis(dashboard.kpis);
+        setCharts(dashboard.charts);
         setIncomeData(incomeList);
         setExpenseData(expenseList);
+        setExportInfo(exportStatus);
       })
       .catch((error) => {
         addToast({
@@ -123,6 +126,11 @@ export function MonthPage() {
         <div>
           <h1 className="page-title">{t("labels.monthTitle")}</h1>
           <p className="page-subtitle">{t("labels.monthSubtitle")}</p>
+          {exportInfo && (
+            <div className="mt-2 text-xs text-app-neutral">
+              {t("labels.lastExport", { date: exportInfo.exported_at.slice(0, 10) })}
+            </div>
+          )}
           {hasSearch && <div className="mt-2 text-xs text-app-neutral">{t("labels.filterActive", { query: globalSearch })}</div>}
         </div>
         <div className="flex flex-wrap gap-2">
@@ -428,7 +436,7 @@ export function MonthPage() {
       <ConfirmDialog
         open={confirmStorno}
         title={t("labels.stornoCreate")}
-        description={t("labels.stornoConfirm")}
+        description={stornoWarning ?? t("labels.stornoConfirm")}
         confirmLabel={t("labels.stornoConfirmAction")}
         onConfirm={async () => {
           if (!selectedTx) return;
@@ -437,13 +445,19 @@ export function MonthPage() {
               public_id: selectedTx.public_id,
               date: formatIsoDate(new Date()),
               reason: "Korrektur",
+              allow_cross_period: stornoWarning !== null,
             });
             addToast({ title: t("labels.stornoCreated"), variant: "success" });
             setConfirmStorno(false);
+            setStornoWarning(null);
             setSelectedTx(null);
             reload(globalSearch);
           } catch (error) {
             const parsed = parseInvokeError(error);
+            if (parsed.code === "STORNO_PERIOD_WARNING") {
+              setStornoWarning(parsed.message);
+              return;
+            }
             addToast({
               title: t("labels.stornoFailed"),
               description: parsed.message,
@@ -451,7 +465,10 @@ export function MonthPage() {
             });
           }
         }}
-        onCancel={() => setConfirmStorno(false)}
+        onCancel={() => {
+          setConfirmStorno(false);
+          setStornoWarning(null);
+        }}
       />
       <ConfirmDialog
         open={confirmDelete}
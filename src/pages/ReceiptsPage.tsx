@@ -44,7 +44,22 @@ export function ReceiptsPage() {
     return () => clearTimeout(timeout);
   }, [year, month, globalSearch, addToast, t]);
 
-  const missing = data?.items.filter((item) => !item.receipt_path && item.amount_chf > 0) ?? [];
+  const missing =
+    data?.items.filter((item) => !item.receipt_path && !item.receipt_not_required && item.amount_chf > 0) ?? [];
+
+  const markNotRequired = async (publicId: string) => {
+    try {
+      const updated = await api.setReceiptNotRequired(publicId, true);
+      setData((current) =>
+        current
+          ? { ...current, items: current.items.map((item) => (item.public_id === publicId ? updated : item)) }
+          : current
+      );
+    } catch (error) {
+      const parsed = parseInvokeError(error);
+      addToast({ title: t("labels.receiptNotRequiredFailed"), description: parsed.message, variant: "danger" });
+    }
+  };
   const hasSearch = globalSearch.trim().length > 0;
   const previewSrc = previewData?.src ?? null;
 
@@ -67,12 +82,13 @@ export function ReceiptsPage() {
                 <TableHeaderCell>{t("labels.tableDate")}</TableHeaderCell>
                 <TableHeaderCell>{t("labels.tableCategory")}</TableHeaderCell>
                 <TableHeaderCell>{t("labels.tableAmount")}</TableHeaderCell>
+                <TableHeaderCell></TableHeaderCell>
               </TableRow>
             </TableHead>
             <tbody>
               {missing.length === 0 && (
                 <TableRow>
-                  <TableCell colSpan={4} className="text-sm text-app-neutral">
+                  <TableCell colSpan={5} className="text-sm text-app-neutral">
                     {t("labels.noMissingReceipts")}
                   </TableCell>
                 </TableRow>
@@ -83,6 +99,11 @@ export function ReceiptsPage() {
                   <TableCell>{formatDate(tx.date, locale)}</TableCell>
                   <TableCell>{tx.category_name ?? "-"}</TableCell>
                   <TableCell>{formatCHF(tx.amount_chf, locale)}</TableCell>
+                  <TableCell>
+                    <Button variant="ghost" size="sm" onClick={() => markNotRequired(tx.public_id)}>
+                      {t("actions.receiptNotRequired")}
+                    </Button>
+                  </TableCell>
                 </TableRow>
               ))}
             </tbody>
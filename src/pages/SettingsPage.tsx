@@ -46,6 +46,7 @@ export function SettingsPage() {
   const [clearBusy, setClearBusy] = useState(false);
   const [syncStatus, setSyncStatus] = useState<SyncStatus | null>(null);
   const [syncBusy, setSyncBusy] = useState(false);
+  const [syncPort, setSyncPort] = useState("");
 
   const formatTimestamp = (value?: string | null) => {
     if (!value) return "-";
@@ -141,6 +142,37 @@ export function SettingsPage() {
             <option value="EFFEKTIV">{t("labels.taxModeEffective")}</option>
             <option value="SALDO">{t("labels.taxModeSaldo")}</option>
           </Select>
+          <Select
+            label={t("labels.vatRounding")}
+            value={form.vat_rounding_mode ?? "PER_LINE"}
+            onChange={(event) =>
+              setForm({ ...form, vat_rounding_mode: event.target.value as Settings["vat_rounding_mode"] })
+            }
+          >
+            <option value="PER_LINE">{t("labels.vatRoundingPerLine")}</option>
+            <option value="AGGREGATE">{t("labels.vatRoundingAggregate")}</option>
+          </Select>
+          <Input
+            type="text"
+            label={t("labels.currencyCode")}
+            value={form.currency_code ?? "CHF"}
+            maxLength={3}
+            onChange={(event) => setForm({ ...form, currency_code: event.target.value.toUpperCase() })}
+          />
+          <Input
+            type="text"
+            label={t("labels.timezone")}
+            value={form.timezone ?? "LOCAL"}
+            placeholder="LOCAL, UTC, +01:00"
+            onChange={(event) => setForm({ ...form, timezone: event.target.value })}
+          />
+          <Input
+            type="number"
+            min={0}
+            label={t("labels.auditRetentionDays")}
+            value={String(form.audit_retention_days ?? 0)}
+            onChange={(event) => setForm({ ...form, audit_retention_days: Number(event.target.value) || 0 })}
+          />
           <Input
             type="text"
             inputMode="decimal"
@@ -171,6 +203,56 @@ export function SettingsPage() {
               </Button>
             </div>
           </div>
+          <div>
+            <Input
+              label={t("labels.exportFolder")}
+              value={form.export_base_folder ?? ""}
+              onChange={(event) => setForm({ ...form, export_base_folder: event.target.value })}
+            />
+            <div className="mt-2">
+              <Button
+                variant="secondary"
+                onClick={async () => {
+                  const selected = await api.pickFolder();
+                  if (selected) {
+                    setForm({ ...form, export_base_folder: selected });
+                  }
+                }}
+              >
+                {t("labels.chooseFolder")}
+              </Button>
+            </div>
+          </div>
+          <Input
+            label={t("labels.receiptMaxSize")}
+            type="number"
+            value={form.receipt_max_bytes ? String(Math.round((form.receipt_max_bytes / (1024 * 1024)) * 10) / 10) : ""}
+            onChange={(event) => {
+              const parsed = parseDecimalInput(event.target.value);
+              setForm({ ...form, receipt_max_bytes: parsed ? Math.round(parsed * 1024 * 1024) : 0 });
+            }}
+          />
+          <Input
+            label={t("labels.receiptExtensions")}
+            value={(form.receipt_allowed_extensions ?? []).join(", ")}
+            onChange={(event) =>
+              setForm({
+                ...form,
+                receipt_allowed_extensions: event.target.value
+                  .split(",")
+                  .map((value) => value.trim())
+                  .filter(Boolean),
+              })
+            }
+          />
+          <label className="flex items-center gap-2 text-sm">
+            <input
+              type="checkbox"
+              checked={form.allow_note_edit_when_closed ?? true}
+              onChange={(event) => setForm({ ...form, allow_note_edit_when_closed: event.target.checked })}
+            />
+            <span>{t("labels.allowNoteEditWhenClosed")}</span>
+          </label>
           <div className="flex justify-end">
             <Button onClick={save}>{t("actions.save")}</Button>
           </div>
@@ -254,6 +336,34 @@ export function SettingsPage() {
           <div className="text-xs text-app-neutral">
             {t("labels.localSyncLastChange", { value: formatTimestamp(syncStatus?.last_change ?? "-") })}
           </div>
+          <div className="flex flex-wrap items-end gap-2">
+            <Input
+              type="number"
+              label={t("labels.localSyncPort")}
+              value={syncPort}
+              placeholder={syncStatus ? String(syncStatus.port) : ""}
+              onChange={(event) => setSyncPort(event.target.value)}
+            />
+            <Button
+              variant="secondary"
+              disabled={syncBusy || !syncPort}
+              onClick={async () => {
+                setSyncBusy(true);
+                try {
+                  const status = await api.restartSyncServer(Number(syncPort));
+                  setSyncStatus(status);
+                  setSyncPort("");
+                } catch (error) {
+                  const parsed = parseInvokeError(error);
+                  addToast({ title: t("labels.localSyncRestartFailed"), description: parsed.message, variant: "danger" });
+                } finally {
+                  setSyncBusy(false);
+                }
+              }}
+            >
+              {t("actions.syncRestart")}
+            </Button>
+          </div>
 
           <div className="space-y-2">
             <div className="text-xs text-app-neutral">{t("labels.localSyncPairedDevices")}</div>
@@ -309,6 +419,24 @@ export function SettingsPage() {
                 </div>
               </div>
               <div className="mt-4 flex flex-wrap justify-end gap-2">
+                <Button
+                  variant="secondary"
+                  disabled={syncBusy}
+                  onClick={async () => {
+                    setSyncBusy(true);
+                    try {
+                      const status = await api.resolveSyncConflict("DISMISS");
+                      setSyncStatus(status);
+                    } catch (error) {
+                      const parsed = parseInvokeError(error);
+                      addToast({ title: t("labels.syncResolveFailed"), description: parsed.message, variant: "danger" });
+                    } finally {
+                      setSyncBusy(false);
+                    }
+                  }}
+                >
+                  {t("actions.syncDismiss")}
+                </Button>
                 <Button
                   variant="secondary"
                   disabled={syncBusy}
@@ -407,10 +535,15 @@ export function SettingsPage() {
         onConfirm={async () => {
           setSeedBusy(true);
           try {
-            const count = await api.seedMockData(Number(seedCount));
+            const summary = await api.seedMockData(Number(seedCount));
             addToast({
               title: t("labels.demoDataCreated"),
-              description: t("labels.demoDataCreatedCount", { count }),
+              description: summary.skipped_closed
+                ? `${t("labels.demoDataCreatedCount", { count: summary.income_count + summary.expense_count })} ${t(
+                    "labels.demoDataSkippedClosed",
+                    { count: summary.skipped_closed }
+                  )}`
+                : t("labels.demoDataCreatedCount", { count: summary.count }),
               variant: "success",
             });
             setSeedOpen(false);
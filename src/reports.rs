@@ -1,8 +1,55 @@
-use rusqlite::{params, Connection};
+use std::path::Path;
 
+use chrono::{Datelike, NaiveDate};
+use rusqlite::{params, Connection, ToSql};
+
+use crate::domain::mwst::{self, VatRoundingMode};
 use crate::error::AppError;
-use crate::models::{CategorySplit, DailySeriesPoint, MonthSeriesPoint, PaymentSplit};
+use crate::models::{
+  BrokenReceipt, CategorySplit, DailySeriesPoint, Kpis, MonthAmountPoint, MonthSeriesPoint, PaymentSplit, Settings, StornoPair,
+  TicketStats, TransactionListItem,
+};
+use crate::settings;
+
+// Shared column list for every query mapped by map_transaction_row; callers append WHERE/ORDER/LIMIT.
+pub const TRANSACTION_SELECT: &str = "SELECT t.id, t.public_id, t.date, t.year, t.month, t.type, t.payment_method, t.category_id,
+        c.name, t.description, t.amount_chf, t.mwst_rate, t.receipt_path, t.note, t.ref_public_id,
+        t.created_at, t.updated_at,
+        EXISTS (SELECT 1 FROM transactions x WHERE x.ref_public_id = t.public_id) as is_stornoed,
+        t.created_by, t.updated_by, t.receipt_not_required
+     FROM transactions t
+     LEFT JOIN categories c ON c.id = t.category_id";
+
+pub fn map_transaction_row(row: &rusqlite::Row) -> Result<TransactionListItem, rusqlite::Error> {
+  let amount_chf: f64 = row.get(10)?;
+  let mwst_rate: f64 = row.get(11)?;
+  Ok(TransactionListItem {
+    id: row.get(0)?,
+    public_id: row.get(1)?,
+    date: row.get(2)?,
+    year: row.get(3)?,
+    month: row.get(4)?,
+    tx_type: row.get(5)?,
+    payment_method: row.get(6)?,
+    category_id: row.get(7)?,
+    category_name: row.get(8)?,
+    description: row.get(9)?,
+    amount_chf,
+    mwst_rate,
+    mwst_chf: mwst::round2(mwst::mwst_from_brutto(amount_chf, mwst_rate)),
+    receipt_path: row.get(12)?,
+    note: row.get(13)?,
+    ref_public_id: row.get(14)?,
+    created_at: row.get(15)?,
+    updated_at: row.get(16)?,
+    is_stornoed: row.get::<_, i64>(17)? == 1,
+    created_by: row.get(18)?,
+    updated_by: row.get(19)?,
+    receipt_not_required: row.get::<_, i64>(20)? == 1,
+  })
+}
 
+#[derive(Clone)]
 pub struct BaseKpis {
   pub income_total: f64,
   pub income_bar: f64,
@@ -12,36 +59,100 @@ pub struct BaseKpis {
   pub mwst_expense: f64,
   pub missing_receipts_count: i64,
   pub missing_receipts_sum: f64,
+  pub income_count: i64,
+  pub expense_count: i64,
+}
+
+// Single source for derived KPIs so dashboard and Excel export report the same VAT due.
+pub fn kpis_from_base(base: BaseKpis, settings: &Settings) -> Kpis {
+  let result = base.income_total - base.expense_total;
+  let margin = mwst::safe_margin(result, base.income_total);
+  let mwst_due = mwst::round2(if mwst::MwstMode::parse(&settings.mwst_mode) == Some(mwst::MwstMode::Saldo) {
+    mwst::saldo_due(base.income_total, settings.mwst_saldo_rate)
+  } else {
+    mwst::effective_due(base.mwst_income, base.mwst_expense)
+  });
+  Kpis {
+    income_total: base.income_total,
+    income_bar: base.income_bar,
+    income_twint: base.income_twint,
+    expense_total: base.expense_total,
+    result,
+    margin,
+    mwst_income: base.mwst_income,
+    mwst_expense: base.mwst_expense,
+    mwst_due,
+    missing_receipts_count: base.missing_receipts_count,
+    missing_receipts_sum: base.missing_receipts_sum,
+    income_count: base.income_count,
+    expense_count: base.expense_count,
+  }
 }
 
 pub fn get_month_base_kpis(conn: &Connection, year: i32, month: i32) -> Result<BaseKpis, AppError> {
-  let (income_total, income_bar, income_twint, expense_total) = conn.query_row(
-    "SELECT
-        COALESCE(SUM(CASE WHEN type='INCOME' THEN amount_chf END), 0),
-        COALESCE(SUM(CASE WHEN type='INCOME' AND payment_method='BAR' THEN amount_chf END), 0),
-        COALESCE(SUM(CASE WHEN type='INCOME' AND payment_method='TWINT' THEN amount_chf END), 0),
-        COALESCE(SUM(CASE WHEN type='EXPENSE' THEN amount_chf END), 0)
-     FROM transactions WHERE year = ?1 AND month = ?2",
-    params![year, month],
-    |row| Ok((row.get::<_, f64>(0)?, row.get::<_, f64>(1)?, row.get::<_, f64>(2)?, row.get::<_, f64>(3)?)),
-  )?;
+  base_kpis(conn, "year = ?1 AND month = ?2", params![year, month])
+}
 
-  let (mwst_income, mwst_expense) = conn.query_row(
-    "SELECT
-        COALESCE(SUM(CASE WHEN type='INCOME' THEN amount_chf * (mwst_rate / (100.0 + mwst_rate)) END), 0),
-        COALESCE(SUM(CASE WHEN type='EXPENSE' THEN amount_chf * (mwst_rate / (100.0 + mwst_rate)) END), 0)
-     FROM transactions WHERE year = ?1 AND month = ?2",
-    params![year, month],
-    |row| Ok((row.get::<_, f64>(0)?, row.get::<_, f64>(1)?)),
+pub fn get_year_base_kpis(conn: &Connection, year: i32) -> Result<BaseKpis, AppError> {
+  base_kpis(conn, "year = ?1", params![year])
+}
+
+pub fn get_range_base_kpis(
+  conn: &Connection,
+  year: i32,
+  month_from: i32,
+  month_to: i32,
+) -> Result<BaseKpis, AppError> {
+  base_kpis(
+    conn,
+    "year = ?1 AND month BETWEEN ?2 AND ?3",
+    params![year, month_from, month_to],
+  )
+}
+
+pub fn get_daterange_base_kpis(conn: &Connection, date_from: &str, date_to: &str) -> Result<BaseKpis, AppError> {
+  base_kpis(conn, "date BETWEEN ?1 AND ?2", params![date_from, date_to])
+}
+
+// Shared by every period variant; only the WHERE clause differs, as in mwst_totals.
+fn base_kpis(conn: &Connection, where_sql: &str, values: &[&dyn ToSql]) -> Result<BaseKpis, AppError> {
+  let (income_total, income_bar, income_twint, expense_total, income_count, expense_count) = conn.query_row(
+    &format!(
+      "SELECT
+          COALESCE(SUM(CASE WHEN type='INCOME' THEN amount_chf END), 0),
+          COALESCE(SUM(CASE WHEN type='INCOME' AND payment_method='BAR' THEN amount_chf END), 0),
+          COALESCE(SUM(CASE WHEN type='INCOME' AND payment_method='TWINT' THEN amount_chf END), 0),
+          COALESCE(SUM(CASE WHEN type='EXPENSE' THEN amount_chf END), 0),
+          COUNT(CASE WHEN type='INCOME' THEN 1 END),
+          COUNT(CASE WHEN type='EXPENSE' THEN 1 END)
+       FROM transactions
+       WHERE {where_sql}"
+    ),
+    values,
+    |row| {
+      Ok((
+        row.get::<_, f64>(0)?,
+        row.get::<_, f64>(1)?,
+        row.get::<_, f64>(2)?,
+        row.get::<_, f64>(3)?,
+        row.get::<_, i64>(4)?,
+        row.get::<_, i64>(5)?,
+      ))
+    },
   )?;
 
+  let (mwst_income, mwst_expense) = mwst_totals(conn, where_sql, values)?;
+
   let (missing_count, missing_sum) = conn.query_row(
-    "SELECT
-        COUNT(*),
-        COALESCE(SUM(amount_chf), 0)
-     FROM transactions
-     WHERE year = ?1 AND month = ?2 AND type='EXPENSE' AND amount_chf > 0 AND (receipt_path IS NULL OR receipt_path = '')",
-    params![year, month],
+    &format!(
+      "SELECT
+          COUNT(*),
+          COALESCE(SUM(amount_chf), 0)
+       FROM transactions
+       WHERE ({where_sql}) AND type='EXPENSE' AND amount_chf > 0
+         AND ref_public_id IS NULL AND (receipt_path IS NULL OR receipt_path = '') AND receipt_not_required = 0"
+    ),
+    values,
     |row| Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)?)),
   )?;
 
@@ -54,101 +165,68 @@ pub fn get_month_base_kpis(conn: &Connection, year: i32, month: i32) -> Result<B
     mwst_expense,
     missing_receipts_count: missing_count,
     missing_receipts_sum: missing_sum,
+    income_count,
+    expense_count,
   })
 }
 
-pub fn get_year_base_kpis(conn: &Connection, year: i32) -> Result<BaseKpis, AppError> {
-  let (income_total, income_bar, income_twint, expense_total) = conn.query_row(
-    "SELECT
-        COALESCE(SUM(CASE WHEN type='INCOME' THEN amount_chf END), 0),
-        COALESCE(SUM(CASE WHEN type='INCOME' AND payment_method='BAR' THEN amount_chf END), 0),
-        COALESCE(SUM(CASE WHEN type='INCOME' AND payment_method='TWINT' THEN amount_chf END), 0),
-        COALESCE(SUM(CASE WHEN type='EXPENSE' THEN amount_chf END), 0)
-     FROM transactions WHERE year = ?1",
-    params![year],
-    |row| Ok((row.get::<_, f64>(0)?, row.get::<_, f64>(1)?, row.get::<_, f64>(2)?, row.get::<_, f64>(3)?)),
-  )?;
-
-  let (mwst_income, mwst_expense) = conn.query_row(
-    "SELECT
-        COALESCE(SUM(CASE WHEN type='INCOME' THEN amount_chf * (mwst_rate / (100.0 + mwst_rate)) END), 0),
-        COALESCE(SUM(CASE WHEN type='EXPENSE' THEN amount_chf * (mwst_rate / (100.0 + mwst_rate)) END), 0)
-     FROM transactions WHERE year = ?1",
-    params![year],
-    |row| Ok((row.get::<_, f64>(0)?, row.get::<_, f64>(1)?)),
-  )?;
+fn mwst_totals(conn: &Connection, where_sql: &str, values: &[&dyn ToSql]) -> Result<(f64, f64), AppError> {
+  if settings::get_vat_rounding_mode(conn)? == VatRoundingMode::PerLine {
+    let totals = conn.query_row(
+      &format!(
+        "SELECT
+            COALESCE(SUM(CASE WHEN type='INCOME' AND mwst_rate > 0 THEN amount_chf * (mwst_rate / (100.0 + mwst_rate)) END), 0),
+            COALESCE(SUM(CASE WHEN type='EXPENSE' AND mwst_rate > 0 THEN amount_chf * (mwst_rate / (100.0 + mwst_rate)) END), 0)
+         FROM transactions
+         WHERE {where_sql}"
+      ),
+      values,
+      |row| Ok((row.get::<_, f64>(0)?, row.get::<_, f64>(1)?)),
+    )?;
+    return Ok(totals);
+  }
 
-  let (missing_count, missing_sum) = conn.query_row(
-    "SELECT
-        COUNT(*),
-        COALESCE(SUM(amount_chf), 0)
+  let mut stmt = conn.prepare(&format!(
+    "SELECT type, mwst_rate, COALESCE(SUM(amount_chf), 0)
      FROM transactions
-     WHERE year = ?1 AND type='EXPENSE' AND amount_chf > 0 AND (receipt_path IS NULL OR receipt_path = '')",
-    params![year],
-    |row| Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)?)),
-  )?;
-
-  Ok(BaseKpis {
-    income_total,
-    income_bar,
-    income_twint,
-    expense_total,
-    mwst_income,
-    mwst_expense,
-    missing_receipts_count: missing_count,
-    missing_receipts_sum: missing_sum,
-  })
+     WHERE ({where_sql}) AND mwst_rate > 0
+     GROUP BY type, mwst_rate"
+  ))?;
+  let rows = stmt.query_map(values, |row| {
+    Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?, row.get::<_, f64>(2)?))
+  })?;
+  let (mut mwst_income, mut mwst_expense) = (0.0, 0.0);
+  for row in rows {
+    let (tx_type, rate, turnover) = row?;
+    let amount = mwst::mwst_from_aggregate(turnover, rate);
+    if tx_type == "INCOME" {
+      mwst_income += amount;
+    } else {
+      mwst_expense += amount;
+    }
+  }
+  Ok((mwst::round2(mwst_income), mwst::round2(mwst_expense)))
 }
 
-pub fn get_range_base_kpis(
-  conn: &Connection,
-  year: i32,
-  month_from: i32,
-  month_to: i32,
-) -> Result<BaseKpis, AppError> {
-  let (income_total, income_bar, income_twint, expense_total) = conn.query_row(
-    "SELECT
-        COALESCE(SUM(CASE WHEN type='INCOME' THEN amount_chf END), 0),
-        COALESCE(SUM(CASE WHEN type='INCOME' AND payment_method='BAR' THEN amount_chf END), 0),
-        COALESCE(SUM(CASE WHEN type='INCOME' AND payment_method='TWINT' THEN amount_chf END), 0),
-        COALESCE(SUM(CASE WHEN type='EXPENSE' THEN amount_chf END), 0)
-     FROM transactions
-     WHERE year = ?1 AND month BETWEEN ?2 AND ?3",
-    params![year, month_from, month_to],
-    |row| Ok((row.get::<_, f64>(0)?, row.get::<_, f64>(1)?, row.get::<_, f64>(2)?, row.get::<_, f64>(3)?)),
-  )?;
-
-  let (mwst_income, mwst_expense) = conn.query_row(
-    "SELECT
-        COALESCE(SUM(CASE WHEN type='INCOME' THEN amount_chf * (mwst_rate / (100.0 + mwst_rate)) END), 0),
-        COALESCE(SUM(CASE WHEN type='EXPENSE' THEN amount_chf * (mwst_rate / (100.0 + mwst_rate)) END), 0)
-     FROM transactions
-     WHERE year = ?1 AND month BETWEEN ?2 AND ?3",
-    params![year, month_from, month_to],
-    |row| Ok((row.get::<_, f64>(0)?, row.get::<_, f64>(1)?)),
-  )?;
+pub fn fiscal_period(date: NaiveDate, start_month: i32) -> (i32, i32) {
+  let start_month = if (1..=12).contains(&start_month) { start_month } else { 1 };
+  let month = date.month() as i32;
+  if month >= start_month {
+    (date.year(), month - start_month + 1)
+  } else {
+    (date.year() - 1, month + 12 - start_month + 1)
+  }
+}
 
-  let (missing_count, missing_sum) = conn.query_row(
-    "SELECT
-        COUNT(*),
-        COALESCE(SUM(amount_chf), 0)
-     FROM transactions
-     WHERE year = ?1 AND month BETWEEN ?2 AND ?3 AND type='EXPENSE' AND amount_chf > 0
-       AND (receipt_path IS NULL OR receipt_path = '')",
-    params![year, month_from, month_to],
-    |row| Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)?)),
-  )?;
+fn fiscal_month_bounds(fiscal_year: i32, start_month: i32) -> (i32, i32) {
+  let start_month = if (1..=12).contains(&start_month) { start_month } else { 1 };
+  let first = fiscal_year * 12 + start_month;
+  (first, first + 11)
+}
 
-  Ok(BaseKpis {
-    income_total,
-    income_bar,
-    income_twint,
-    expense_total,
-    mwst_income,
-    mwst_expense,
-    missing_receipts_count: missing_count,
-    missing_receipts_sum: missing_sum,
-  })
+pub fn get_fiscal_year_base_kpis(conn: &Connection, fiscal_year: i32, start_month: i32) -> Result<BaseKpis, AppError> {
+  let (first, last) = fiscal_month_bounds(fiscal_year, start_month);
+  base_kpis(conn, "year * 12 + month BETWEEN ?1 AND ?2", params![first, last])
 }
 
 pub fn get_daily_series(conn: &Connection, year: i32, month: i32) -> Result<Vec<DailySeriesPoint>, AppError> {
@@ -256,6 +334,46 @@ pub fn get_top_categories(conn: &Connection, year: i32, month: Option<i32>, limi
   Ok(data)
 }
 
+pub fn get_largest_transactions(
+  conn: &Connection,
+  year: i32,
+  month: Option<i32>,
+  tx_type: Option<&str>,
+  limit: i64,
+) -> Result<Vec<TransactionListItem>, AppError> {
+  let mut stmt = conn.prepare(&format!(
+    "{TRANSACTION_SELECT}
+     WHERE t.year = ?1 AND (?2 IS NULL OR t.month = ?2) AND (?3 IS NULL OR t.type = ?3)
+     ORDER BY t.amount_chf DESC, t.date DESC
+     LIMIT ?4"
+  ))?;
+  let rows = stmt.query_map(params![year, month, tx_type, limit], |row| map_transaction_row(row))?;
+  let mut data = Vec::new();
+  for row in rows {
+    data.push(row?);
+  }
+  Ok(data)
+}
+
+pub fn get_category_trend(conn: &Connection, year: i32, category_id: i64) -> Result<Vec<MonthAmountPoint>, AppError> {
+  let mut stmt = conn.prepare(
+    "SELECT month, COALESCE(SUM(amount_chf), 0)
+     FROM transactions
+     WHERE year = ?1 AND type = 'EXPENSE' AND category_id = ?2
+     GROUP BY month",
+  )?;
+  let rows = stmt.query_map(params![year, category_id], |row| Ok((row.get::<_, i32>(0)?, row.get::<_, f64>(1)?)))?;
+  // Zero-filled so charts always get a continuous Jan..Dec axis.
+  let mut data: Vec<MonthAmountPoint> = (1..=12).map(|month| MonthAmountPoint { month, amount: 0.0 }).collect();
+  for row in rows {
+    let (month, amount) = row?;
+    if let Some(point) = data.get_mut((month - 1) as usize) {
+      point.amount = amount;
+    }
+  }
+  Ok(data)
+}
+
 pub fn get_month_series(conn: &Connection, year: i32) -> Result<Vec<MonthSeriesPoint>, AppError> {
   let mut stmt = conn.prepare(
     "SELECT month,
@@ -278,3 +396,252 @@ pub fn get_month_series(conn: &Connection, year: i32) -> Result<Vec<MonthSeriesP
   })?;
   Ok(rows.filter_map(Result::ok).collect())
 }
+
+pub fn get_fiscal_month_series(conn: &Connection, fiscal_year: i32, start_month: i32) -> Result<Vec<MonthSeriesPoint>, AppError> {
+  let (first, last) = fiscal_month_bounds(fiscal_year, start_month);
+  let mut stmt = conn.prepare(
+    "SELECT year * 12 + month - ?1 + 1 AS period,
+        COALESCE(SUM(CASE WHEN type='INCOME' THEN amount_chf END), 0),
+        COALESCE(SUM(CASE WHEN type='EXPENSE' THEN amount_chf END), 0)
+     FROM transactions
+     WHERE year * 12 + month BETWEEN ?1 AND ?2
+     GROUP BY period
+     ORDER BY period",
+  )?;
+  let rows = stmt.query_map(params![first, last], |row| {
+    let income: f64 = row.get(1)?;
+    let expense: f64 = row.get(2)?;
+    Ok(MonthSeriesPoint {
+      month: row.get(0)?,
+      income,
+      expense,
+      result: income - expense,
+    })
+  })?;
+  Ok(rows.filter_map(Result::ok).collect())
+}
+
+pub fn get_ticket_stats(conn: &Connection, year: i32, month: Option<i32>) -> Result<TicketStats, AppError> {
+  let mut amounts = Vec::new();
+  if let Some(month) = month {
+    let mut stmt = conn.prepare(
+      "SELECT amount_chf FROM transactions
+       WHERE type='INCOME' AND amount_chf > 0 AND year = ?1 AND month = ?2
+       ORDER BY amount_chf",
+    )?;
+    let rows = stmt.query_map(params![year, month], |row| row.get::<_, f64>(0))?;
+    for row in rows {
+      amounts.push(row?);
+    }
+  } else {
+    let mut stmt = conn.prepare(
+      "SELECT amount_chf FROM transactions
+       WHERE type='INCOME' AND amount_chf > 0 AND year = ?1
+       ORDER BY amount_chf",
+    )?;
+    let rows = stmt.query_map(params![year], |row| row.get::<_, f64>(0))?;
+    for row in rows {
+      amounts.push(row?);
+    }
+  }
+
+  let count = amounts.len();
+  if count == 0 {
+    return Ok(TicketStats {
+      count: 0,
+      average: 0.0,
+      median: 0.0,
+      min: 0.0,
+      max: 0.0,
+    });
+  }
+
+  let total: f64 = amounts.iter().sum();
+  let median = if count % 2 == 0 {
+    (amounts[count / 2 - 1] + amounts[count / 2]) / 2.0
+  } else {
+    amounts[count / 2]
+  };
+
+  Ok(TicketStats {
+    count: count as i64,
+    average: total / count as f64,
+    median,
+    min: amounts[0],
+    max: amounts[count - 1],
+  })
+}
+
+pub fn get_storno_report(conn: &Connection, year: i32, month: Option<i32>) -> Result<Vec<StornoPair>, AppError> {
+  let mut data = Vec::new();
+  let base_query = "SELECT s.public_id, s.date, s.type, s.amount_chf, s.note, s.ref_public_id,
+        o.public_id, o.date, o.amount_chf,
+        (SELECT COUNT(*) FROM transactions x WHERE x.ref_public_id = s.ref_public_id AND x.amount_chf < 0)
+     FROM transactions s
+     LEFT JOIN transactions o ON o.public_id = s.ref_public_id";
+
+  if let Some(month) = month {
+    let mut stmt = conn.prepare(&format!(
+      "{base_query}
+       WHERE s.ref_public_id IS NOT NULL AND s.amount_chf < 0 AND s.year = ?1 AND s.month = ?2
+       ORDER BY s.date, s.public_id"
+    ))?;
+    let rows = stmt.query_map(params![year, month], |row| map_storno_row(row))?;
+    for row in rows {
+      data.push(row?);
+    }
+  } else {
+    let mut stmt = conn.prepare(&format!(
+      "{base_query}
+       WHERE s.ref_public_id IS NOT NULL AND s.amount_chf < 0 AND s.year = ?1
+       ORDER BY s.date, s.public_id"
+    ))?;
+    let rows = stmt.query_map(params![year], |row| map_storno_row(row))?;
+    for row in rows {
+      data.push(row?);
+    }
+  }
+
+  Ok(data)
+}
+
+pub fn find_broken_receipts(conn: &Connection) -> Result<Vec<BrokenReceipt>, AppError> {
+  let mut stmt = conn.prepare(
+    "SELECT public_id, date, receipt_path
+     FROM transactions
+     WHERE receipt_path IS NOT NULL AND TRIM(receipt_path) <> ''
+     ORDER BY date, public_id",
+  )?;
+  let rows = stmt.query_map([], |row| {
+    Ok(BrokenReceipt {
+      public_id: row.get(0)?,
+      date: row.get(1)?,
+      receipt_path: row.get(2)?,
+    })
+  })?;
+
+  let mut data = Vec::new();
+  for row in rows {
+    let entry = row?;
+    if !Path::new(&entry.receipt_path).exists() {
+      data.push(entry);
+    }
+  }
+  Ok(data)
+}
+
+fn map_storno_row(row: &rusqlite::Row) -> Result<StornoPair, rusqlite::Error> {
+  let storno_public_id: String = row.get(0)?;
+  let note: Option<String> = row.get(4)?;
+  let ref_public_id: String = row.get(5)?;
+  let original_public_id: Option<String> = row.get(6)?;
+  let reason = note.as_deref().and_then(|value| parse_storno_reason(value, &ref_public_id));
+  Ok(StornoPair {
+    storno_public_id,
+    storno_date: row.get(1)?,
+    tx_type: row.get(2)?,
+    storno_amount: row.get(3)?,
+    original_public_id: ref_public_id,
+    original_date: row.get(7)?,
+    original_amount: row.get(8)?,
+    reason,
+    has_original: original_public_id.is_some(),
+    storno_count: row.get(9)?,
+  })
+}
+
+fn parse_storno_reason(note: &str, ref_public_id: &str) -> Option<String> {
+  let prefix = format!("Storno {ref_public_id}:");
+  let reason = note.strip_prefix(&prefix).unwrap_or(note).trim();
+  if reason.is_empty() {
+    None
+  } else {
+    Some(reason.to_string())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+  }
+
+  fn insert_tx(conn: &Connection, public_id: &str, tx_type: &str, amount: f64, ref_public_id: Option<&str>) {
+    conn
+      .execute(
+        "INSERT INTO transactions (public_id, date, year, month, type, payment_method, amount_chf, mwst_rate, ref_public_id, created_at, updated_at)
+         VALUES (?1, '2024-05-10', 2024, 5, ?2, 'BAR', ?3, 8.1, ?4, '2024-05-10T00:00:00Z', '2024-05-10T00:00:00Z')",
+        params![public_id, tx_type, amount, ref_public_id],
+      )
+      .unwrap();
+  }
+
+  #[test]
+  fn storno_nets_month_kpis_to_zero() {
+    let conn = crate::db::open_test_db();
+    insert_tx(&conn, "E-1", "INCOME", 100.0, None);
+    insert_tx(&conn, "E-1-S", "INCOME", -100.0, Some("E-1"));
+    insert_tx(&conn, "A-1", "EXPENSE", 54.0, None);
+    insert_tx(&conn, "A-1-S", "EXPENSE", -54.0, Some("A-1"));
+
+    let settings = settings::get_settings(&conn).unwrap();
+    let kpis = kpis_from_base(get_month_base_kpis(&conn, 2024, 5).unwrap(), &settings);
+    assert_eq!(kpis.income_total, 0.0);
+    assert_eq!(kpis.expense_total, 0.0);
+    assert_eq!(kpis.mwst_income, 0.0);
+    assert_eq!(kpis.mwst_expense, 0.0);
+    assert_eq!(kpis.mwst_due, 0.0);
+  }
+
+  #[test]
+  fn storno_of_expense_with_receipt_is_not_missing_a_receipt() {
+    let conn = crate::db::open_test_db();
+    insert_tx(&conn, "A-1", "EXPENSE", 54.0, None);
+    conn
+      .execute("UPDATE transactions SET receipt_path = 'Belege/2024/05/A-1.pdf' WHERE public_id = 'A-1'", [])
+      .unwrap();
+    insert_tx(&conn, "A-1-S", "EXPENSE", -54.0, Some("A-1"));
+
+    let base = get_month_base_kpis(&conn, 2024, 5).unwrap();
+    assert_eq!(base.missing_receipts_count, 0);
+    assert_eq!(base.missing_receipts_sum, 0.0);
+  }
+
+  #[test]
+  fn vat_rounding_modes_diverge() {
+    let conn = crate::db::open_test_db();
+    for index in 0..10 {
+      insert_tx(&conn, &format!("E-{index}"), "INCOME", 1.0, None);
+    }
+    let where_sql = "year = ?1 AND month = ?2";
+
+    conn
+      .execute("UPDATE settings SET value = 'PER_LINE' WHERE key = 'vat_rounding_mode'", [])
+      .unwrap();
+    let (per_line, _) = mwst_totals(&conn, where_sql, params![2024, 5]).unwrap();
+
+    conn
+      .execute("UPDATE settings SET value = 'AGGREGATE' WHERE key = 'vat_rounding_mode'", [])
+      .unwrap();
+    let (aggregate, _) = mwst_totals(&conn, where_sql, params![2024, 5]).unwrap();
+
+    assert!((per_line - 10.0 * 8.1 / 108.1).abs() < 1e-9);
+    assert_eq!(aggregate, 0.75);
+    assert_ne!(per_line, aggregate);
+  }
+
+  #[test]
+  fn fiscal_period_wraps_december_for_april_start() {
+    assert_eq!(fiscal_period(date(2024, 4, 1), 4), (2024, 1));
+    assert_eq!(fiscal_period(date(2024, 12, 31), 4), (2024, 9));
+    assert_eq!(fiscal_period(date(2025, 1, 1), 4), (2024, 10));
+    assert_eq!(fiscal_period(date(2025, 3, 31), 4), (2024, 12));
+
+    let (first, last) = fiscal_month_bounds(2024, 4);
+    assert!((first..=last).contains(&(2024 * 12 + 12)));
+    assert!((first..=last).contains(&(2025 * 12 + 1)));
+    assert_eq!(last, 2025 * 12 + 3);
+  }
+}
@@ -1,15 +1,39 @@
 ﻿use std::path::Path;
 
 use chrono::Datelike;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 
+use crate::domain::mwst::{MwstMode, VatRoundingMode};
+use crate::domain::time::{Timezone, DEFAULT_TIMEZONE};
 use crate::error::AppError;
+use crate::files::receipts::{
+  normalize_extensions, DEFAULT_RECEIPT_EXTENSIONS, DEFAULT_RECEIPT_MAX_BYTES, DEFAULT_RECEIPT_NAMING_PATTERN,
+};
 use crate::models::Settings;
 
 const KEY_YEAR: &str = "current_year";
 const KEY_MWST_MODE: &str = "mwst_mode";
 const KEY_MWST_SALDO: &str = "mwst_saldo_rate";
 const KEY_RECEIPT_BASE: &str = "receipt_base_folder";
+const KEY_COMPRESS_RECEIPTS: &str = "compress_receipts";
+const KEY_LAST_VIEWED_YEAR: &str = "last_viewed_year";
+const KEY_RECEIPT_NAMING_PATTERN: &str = "receipt_naming_pattern";
+const KEY_FISCAL_YEAR_START_MONTH: &str = "fiscal_year_start_month";
+const KEY_EXPORT_BASE: &str = "export_base_folder";
+const KEY_DEFAULT_PAGE_SIZE: &str = "default_page_size";
+const KEY_RECEIPT_MAX_BYTES: &str = "receipt_max_bytes";
+const KEY_RECEIPT_EXTENSIONS: &str = "receipt_allowed_extensions";
+const KEY_VAT_ROUNDING_MODE: &str = "vat_rounding_mode";
+const KEY_CURRENCY_CODE: &str = "currency_code";
+const KEY_ALLOW_NOTE_EDIT_WHEN_CLOSED: &str = "allow_note_edit_when_closed";
+const KEY_SYNC_PORT: &str = "sync_port";
+const KEY_TIMEZONE: &str = "timezone";
+const KEY_AUDIT_RETENTION_DAYS: &str = "audit_retention_days";
+
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+pub const PAGE_SIZE_MIN: i64 = 10;
+pub const PAGE_SIZE_MAX: i64 = 500;
+pub const DEFAULT_CURRENCY_CODE: &str = "CHF";
 
 pub fn ensure_defaults(conn: &Connection, receipt_base: &Path) -> Result<(), AppError> {
   let year = chrono::Utc::now().year();
@@ -19,7 +43,7 @@ pub fn ensure_defaults(conn: &Connection, receipt_base: &Path) -> Result<(), App
   )?;
   conn.execute(
     "INSERT OR IGNORE INTO settings (key, value) VALUES (?1, ?2)",
-    params![KEY_MWST_MODE, "EFFEKTIV"],
+    params![KEY_MWST_MODE, MwstMode::Effektiv.as_str()],
   )?;
   conn.execute(
     "INSERT OR IGNORE INTO settings (key, value) VALUES (?1, ?2)",
@@ -29,6 +53,54 @@ pub fn ensure_defaults(conn: &Connection, receipt_base: &Path) -> Result<(), App
     "INSERT OR IGNORE INTO settings (key, value) VALUES (?1, ?2)",
     params![KEY_RECEIPT_BASE, receipt_base.to_string_lossy().to_string()],
   )?;
+  conn.execute(
+    "INSERT OR IGNORE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_COMPRESS_RECEIPTS, "0"],
+  )?;
+  conn.execute(
+    "INSERT OR IGNORE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_RECEIPT_NAMING_PATTERN, DEFAULT_RECEIPT_NAMING_PATTERN],
+  )?;
+  conn.execute(
+    "INSERT OR IGNORE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_FISCAL_YEAR_START_MONTH, "1"],
+  )?;
+  conn.execute(
+    "INSERT OR IGNORE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_EXPORT_BASE, ""],
+  )?;
+  conn.execute(
+    "INSERT OR IGNORE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_DEFAULT_PAGE_SIZE, DEFAULT_PAGE_SIZE.to_string()],
+  )?;
+  conn.execute(
+    "INSERT OR IGNORE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_RECEIPT_MAX_BYTES, DEFAULT_RECEIPT_MAX_BYTES.to_string()],
+  )?;
+  conn.execute(
+    "INSERT OR IGNORE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_RECEIPT_EXTENSIONS, DEFAULT_RECEIPT_EXTENSIONS.join(",")],
+  )?;
+  conn.execute(
+    "INSERT OR IGNORE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_VAT_ROUNDING_MODE, VatRoundingMode::PerLine.as_str()],
+  )?;
+  conn.execute(
+    "INSERT OR IGNORE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_CURRENCY_CODE, DEFAULT_CURRENCY_CODE],
+  )?;
+  conn.execute(
+    "INSERT OR IGNORE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_ALLOW_NOTE_EDIT_WHEN_CLOSED, "1"],
+  )?;
+  conn.execute(
+    "INSERT OR IGNORE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_TIMEZONE, DEFAULT_TIMEZONE],
+  )?;
+  conn.execute(
+    "INSERT OR IGNORE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_AUDIT_RETENTION_DAYS, "0"],
+  )?;
   Ok(())
 }
 
@@ -37,9 +109,22 @@ pub fn get_settings(conn: &Connection) -> Result<Settings, AppError> {
   let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
 
   let mut current_year = chrono::Utc::now().year();
-  let mut mwst_mode = "EFFEKTIV".to_string();
+  let mut mwst_mode = MwstMode::Effektiv;
   let mut mwst_saldo_rate = 5.9_f64;
   let mut receipt_base_folder = String::new();
+  let mut compress_receipts = false;
+  let mut last_viewed_year: Option<i32> = None;
+  let mut receipt_naming_pattern = DEFAULT_RECEIPT_NAMING_PATTERN.to_string();
+  let mut fiscal_year_start_month = 1;
+  let mut export_base_folder = String::new();
+  let mut default_page_size = DEFAULT_PAGE_SIZE;
+  let mut receipt_max_bytes = DEFAULT_RECEIPT_MAX_BYTES;
+  let mut receipt_allowed_extensions: Vec<String> = Vec::new();
+  let mut vat_rounding_mode = VatRoundingMode::PerLine;
+  let mut currency_code = DEFAULT_CURRENCY_CODE.to_string();
+  let mut timezone = Timezone::Local;
+  let mut allow_note_edit_when_closed = true;
+  let mut audit_retention_days = 0;
 
   for row in rows {
     let (key, value) = row?;
@@ -48,7 +133,7 @@ pub fn get_settings(conn: &Connection) -> Result<Settings, AppError> {
         current_year = value.parse().unwrap_or(current_year);
       }
       KEY_MWST_MODE => {
-        mwst_mode = value;
+        mwst_mode = MwstMode::parse(&value).unwrap_or(MwstMode::Effektiv);
       }
       KEY_MWST_SALDO => {
         mwst_saldo_rate = value.parse().unwrap_or(mwst_saldo_rate);
@@ -56,15 +141,82 @@ pub fn get_settings(conn: &Connection) -> Result<Settings, AppError> {
       KEY_RECEIPT_BASE => {
         receipt_base_folder = value;
       }
+      KEY_COMPRESS_RECEIPTS => {
+        compress_receipts = value == "1";
+      }
+      KEY_LAST_VIEWED_YEAR => {
+        last_viewed_year = value.parse().ok();
+      }
+      KEY_RECEIPT_NAMING_PATTERN => {
+        if !value.trim().is_empty() {
+          receipt_naming_pattern = value;
+        }
+      }
+      KEY_FISCAL_YEAR_START_MONTH => {
+        fiscal_year_start_month = value
+          .parse()
+          .ok()
+          .filter(|month| (1..=12).contains(month))
+          .unwrap_or(1);
+      }
+      KEY_EXPORT_BASE => {
+        export_base_folder = value;
+      }
+      KEY_DEFAULT_PAGE_SIZE => {
+        default_page_size = value
+          .parse::<i64>()
+          .ok()
+          .filter(|size| *size > 0)
+          .map(|size| size.clamp(PAGE_SIZE_MIN, PAGE_SIZE_MAX))
+          .unwrap_or(DEFAULT_PAGE_SIZE);
+      }
+      KEY_RECEIPT_MAX_BYTES => {
+        receipt_max_bytes = value
+          .parse()
+          .ok()
+          .filter(|bytes| *bytes > 0)
+          .unwrap_or(DEFAULT_RECEIPT_MAX_BYTES);
+      }
+      KEY_RECEIPT_EXTENSIONS => {
+        receipt_allowed_extensions = value.split(',').map(|ext| ext.to_string()).collect();
+      }
+      KEY_VAT_ROUNDING_MODE => {
+        vat_rounding_mode = VatRoundingMode::parse(&value).unwrap_or(VatRoundingMode::PerLine);
+      }
+      KEY_CURRENCY_CODE => {
+        currency_code = normalize_currency_code(&value);
+      }
+      KEY_ALLOW_NOTE_EDIT_WHEN_CLOSED => {
+        allow_note_edit_when_closed = value != "0";
+      }
+      KEY_TIMEZONE => {
+        timezone = Timezone::parse(&value).unwrap_or(Timezone::Local);
+      }
+      KEY_AUDIT_RETENTION_DAYS => {
+        audit_retention_days = value.parse::<i64>().unwrap_or(0).max(0);
+      }
       _ => {}
     }
   }
 
   Ok(Settings {
     current_year,
-    mwst_mode,
+    mwst_mode: mwst_mode.as_str().to_string(),
     mwst_saldo_rate,
     receipt_base_folder,
+    compress_receipts,
+    last_viewed_year: last_viewed_year.unwrap_or(current_year),
+    receipt_naming_pattern,
+    fiscal_year_start_month,
+    export_base_folder,
+    default_page_size,
+    receipt_max_bytes,
+    receipt_allowed_extensions: normalize_extensions(&receipt_allowed_extensions),
+    vat_rounding_mode: vat_rounding_mode.as_str().to_string(),
+    currency_code,
+    allow_note_edit_when_closed,
+    timezone: timezone.to_setting(),
+    audit_retention_days,
   })
 }
 
@@ -73,9 +225,10 @@ pub fn update_settings(conn: &Connection, settings: &Settings) -> Result<(), App
     "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
     params![KEY_YEAR, settings.current_year.to_string()],
   )?;
+  let mwst_mode = MwstMode::parse(&settings.mwst_mode).unwrap_or(MwstMode::Effektiv);
   conn.execute(
     "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
-    params![KEY_MWST_MODE, settings.mwst_mode.clone()],
+    params![KEY_MWST_MODE, mwst_mode.as_str()],
   )?;
   conn.execute(
     "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
@@ -85,5 +238,194 @@ pub fn update_settings(conn: &Connection, settings: &Settings) -> Result<(), App
     "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
     params![KEY_RECEIPT_BASE, settings.receipt_base_folder.clone()],
   )?;
+  conn.execute(
+    "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_COMPRESS_RECEIPTS, if settings.compress_receipts { "1" } else { "0" }],
+  )?;
+  let naming_pattern = if settings.receipt_naming_pattern.trim().is_empty() {
+    DEFAULT_RECEIPT_NAMING_PATTERN
+  } else {
+    settings.receipt_naming_pattern.trim()
+  };
+  conn.execute(
+    "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_RECEIPT_NAMING_PATTERN, naming_pattern],
+  )?;
+  let fiscal_start = if (1..=12).contains(&settings.fiscal_year_start_month) {
+    settings.fiscal_year_start_month
+  } else {
+    1
+  };
+  conn.execute(
+    "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_FISCAL_YEAR_START_MONTH, fiscal_start.to_string()],
+  )?;
+  conn.execute(
+    "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_EXPORT_BASE, settings.export_base_folder.clone()],
+  )?;
+  let page_size = if settings.default_page_size < 1 {
+    DEFAULT_PAGE_SIZE
+  } else {
+    settings.default_page_size
+  };
+  conn.execute(
+    "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_DEFAULT_PAGE_SIZE, page_size.to_string()],
+  )?;
+  let max_bytes = if settings.receipt_max_bytes == 0 {
+    DEFAULT_RECEIPT_MAX_BYTES
+  } else {
+    settings.receipt_max_bytes
+  };
+  conn.execute(
+    "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_RECEIPT_MAX_BYTES, max_bytes.to_string()],
+  )?;
+  conn.execute(
+    "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+    params![
+      KEY_RECEIPT_EXTENSIONS,
+      normalize_extensions(&settings.receipt_allowed_extensions).join(",")
+    ],
+  )?;
+  let vat_rounding_mode = VatRoundingMode::parse(&settings.vat_rounding_mode).unwrap_or(VatRoundingMode::PerLine);
+  conn.execute(
+    "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_VAT_ROUNDING_MODE, vat_rounding_mode.as_str()],
+  )?;
+  conn.execute(
+    "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_CURRENCY_CODE, normalize_currency_code(&settings.currency_code)],
+  )?;
+  conn.execute(
+    "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+    params![
+      KEY_ALLOW_NOTE_EDIT_WHEN_CLOSED,
+      if settings.allow_note_edit_when_closed { "1" } else { "0" }
+    ],
+  )?;
+  let timezone = Timezone::parse(&settings.timezone).unwrap_or(Timezone::Local);
+  conn.execute(
+    "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_TIMEZONE, timezone.to_setting()],
+  )?;
+  conn.execute(
+    "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_AUDIT_RETENTION_DAYS, settings.audit_retention_days.max(0).to_string()],
+  )?;
+  Ok(())
+}
+
+pub fn get_vat_rounding_mode(conn: &Connection) -> Result<VatRoundingMode, AppError> {
+  let value: Option<String> = conn
+    .query_row(
+      "SELECT value FROM settings WHERE key = ?1",
+      params![KEY_VAT_ROUNDING_MODE],
+      |row| row.get(0),
+    )
+    .optional()?;
+  Ok(value
+    .as_deref()
+    .and_then(VatRoundingMode::parse)
+    .unwrap_or(VatRoundingMode::PerLine))
+}
+
+pub fn get_currency_code(conn: &Connection) -> Result<String, AppError> {
+  let value: Option<String> = conn
+    .query_row(
+      "SELECT value FROM settings WHERE key = ?1",
+      params![KEY_CURRENCY_CODE],
+      |row| row.get(0),
+    )
+    .optional()?;
+  Ok(normalize_currency_code(value.as_deref().unwrap_or_default()))
+}
+
+pub fn get_allow_note_edit_when_closed(conn: &Connection) -> Result<bool, AppError> {
+  let value: Option<String> = conn
+    .query_row(
+      "SELECT value FROM settings WHERE key = ?1",
+      params![KEY_ALLOW_NOTE_EDIT_WHEN_CLOSED],
+      |row| row.get(0),
+    )
+    .optional()?;
+  Ok(value.as_deref() != Some("0"))
+}
+
+pub fn get_timezone(conn: &Connection) -> Result<Timezone, AppError> {
+  let value: Option<String> = conn
+    .query_row("SELECT value FROM settings WHERE key = ?1", params![KEY_TIMEZONE], |row| row.get(0))
+    .optional()?;
+  Ok(value.as_deref().and_then(Timezone::parse).unwrap_or(Timezone::Local))
+}
+
+pub fn get_audit_retention_days(conn: &Connection) -> Result<i64, AppError> {
+  let value: Option<String> = conn
+    .query_row(
+      "SELECT value FROM settings WHERE key = ?1",
+      params![KEY_AUDIT_RETENTION_DAYS],
+      |row| row.get(0),
+    )
+    .optional()?;
+  Ok(value.and_then(|value| value.parse::<i64>().ok()).unwrap_or(0).max(0))
+}
+
+fn normalize_currency_code(value: &str) -> String {
+  let code = value.trim().to_ascii_uppercase();
+  if code.is_empty() {
+    DEFAULT_CURRENCY_CODE.to_string()
+  } else {
+    code
+  }
+}
+
+pub fn get_sync_port(conn: &Connection) -> Result<Option<u16>, AppError> {
+  let value: Option<String> = conn
+    .query_row("SELECT value FROM settings WHERE key = ?1", params![KEY_SYNC_PORT], |row| row.get(0))
+    .optional()?;
+  Ok(value.and_then(|value| value.parse().ok()))
+}
+
+pub fn set_sync_port(conn: &Connection, port: u16) -> Result<(), AppError> {
+  conn.execute(
+    "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_SYNC_PORT, port.to_string()],
+  )?;
   Ok(())
 }
+
+pub fn set_current_year(conn: &Connection, year: i32) -> Result<(), AppError> {
+  conn.execute(
+    "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_YEAR, year.to_string()],
+  )?;
+  Ok(())
+}
+
+pub fn set_last_viewed_year(conn: &Connection, year: i32) -> Result<(), AppError> {
+  conn.execute(
+    "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+    params![KEY_LAST_VIEWED_YEAR, year.to_string()],
+  )?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn page_size_is_clamped_on_read() {
+    let conn = crate::db::open_test_db();
+    for (stored, expected) in [("100000", PAGE_SIZE_MAX), ("3", PAGE_SIZE_MIN), ("0", DEFAULT_PAGE_SIZE), ("abc", DEFAULT_PAGE_SIZE)] {
+      conn
+        .execute(
+          "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+          params![KEY_DEFAULT_PAGE_SIZE, stored],
+        )
+        .unwrap();
+      assert_eq!(get_settings(&conn).unwrap().default_page_size, expected);
+    }
+  }
+}
@@ -93,7 +93,10 @@ export const useAppStore = create<AppStore>((set, get) => ({
   uiScale: 100,
   ocrMode: "auto",
   ocrApiKey: "",
-  setYear: (year) => set({ year }),
+  setYear: (year) => {
+    set({ year });
+    void api.setLastViewedYear(year).catch(() => undefined);
+  },
   setMonth: (month) => set({ month }),
   setSettings: (settings) => set({ settings }),
   setGlobalSearch: (value) => set({ globalSearch: value }),
@@ -158,7 +161,7 @@ export const useAppStore = create<AppStore>((set, get) => ({
     const settings = await api.getSettings();
     set({
       settings,
-      year: settings.current_year ?? get().year,
+      year: settings.last_viewed_year ?? settings.current_year ?? get().year,
       theme: getStoredTheme(),
       sidebarCollapsed: getStoredSidebar(),
       language: getStoredLanguage(),
@@ -1,9 +1,11 @@
 use std::collections::HashMap;
 use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Mutex;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use chrono::{DateTime, Utc};
 use rand::{distributions::Alphanumeric, Rng};
@@ -11,18 +13,45 @@ use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use tiny_http::{Header, Method, Request, Response, Server, StatusCode};
 use walkdir::WalkDir;
-use tauri::Manager;
+use tauri::{AppHandle, Emitter, Manager};
 
-use crate::audit::log::append_audit;
+use crate::audit::log::{append_audit, map_audit_row};
 use crate::db;
 use crate::error::AppError;
+use crate::events;
 use crate::files::backup;
-use crate::models::{SyncConflictInfo, SyncConflictItem, SyncConflictSummary, SyncDeviceInfo};
+use crate::models::{AuditLogEntry, Paginated, ReceiptRelinkSummary, SyncConflictInfo, SyncConflictItem, SyncConflictSummary, SyncDeviceInfo, SyncLogEntry, SyncProgress};
 use crate::AppState;
 
 const PAIR_CODE_LEN: usize = 10;
 const TOKEN_LEN: usize = 32;
 const SYNC_PORT_FALLBACK: u16 = 48080;
+const SYNC_PORT_RANGE: std::ops::Range<u16> = 48080..48090;
+const PAIR_MAX_FAILURES: u32 = 5;
+const PAIR_LOCKOUT: Duration = Duration::from_secs(5 * 60);
+const PAIR_ATTEMPT_TTL: Duration = Duration::from_secs(30 * 60);
+const SYNC_PROGRESS_EVENT: &str = "sync://progress";
+const SYNC_PROGRESS_STEP: u64 = 1024 * 1024;
+const DIRECTION_OUTGOING: &str = "OUTGOING";
+const DIRECTION_INCOMING: &str = "INCOMING";
+const PEER_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const PEER_IO_TIMEOUT: Duration = Duration::from_secs(120);
+const OUTCOME_OK: &str = "OK";
+const OUTCOME_FAILED: &str = "FAILED";
+const OUTCOME_CONFLICT: &str = "CONFLICT";
+const OUTCOME_SKIPPED: &str = "SKIPPED";
+const CONFLICT_ARCHIVE_DIR: &str = "SyncConflicts";
+const CONFLICT_ARCHIVE_MAX_AGE: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+
+struct SyncLogEvent<'a> {
+  event: &'a str,
+  direction: Option<&'a str>,
+  device_id: Option<&'a str>,
+  device_name: Option<&'a str>,
+  bytes: u64,
+  outcome: &'a str,
+  message: Option<&'a str>,
+}
 
 #[derive(Debug, Clone)]
 pub struct SyncSnapshot {
@@ -32,12 +61,24 @@ pub struct SyncSnapshot {
 }
 
 pub struct SyncState {
-  port: u16,
+  preferred_port: AtomicU16,
+  port: AtomicU16,
   active: AtomicBool,
+  server_generation: AtomicU64,
+  server: Mutex<Option<Arc<Server>>>,
+  last_error: Mutex<Option<String>>,
+  pair_attempts: Mutex<HashMap<String, PairAttempts>>,
   store_path: PathBuf,
   store: Mutex<SyncStore>,
 }
 
+#[derive(Debug, Clone)]
+struct PairAttempts {
+  failures: u32,
+  last_failure: Instant,
+  locked_until: Option<Instant>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct SyncStore {
   device_id: String,
@@ -53,10 +94,16 @@ struct SyncStore {
 struct PairedDevice {
   device_id: String,
   device_name: String,
+  // Empty when only this device paired outgoing; the peer then holds no token for us.
+  #[serde(default)]
   token: String,
   last_sync_at: Option<String>,
   last_remote_change: Option<String>,
   last_known_ip: Option<String>,
+  #[serde(default)]
+  peer_token: Option<String>,
+  #[serde(default)]
+  peer_port: Option<u16>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -71,14 +118,14 @@ struct PendingConflict {
   remote_summary: Option<SyncConflictSummary>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct PairRequest {
   code: String,
   device_id: String,
   device_name: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct PairResponse {
   device_token: String,
   server_device_id: String,
@@ -86,7 +133,7 @@ struct PairResponse {
   last_change: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct StatusResponse {
   device_id: String,
   device_name: String,
@@ -114,16 +161,64 @@ impl SyncState {
       store.pair_code = generate_pair_code();
     }
     let _ = save_store(&store_path, &store);
+    let preferred_port = if port == 0 { SYNC_PORT_FALLBACK } else { port };
     Self {
-      port: if port == 0 { SYNC_PORT_FALLBACK } else { port },
+      preferred_port: AtomicU16::new(preferred_port),
+      port: AtomicU16::new(preferred_port),
       active: AtomicBool::new(false),
+      server_generation: AtomicU64::new(0),
+      server: Mutex::new(None),
+      last_error: Mutex::new(None),
+      pair_attempts: Mutex::new(HashMap::new()),
       store_path,
       store: Mutex::new(store),
     }
   }
 
   pub fn port(&self) -> u16 {
-    self.port
+    self.port.load(Ordering::Relaxed)
+  }
+
+  pub fn preferred_port(&self) -> u16 {
+    self.preferred_port.load(Ordering::Relaxed)
+  }
+
+  fn install_server(&self, server: Arc<Server>, port: u16) -> u64 {
+    let generation = self.server_generation.fetch_add(1, Ordering::SeqCst) + 1;
+    if let Ok(mut guard) = self.server.lock() {
+      *guard = Some(server);
+    }
+    self.set_port(port);
+    self.set_last_error(None);
+    self.set_active(true);
+    generation
+  }
+
+  fn is_current_server(&self, generation: u64) -> bool {
+    self.server_generation.load(Ordering::SeqCst) == generation
+  }
+
+  fn stop_server(&self) {
+    self.server_generation.fetch_add(1, Ordering::SeqCst);
+    let server = self.server.lock().ok().and_then(|mut guard| guard.take());
+    if let Some(server) = server {
+      server.unblock();
+    }
+    self.set_active(false);
+  }
+
+  pub fn set_port(&self, port: u16) {
+    self.port.store(port, Ordering::Relaxed);
+  }
+
+  pub fn last_error(&self) -> Option<String> {
+    self.last_error.lock().ok().and_then(|guard| guard.clone())
+  }
+
+  pub fn set_last_error(&self, error: Option<String>) {
+    if let Ok(mut guard) = self.last_error.lock() {
+      *guard = error;
+    }
   }
 
   pub fn is_active(&self) -> bool {
@@ -178,6 +273,9 @@ impl SyncState {
       if let Some(ip) = last_known_ip {
         existing.last_known_ip = Some(ip);
       }
+      if existing.token.is_empty() {
+        existing.token = generate_token(TOKEN_LEN);
+      }
       let token = existing.token.clone();
       save_store(&self.store_path, &store)?;
       return Ok(token);
@@ -191,17 +289,103 @@ impl SyncState {
       last_sync_at: None,
       last_remote_change: None,
       last_known_ip,
+      peer_token: None,
+      peer_port: None,
     });
     save_store(&self.store_path, &store)?;
     Ok(token)
   }
 
+  fn store_peer_token(
+    &self,
+    device_id: &str,
+    device_name: &str,
+    peer_token: &str,
+    peer_ip: &str,
+    peer_port: u16,
+  ) -> Result<(), AppError> {
+    let mut store = self.store.lock()?;
+    if let Some(existing) = store.paired_devices.iter_mut().find(|device| device.device_id == device_id) {
+      existing.device_name = device_name.to_string();
+      existing.peer_token = Some(peer_token.to_string());
+      existing.peer_port = Some(peer_port);
+      existing.last_known_ip = Some(peer_ip.to_string());
+    } else {
+      store.paired_devices.push(PairedDevice {
+        device_id: device_id.to_string(),
+        device_name: device_name.to_string(),
+        token: String::new(),
+        last_sync_at: None,
+        last_remote_change: None,
+        last_known_ip: Some(peer_ip.to_string()),
+        peer_token: Some(peer_token.to_string()),
+        peer_port: Some(peer_port),
+      });
+    }
+    save_store(&self.store_path, &store)?;
+    Ok(())
+  }
+
+  fn peer_for_ip(&self, peer_ip: &str) -> Result<Option<PairedDevice>, AppError> {
+    let store = self.store.lock()?;
+    Ok(store
+      .paired_devices
+      .iter()
+      .find(|device| device.last_known_ip.as_deref() == Some(peer_ip) && device.peer_token.is_some())
+      .cloned())
+  }
+
+  fn check_pair_allowed(&self, remote_ip: &str) -> Result<(), AppError> {
+    let mut attempts = self.pair_attempts.lock()?;
+    let now = Instant::now();
+    attempts.retain(|_, entry| {
+      entry.locked_until.map(|until| until > now).unwrap_or(false)
+        || now.duration_since(entry.last_failure) < PAIR_ATTEMPT_TTL
+    });
+    if let Some(until) = attempts.get(remote_ip).and_then(|entry| entry.locked_until) {
+      if until > now {
+        let remaining = until.duration_since(now).as_secs().max(1);
+        return Err(AppError::new(
+          "SYNC_RATE_LIMIT",
+          format!("Zu viele Pairing-Versuche. Bitte in {remaining} Sekunden erneut versuchen."),
+        ));
+      }
+    }
+    Ok(())
+  }
+
+  fn record_pair_failure(&self, remote_ip: &str) -> Result<(), AppError> {
+    let mut attempts = self.pair_attempts.lock()?;
+    let now = Instant::now();
+    let entry = attempts.entry(remote_ip.to_string()).or_insert(PairAttempts {
+      failures: 0,
+      last_failure: now,
+      locked_until: None,
+    });
+    if entry.locked_until.map(|until| until <= now).unwrap_or(false) {
+      entry.failures = 0;
+      entry.locked_until = None;
+    }
+    entry.failures += 1;
+    entry.last_failure = now;
+    if entry.failures >= PAIR_MAX_FAILURES {
+      entry.locked_until = Some(now + PAIR_LOCKOUT);
+    }
+    Ok(())
+  }
+
+  fn reset_pair_attempts(&self, remote_ip: &str) -> Result<(), AppError> {
+    let mut attempts = self.pair_attempts.lock()?;
+    attempts.remove(remote_ip);
+    Ok(())
+  }
+
   fn device_for_token(&self, device_id: &str, token: &str) -> Result<Option<PairedDevice>, AppError> {
     let store = self.store.lock()?;
     Ok(store
       .paired_devices
       .iter()
-      .find(|device| device.device_id == device_id && device.token == token)
+      .find(|device| device.device_id == device_id && !device.token.is_empty() && device.token == token)
       .cloned())
   }
 
@@ -265,43 +449,237 @@ impl SyncState {
   }
 }
 
-pub fn start_sync_server(handle: tauri::AppHandle) {
+pub fn start_sync_server(handle: AppHandle) {
   std::thread::spawn(move || {
     let state = handle.state::<AppState>();
-    let port = state.sync.port();
-    let server = Server::http(("0.0.0.0", port));
-    match server {
-      Ok(server) => {
-        state.sync.set_active(true);
-        for request in server.incoming_requests() {
-          handle_sync_request(request, &state);
-        }
-        state.sync.set_active(false);
+    cleanup_conflict_archives(&state);
+    match bind_sync_server(state.sync.preferred_port()) {
+      Ok((server, port)) => {
+        let server = Arc::new(server);
+        let generation = state.sync.install_server(server.clone(), port);
+        run_sync_server(&handle, &state, &server, generation);
       }
-      Err(_) => {
+      Err(message) => {
+        state.sync.set_last_error(Some(message));
         state.sync.set_active(false);
       }
     }
   });
 }
 
+pub fn restart_sync_server(handle: &AppHandle, state: &AppState, port: u16) -> Result<(), AppError> {
+  if port < 1024 {
+    return Err(AppError::new_field(
+      "INVALID_PORT",
+      "port",
+      "Port muss zwischen 1024 und 65535 liegen",
+    ));
+  }
+  if state.sync.is_active() && state.sync.port() == port {
+    return Ok(());
+  }
+  // Bind first so an occupied port leaves the running server untouched.
+  let server = Server::http(("0.0.0.0", port))
+    .map_err(|err| AppError::new("SYNC_PORT", format!("Port {port} ist nicht verfuegbar: {err}")))?;
+  state.sync.stop_server();
+  state.sync.preferred_port.store(port, Ordering::Relaxed);
+  let server = Arc::new(server);
+  let generation = state.sync.install_server(server.clone(), port);
+  let handle = handle.clone();
+  std::thread::spawn(move || {
+    let state = handle.state::<AppState>();
+    run_sync_server(&handle, &state, &server, generation);
+  });
+  Ok(())
+}
+
+fn run_sync_server(handle: &AppHandle, state: &AppState, server: &Server, generation: u64) {
+  for request in server.incoming_requests() {
+    if !state.sync.is_current_server(generation) {
+      break;
+    }
+    handle_sync_request(request, handle, state);
+  }
+  if state.sync.is_current_server(generation) {
+    state.sync.set_active(false);
+  }
+}
+
+pub fn bind_sync_server(preferred_port: u16) -> Result<(Server, u16), String> {
+  let mut candidates = vec![preferred_port];
+  candidates.extend(SYNC_PORT_RANGE.filter(|port| *port != preferred_port));
+
+  let mut errors = Vec::new();
+  for port in candidates {
+    match Server::http(("0.0.0.0", port)) {
+      Ok(server) => return Ok((server, port)),
+      Err(err) => errors.push(format!("Port {port}: {err}")),
+    }
+  }
+  Err(format!("Sync-Server konnte nicht gestartet werden ({})", errors.join("; ")))
+}
+
 pub fn local_ip_string() -> String {
   local_ip_address::local_ip()
     .map(|ip| ip.to_string())
     .unwrap_or_else(|_| "0.0.0.0".to_string())
 }
 
+// Audit purges are housekeeping, not data changes, and must not count as a local change for conflicts.
 pub fn get_last_change(conn: &Connection) -> Result<String, AppError> {
-  let ts: Option<String> = conn.query_row("SELECT MAX(ts) FROM audit_log", [], |row| row.get(0))?;
+  let ts: Option<String> = conn.query_row(
+    "SELECT MAX(ts) FROM audit_log WHERE action <> 'PURGE_AUDIT'",
+    [],
+    |row| row.get(0),
+  )?;
   Ok(ts.unwrap_or_else(|| "1970-01-01T00:00:00Z".to_string()))
 }
 
-pub fn resolve_sync_conflict(state: &AppState, action: &str) -> Result<(), AppError> {
+pub fn changes_since(conn: &Connection, ts: Option<&str>, limit: i64) -> Result<Vec<AuditLogEntry>, AppError> {
+  let mut stmt = conn.prepare(
+    "SELECT id, ts, actor, action, entity_type, entity_id, ref_id, payload_json, details
+     FROM audit_log
+     WHERE ts > ?1
+     ORDER BY ts DESC, id DESC
+     LIMIT ?2",
+  )?;
+  let since = ts.unwrap_or("1970-01-01T00:00:00Z");
+  let rows = stmt.query_map(params![since, limit], |row| map_audit_row(row))?;
+  let mut items = Vec::new();
+  for row in rows {
+    items.push(row?);
+  }
+  Ok(items)
+}
+
+pub fn list_sync_log(conn: &Connection, page: i64, page_size: i64) -> Result<Paginated<SyncLogEntry>, AppError> {
+  let total: i64 = conn.query_row("SELECT COUNT(*) FROM sync_log", [], |row| row.get(0))?;
+  let mut stmt = conn.prepare(
+    "SELECT id, ts, event, direction, peer_device_id, peer_device_name, bytes, outcome, message
+     FROM sync_log
+     ORDER BY ts DESC, id DESC
+     LIMIT ?1 OFFSET ?2",
+  )?;
+  let rows = stmt.query_map(params![page_size, (page - 1) * page_size], |row| map_sync_log_row(row))?;
+  let mut items = Vec::new();
+  for row in rows {
+    items.push(row?);
+  }
+  Ok(Paginated { total, items })
+}
+
+fn map_sync_log_row(row: &rusqlite::Row) -> Result<SyncLogEntry, rusqlite::Error> {
+  Ok(SyncLogEntry {
+    id: row.get(0)?,
+    ts: row.get(1)?,
+    event: row.get(2)?,
+    direction: row.get(3)?,
+    peer_device_id: row.get(4)?,
+    peer_device_name: row.get(5)?,
+    bytes: row.get(6)?,
+    outcome: row.get(7)?,
+    message: row.get(8)?,
+  })
+}
+
+fn record_sync_event(state: &AppState, event: SyncLogEvent) {
+  let _ = db::with_conn(&state.db, |conn| {
+    conn.execute(
+      "INSERT INTO sync_log (ts, event, direction, peer_device_id, peer_device_name, bytes, outcome, message)
+       VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+      params![
+        Utc::now().to_rfc3339(),
+        event.event,
+        event.direction,
+        event.device_id,
+        event.device_name,
+        event.bytes as i64,
+        event.outcome,
+        event.message
+      ],
+    )?;
+    Ok(())
+  });
+}
+
+fn read_sync_log(conn: &Connection) -> Result<Vec<SyncLogEntry>, AppError> {
+  let mut stmt = conn.prepare(
+    "SELECT id, ts, event, direction, peer_device_id, peer_device_name, bytes, outcome, message
+     FROM sync_log
+     ORDER BY id",
+  )?;
+  let rows = stmt.query_map([], |row| map_sync_log_row(row))?;
+  let mut items = Vec::new();
+  for row in rows {
+    items.push(row?);
+  }
+  Ok(items)
+}
+
+fn restore_sync_log(conn: &Connection, entries: &[SyncLogEntry]) -> Result<(), AppError> {
+  conn.execute("DELETE FROM sync_log", [])?;
+  for entry in entries {
+    conn.execute(
+      "INSERT INTO sync_log (ts, event, direction, peer_device_id, peer_device_name, bytes, outcome, message)
+       VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+      params![
+        entry.ts,
+        entry.event,
+        entry.direction,
+        entry.peer_device_id,
+        entry.peer_device_name,
+        entry.bytes,
+        entry.outcome,
+        entry.message
+      ],
+    )?;
+  }
+  Ok(())
+}
+
+pub fn resolve_sync_conflict(handle: &AppHandle, state: &AppState, action: &str) -> Result<(), AppError> {
   let pending = state
     .sync
     .get_pending_conflict()?
     .ok_or_else(|| AppError::new("SYNC_CONFLICT", "Kein Konflikt vorhanden"))?;
+  if !matches!(action, "KEEP_LOCAL" | "USE_REMOTE" | "MERGE" | "DISMISS") {
+    return Err(AppError::new("SYNC_CONFLICT", "Unbekannte Konfliktaktion"));
+  }
 
+  let result = apply_conflict_action(handle, state, &pending, action);
+  let message = match &result {
+    Ok(()) => action.to_string(),
+    Err(err) => format!("{action}: {}", err.message),
+  };
+  record_sync_event(
+    state,
+    SyncLogEvent {
+      event: match action {
+        "MERGE" => "MERGE",
+        "DISMISS" => "DISMISS_CONFLICT",
+        _ => "RESOLVE_CONFLICT",
+      },
+      direction: None,
+      device_id: Some(&pending.device_id),
+      device_name: Some(&pending.device_name),
+      bytes: 0,
+      outcome: match (&result, action) {
+        (Err(_), _) => OUTCOME_FAILED,
+        (Ok(()), "DISMISS") => OUTCOME_SKIPPED,
+        (Ok(()), _) => OUTCOME_OK,
+      },
+      message: Some(&message),
+    },
+  );
+  result
+}
+
+fn apply_conflict_action(
+  handle: &AppHandle,
+  state: &AppState,
+  pending: &PendingConflict,
+  action: &str,
+) -> Result<(), AppError> {
   let device_id = pending.device_id.clone();
   let archive_path = pending.archive_path.clone();
 
@@ -318,7 +696,8 @@ pub fn resolve_sync_conflict(state: &AppState, action: &str) -> Result<(), AppEr
       let archive_path = archive_path.ok_or_else(|| {
         AppError::new("SYNC_CONFLICT", "Kein Remote-Datensatz fuer die Wiederherstellung vorhanden.")
       })?;
-      apply_remote_restore(state, &archive_path, Some("SYNC_RESTORE_REMOTE"))?;
+      ensure_conflict_archive(&archive_path)?;
+      apply_remote_restore(handle, state, &archive_path, Some("SYNC_RESTORE_REMOTE"))?;
       state.sync.update_device_sync(&device_id, Some(&pending.remote_last_change))?;
       state.sync.clear_pending_conflict()?;
       let _ = fs::remove_file(archive_path);
@@ -327,27 +706,273 @@ pub fn resolve_sync_conflict(state: &AppState, action: &str) -> Result<(), AppEr
     "MERGE" => {
       let archive_path = archive_path
         .ok_or_else(|| AppError::new("SYNC_CONFLICT", "Kein Remote-Datensatz zum Mergen vorhanden."))?;
-      merge_sync_backup(state, &archive_path)?;
+      ensure_conflict_archive(&archive_path)?;
+      merge_sync_backup(handle, state, &archive_path)?;
       state.sync.update_device_sync(&device_id, Some(&pending.remote_last_change))?;
       state.sync.clear_pending_conflict()?;
       let _ = fs::remove_file(archive_path);
       Ok(())
     }
+    // Only hides the banner: data stays diverged, the archive stays on disk and
+    // last_sync_at is untouched, so the next sync reports the conflict again.
+    "DISMISS" => {
+      state.sync.clear_pending_conflict()?;
+      Ok(())
+    }
     _ => Err(AppError::new("SYNC_CONFLICT", "Unbekannte Konfliktaktion")),
   }
 }
 
-fn handle_sync_request(mut request: Request, state: &AppState) {
+// The archive can vanish between app restarts; only KEEP_LOCAL still works then.
+fn ensure_conflict_archive(archive_path: &str) -> Result<(), AppError> {
+  if Path::new(archive_path).is_file() {
+    return Ok(());
+  }
+  Err(AppError::new(
+    "SYNC_ARCHIVE_MISSING",
+    "Remote-Datensatz des Konflikts ist nicht mehr vorhanden. Bitte lokale Daten behalten und erneut synchronisieren.",
+  )
+  .with_details(serde_json::json!({
+    "archive_path": archive_path,
+    "allowed_actions": ["KEEP_LOCAL"],
+  })))
+}
+
+pub fn pair_with_peer(state: &AppState, peer_ip: &str, port: Option<u16>, code: &str) -> Result<(), AppError> {
+  let port = port.unwrap_or(SYNC_PORT_FALLBACK);
+  let (device_id, device_name) = state.sync.device_identity()?;
+  let body = serde_json::to_vec(&PairRequest {
+    code: code.trim().to_string(),
+    device_id,
+    device_name,
+  })
+  .map_err(|err| AppError::new("SYNC_PAIR", err.to_string()))?;
+  let (status, response) = peer_request(peer_ip, port, "POST", "/sync/pair", &[], &body)?;
+  if status != 200 {
+    return Err(peer_error(status, &response, "SYNC_PAIR"));
+  }
+  let paired: PairResponse = serde_json::from_slice(&response)
+    .map_err(|_| AppError::new("SYNC_PAIR", "Antwort des Geraets ist ungueltig."))?;
+  state.sync.store_peer_token(
+    &paired.server_device_id,
+    &paired.server_device_name,
+    &paired.device_token,
+    peer_ip,
+    port,
+  )
+}
+
+pub fn push_to_peer(handle: &AppHandle, state: &AppState, peer_ip: &str, port: Option<u16>) -> Result<(), AppError> {
+  let peer = state.sync.peer_for_ip(peer_ip)?.ok_or_else(|| {
+    AppError::new(
+      "SYNC_PEER_UNKNOWN",
+      "Geraet ist nicht gekoppelt. Bitte zuerst mit dem Pairing-Code des Geraets koppeln.",
+    )
+  })?;
+  let port = port.or(peer.peer_port).unwrap_or(SYNC_PORT_FALLBACK);
+  let peer_token = peer.peer_token.clone().unwrap_or_default();
+
+  let result = push_backup_to_peer(handle, state, &peer, peer_ip, port, &peer_token);
+  let (outcome, bytes, message) = match &result {
+    Ok(bytes) => (OUTCOME_OK, *bytes, None),
+    Err(err) if err.code == "SYNC_CONFLICT" => (OUTCOME_CONFLICT, 0, Some(err.message.clone())),
+    Err(err) if err.code == "SYNC_REMOTE_NEWER" => (OUTCOME_SKIPPED, 0, Some(err.message.clone())),
+    Err(err) => (OUTCOME_FAILED, 0, Some(err.message.clone())),
+  };
+  emit_progress(
+    handle,
+    DIRECTION_OUTGOING,
+    if result.is_ok() { "DONE" } else { "FAILED" },
+    Some(&peer.device_name),
+    bytes,
+    None,
+  );
+  record_sync_event(
+    state,
+    SyncLogEvent {
+      event: "PUSH",
+      direction: Some(DIRECTION_OUTGOING),
+      device_id: Some(&peer.device_id),
+      device_name: Some(&peer.device_name),
+      bytes,
+      outcome,
+      message: message.as_deref(),
+    },
+  );
+  result.map(|_| ())
+}
+
+fn push_backup_to_peer(
+  handle: &AppHandle,
+  state: &AppState,
+  peer: &PairedDevice,
+  peer_ip: &str,
+  port: u16,
+  peer_token: &str,
+) -> Result<u64, AppError> {
+  let (status, response) = peer_request(peer_ip, port, "GET", "/sync/status", &[], &[])?;
+  if status != 200 {
+    return Err(peer_error(status, &response, "SYNC_PEER"));
+  }
+  let remote: StatusResponse = serde_json::from_slice(&response)
+    .map_err(|_| AppError::new("SYNC_PEER", "Antwort des Geraets ist ungueltig."))?;
+
+  emit_progress(handle, DIRECTION_OUTGOING, "CREATING_BACKUP", Some(&peer.device_name), 0, None);
+  db::with_conn(&state.db, |conn| db::ensure_wal_drained(conn))?;
+  let local_last_change = db::with_conn(&state.db, |conn| get_last_change(conn))?;
+  let temp_dir = state.app_dir.join("SyncTemp");
+  fs::create_dir_all(&temp_dir)?;
+  let filename = temp_dir.join(format!("sync_push_{}.zip", Utc::now().timestamp()));
+  let backup_path = backup::create_backup(
+    &state.app_dir,
+    &state.db.db_path,
+    &state.receipt_base,
+    true,
+    Some(filename.to_string_lossy().to_string()),
+  )?;
+  let body = fs::read(&backup_path);
+  let _ = fs::remove_file(&backup_path);
+  let body = body?;
+  let total = body.len() as u64;
+
+  emit_progress(handle, DIRECTION_OUTGOING, "TRANSFERRING", Some(&peer.device_name), 0, Some(total));
+  let (device_id, _) = state.sync.device_identity()?;
+  let headers = [
+    ("X-Pizza-Device-Id", device_id),
+    ("X-Pizza-Device-Token", peer_token.to_string()),
+    ("X-Pizza-Remote-Last-Change", local_last_change.clone()),
+    ("Content-Type", "application/zip".to_string()),
+  ];
+  let (status, response) = peer_request(peer_ip, port, "POST", "/sync/restore", &headers, &body)?;
+  if status == 409 {
+    let err = peer_error(status, &response, "SYNC_CONFLICT");
+    if err.code == "SYNC_CONFLICT" {
+      state.sync.set_pending_conflict(PendingConflict {
+        device_id: peer.device_id.clone(),
+        device_name: peer.device_name.clone(),
+        local_last_change,
+        remote_last_change: remote.last_change.clone(),
+        received_at: Utc::now().to_rfc3339(),
+        archive_path: None,
+        local_summary: build_conflict_summary(&state.db).ok(),
+        remote_summary: None,
+      })?;
+      return Err(err);
+    }
+    if err.code == "SYNC_LOCAL_NEWER" {
+      state
+        .sync
+        .update_device_seen(&peer.device_id, None, None, Some(&remote.last_change))?;
+      return Err(AppError::new("SYNC_REMOTE_NEWER", "Daten auf dem anderen Geraet sind aktueller."));
+    }
+    return Err(err);
+  }
+  if status != 200 {
+    return Err(peer_error(status, &response, "SYNC_PUSH"));
+  }
+
+  state.sync.update_device_sync(&peer.device_id, Some(&local_last_change))?;
+  Ok(total)
+}
+
+fn peer_request(
+  peer_ip: &str,
+  port: u16,
+  method: &str,
+  path: &str,
+  headers: &[(&str, String)],
+  body: &[u8],
+) -> Result<(u16, Vec<u8>), AppError> {
+  let offline = |detail: String| AppError::new("SYNC_PEER_UNREACHABLE", format!("Geraet nicht erreichbar: {detail}"));
+  let addr = (peer_ip, port)
+    .to_socket_addrs()
+    .map_err(|err| offline(err.to_string()))?
+    .next()
+    .ok_or_else(|| offline(peer_ip.to_string()))?;
+  let mut stream = TcpStream::connect_timeout(&addr, PEER_CONNECT_TIMEOUT).map_err(|err| offline(err.to_string()))?;
+  stream.set_read_timeout(Some(PEER_IO_TIMEOUT))?;
+  stream.set_write_timeout(Some(PEER_IO_TIMEOUT))?;
+
+  // HTTP/1.0 keeps tiny_http from answering with a chunked body.
+  let mut head = format!(
+    "{method} {path} HTTP/1.0\r\nHost: {peer_ip}:{port}\r\nContent-Length: {}\r\n",
+    body.len()
+  );
+  for (name, value) in headers {
+    head.push_str(&format!("{name}: {value}\r\n"));
+  }
+  head.push_str("\r\n");
+  stream.write_all(head.as_bytes())?;
+  stream.write_all(body)?;
+  stream.flush()?;
+
+  let mut response = Vec::new();
+  stream.read_to_end(&mut response)?;
+  let invalid = || AppError::new("SYNC_PEER", "Antwort des Geraets ist ungueltig.");
+  let split = response
+    .windows(4)
+    .position(|window| window == b"\r\n\r\n")
+    .ok_or_else(invalid)?;
+  let status = String::from_utf8_lossy(&response[..split])
+    .lines()
+    .next()
+    .and_then(|line| line.split_whitespace().nth(1))
+    .and_then(|code| code.parse::<u16>().ok())
+    .ok_or_else(invalid)?;
+  Ok((status, response[split + 4..].to_vec()))
+}
+
+fn peer_error(status: u16, body: &[u8], fallback_code: &str) -> AppError {
+  let parsed: Option<serde_json::Value> = serde_json::from_slice(body).ok();
+  let code = parsed
+    .as_ref()
+    .and_then(|value| value.get("code"))
+    .and_then(|value| value.as_str())
+    .unwrap_or(fallback_code);
+  let message = parsed
+    .as_ref()
+    .and_then(|value| value.get("message"))
+    .and_then(|value| value.as_str())
+    .map(|value| value.to_string())
+    .unwrap_or_else(|| format!("Geraet antwortete mit Status {status}"));
+  AppError::new(code, message)
+}
+
+fn handle_sync_request(mut request: Request, handle: &AppHandle, state: &AppState) {
   let method = request.method().clone();
   let url = request.url().split('?').next().unwrap_or("").to_string();
+  let direction = match (&method, url.as_str()) {
+    (Method::Get, "/sync/backup") => Some(DIRECTION_OUTGOING),
+    (Method::Post, "/sync/restore") => Some(DIRECTION_INCOMING),
+    _ => None,
+  };
+  // Progress is only reported for authenticated transfers so rejected requests don't show up as syncs.
+  let mut authorized = false;
   let response = match (method, url.as_str()) {
     (Method::Get, "/sync/status") => handle_status(state),
     (Method::Post, "/sync/pair") => handle_pair(&mut request, state),
-    (Method::Get, "/sync/backup") => handle_backup(&request, state),
-    (Method::Post, "/sync/restore") => handle_restore(&mut request, state),
+    (Method::Get, "/sync/backup") => match authorize_request(&request, state) {
+      Ok(auth) => {
+        authorized = true;
+        handle_backup(&request, handle, state, auth)
+      }
+      Err(response) => response,
+    },
+    (Method::Post, "/sync/restore") => match authorize_request(&request, state) {
+      Ok(auth) => {
+        authorized = true;
+        handle_restore(&mut request, handle, state, auth)
+      }
+      Err(response) => response,
+    },
     _ => json_error(StatusCode(404), "SYNC_NOT_FOUND", "Route nicht gefunden"),
   };
-  let _ = request.respond(response);
+  let succeeded = response.status_code().0 == 200;
+  let sent = request.respond(response).is_ok();
+  if let Some(direction) = direction.filter(|_| authorized) {
+    let phase = if succeeded && sent { "DONE" } else { "FAILED" };
+    emit_progress(handle, direction, phase, None, 0, None);
+  }
 }
 
 fn handle_status(state: &AppState) -> Response<std::io::Cursor<Vec<u8>>> {
@@ -367,6 +992,12 @@ fn handle_status(state: &AppState) -> Response<std::io::Cursor<Vec<u8>>> {
 }
 
 fn handle_pair(request: &mut Request, state: &AppState) -> Response<std::io::Cursor<Vec<u8>>> {
+  let remote_ip = request.remote_addr().map(|addr| addr.ip().to_string());
+  let attempt_key = remote_ip.clone().unwrap_or_else(|| "unknown".to_string());
+  if let Err(err) = state.sync.check_pair_allowed(&attempt_key) {
+    return json_error(StatusCode(429), &err.code, &err.message);
+  }
+
   let mut body = Vec::new();
   if request.as_reader().read_to_end(&mut body).is_err() {
     return json_error(StatusCode(400), "SYNC_PAIR", "Pairing-Daten konnten nicht gelesen werden.");
@@ -376,14 +1007,19 @@ fn handle_pair(request: &mut Request, state: &AppState) -> Response<std::io::Cur
     Err(_) => return json_error(StatusCode(400), "SYNC_PAIR", "Pairing-Daten sind ungueltig."),
   };
 
-  let remote_ip = request.remote_addr().map(|addr| addr.ip().to_string());
   let token = match state
     .sync
     .pair_device(&payload.code, &payload.device_id, &payload.device_name, remote_ip)
   {
     Ok(token) => token,
-    Err(err) => return json_error(StatusCode(401), &err.code, &err.message),
+    Err(err) => {
+      if err.code == "SYNC_PAIR_CODE" {
+        let _ = state.sync.record_pair_failure(&attempt_key);
+      }
+      return json_error(StatusCode(401), &err.code, &err.message);
+    }
   };
+  let _ = state.sync.reset_pair_attempts(&attempt_key);
 
   let last_change = db::with_conn(&state.db, |conn| get_last_change(conn)).unwrap_or_else(|_| "unknown".to_string());
   let (server_device_id, server_device_name) = match state.sync.device_identity() {
@@ -402,12 +1038,12 @@ fn handle_pair(request: &mut Request, state: &AppState) -> Response<std::io::Cur
   )
 }
 
-fn handle_backup(request: &Request, state: &AppState) -> Response<std::io::Cursor<Vec<u8>>> {
-  let auth = match authorize_request(request, state) {
-    Ok(auth) => auth,
-    Err(response) => return response,
-  };
-
+fn handle_backup(
+  request: &Request,
+  handle: &AppHandle,
+  state: &AppState,
+  auth: DeviceAuth,
+) -> Response<std::io::Cursor<Vec<u8>>> {
   let remote_last_change = match read_remote_last_change(request) {
     Ok(value) => value,
     Err(response) => return response,
@@ -425,6 +1061,7 @@ fn handle_backup(request: &Request, state: &AppState) -> Response<std::io::Curso
       local_summary: build_conflict_summary(&state.db).ok(),
       remote_summary: None,
     });
+    log_backup_event(state, &auth, 0, OUTCOME_CONFLICT, Some("Beide Seiten wurden geaendert."));
     return json_error(StatusCode(409), "SYNC_CONFLICT", "Beide Seiten wurden geaendert.");
   }
 
@@ -432,14 +1069,19 @@ fn handle_backup(request: &Request, state: &AppState) -> Response<std::io::Curso
     let _ = state
       .sync
       .update_device_seen(&auth.device_id, None, None, Some(&remote_last_change));
+    log_backup_event(state, &auth, 0, OUTCOME_SKIPPED, Some("Remote-Daten sind aktueller."));
     return json_error(StatusCode(409), "SYNC_REMOTE_NEWER", "Remote-Daten sind aktueller.");
   }
 
+  emit_progress(handle, DIRECTION_OUTGOING, "CREATING_BACKUP", Some(&auth.device_name), 0, None);
   let temp_dir = state.app_dir.join("SyncTemp");
   let _ = fs::create_dir_all(&temp_dir);
   let filename = temp_dir.join(format!("sync_backup_{}.zip", Utc::now().timestamp()));
 
-  let _ = db::with_conn(&state.db, |conn| db::checkpoint(conn));
+  if let Err(err) = db::with_conn(&state.db, |conn| db::ensure_wal_drained(conn)) {
+    log_backup_event(state, &auth, 0, OUTCOME_FAILED, Some(&err.message));
+    return json_error(StatusCode(503), &err.code, &err.message);
+  }
   let backup_path = match backup::create_backup(
     &state.app_dir,
     &state.db.db_path,
@@ -448,42 +1090,53 @@ fn handle_backup(request: &Request, state: &AppState) -> Response<std::io::Curso
     Some(filename.to_string_lossy().to_string()),
   ) {
     Ok(path) => path,
-    Err(err) => return json_error(StatusCode(500), &err.code, &err.message),
+    Err(err) => {
+      log_backup_event(state, &auth, 0, OUTCOME_FAILED, Some(&err.message));
+      return json_error(StatusCode(500), &err.code, &err.message);
+    }
   };
 
   let file_bytes = match fs::read(&backup_path) {
     Ok(bytes) => bytes,
     Err(err) => {
       let message = err.to_string();
+      log_backup_event(state, &auth, 0, OUTCOME_FAILED, Some(&message));
       return json_error(StatusCode(500), "SYNC_BACKUP", &message);
     }
   };
   schedule_cleanup(PathBuf::from(&backup_path));
+  let total = file_bytes.len() as u64;
+  emit_progress(handle, DIRECTION_OUTGOING, "TRANSFERRING", Some(&auth.device_name), 0, Some(total));
 
   let _ = state
     .sync
     .update_device_sync(&auth.device_id, Some(&remote_last_change));
+  log_backup_event(state, &auth, total, OUTCOME_OK, None);
 
   let mut response = Response::from_data(file_bytes);
   response.add_header(json_header("Content-Type", "application/zip"));
   response
 }
 
-fn handle_restore(request: &mut Request, state: &AppState) -> Response<std::io::Cursor<Vec<u8>>> {
-  let auth = match authorize_request(request, state) {
-    Ok(auth) => auth,
-    Err(response) => return response,
-  };
-
+fn handle_restore(
+  request: &mut Request,
+  handle: &AppHandle,
+  state: &AppState,
+  auth: DeviceAuth,
+) -> Response<std::io::Cursor<Vec<u8>>> {
   let remote_last_change = match read_remote_last_change(request) {
     Ok(value) => value,
     Err(response) => return response,
   };
 
-  let mut body = Vec::new();
-  if request.as_reader().read_to_end(&mut body).is_err() {
-    return json_error(StatusCode(400), "SYNC_RESTORE", "Backup konnte nicht gelesen werden.");
-  }
+  let body = match read_body_with_progress(request, handle, &auth.device_name) {
+    Ok(body) => body,
+    Err(_) => {
+      log_restore_event(state, &auth, 0, OUTCOME_FAILED, Some("Backup konnte nicht gelesen werden."));
+      return json_error(StatusCode(400), "SYNC_RESTORE", "Backup konnte nicht gelesen werden.");
+    }
+  };
+  let total = body.len() as u64;
 
   let local_last_change = db::with_conn(&state.db, |conn| get_last_change(conn)).unwrap_or_else(|_| "unknown".to_string());
   if has_conflict(auth.last_sync_at.as_deref(), &local_last_change, &remote_last_change) {
@@ -503,6 +1156,7 @@ fn handle_restore(request: &mut Request, state: &AppState) -> Response<std::io::
       local_summary,
       remote_summary,
     });
+    log_restore_event(state, &auth, total, OUTCOME_CONFLICT, Some("Beide Seiten wurden geaendert."));
     return json_error(StatusCode(409), "SYNC_CONFLICT", "Beide Seiten wurden geaendert.");
   }
 
@@ -510,6 +1164,7 @@ fn handle_restore(request: &mut Request, state: &AppState) -> Response<std::io::
     let _ = state
       .sync
       .update_device_seen(&auth.device_id, None, None, Some(&remote_last_change));
+    log_restore_event(state, &auth, total, OUTCOME_SKIPPED, Some("Lokale Daten sind aktueller."));
     return json_error(StatusCode(409), "SYNC_LOCAL_NEWER", "Lokale Daten sind aktueller.");
   }
 
@@ -517,10 +1172,13 @@ fn handle_restore(request: &mut Request, state: &AppState) -> Response<std::io::
   let _ = fs::create_dir_all(&temp_dir);
   let archive_path = temp_dir.join(format!("sync_restore_{}.zip", Utc::now().timestamp()));
   if fs::write(&archive_path, &body).is_err() {
+    log_restore_event(state, &auth, total, OUTCOME_FAILED, Some("Backup konnte nicht gespeichert werden."));
     return json_error(StatusCode(500), "SYNC_RESTORE", "Backup konnte nicht gespeichert werden.");
   }
 
-  if let Err(err) = apply_remote_restore(state, archive_path.to_string_lossy().as_ref(), Some("SYNC_RESTORE")) {
+  emit_progress(handle, DIRECTION_INCOMING, "APPLYING", Some(&auth.device_name), total, Some(total));
+  if let Err(err) = apply_remote_restore(handle, state, archive_path.to_string_lossy().as_ref(), Some("SYNC_RESTORE")) {
+    log_restore_event(state, &auth, total, OUTCOME_FAILED, Some(&err.message));
     return json_error(StatusCode(500), &err.code, &err.message);
   }
   let _ = fs::remove_file(&archive_path);
@@ -528,53 +1186,104 @@ fn handle_restore(request: &mut Request, state: &AppState) -> Response<std::io::
   let _ = state
     .sync
     .update_device_sync(&auth.device_id, Some(&remote_last_change));
+  log_restore_event(state, &auth, total, OUTCOME_OK, None);
   json_response(StatusCode(200), &serde_json::json!({ "ok": true }))
 }
 
-fn apply_remote_restore(state: &AppState, archive_path: &str, audit_action: Option<&str>) -> Result<(), AppError> {
+fn log_backup_event(state: &AppState, auth: &DeviceAuth, bytes: u64, outcome: &str, message: Option<&str>) {
+  record_sync_event(
+    state,
+    SyncLogEvent {
+      event: "PULL",
+      direction: Some(DIRECTION_OUTGOING),
+      device_id: Some(&auth.device_id),
+      device_name: Some(&auth.device_name),
+      bytes,
+      outcome,
+      message,
+    },
+  );
+}
+
+fn log_restore_event(state: &AppState, auth: &DeviceAuth, bytes: u64, outcome: &str, message: Option<&str>) {
+  record_sync_event(
+    state,
+    SyncLogEvent {
+      event: "PUSH",
+      direction: Some(DIRECTION_INCOMING),
+      device_id: Some(&auth.device_id),
+      device_name: Some(&auth.device_name),
+      bytes,
+      outcome,
+      message,
+    },
+  );
+}
+
+fn apply_remote_restore(
+  handle: &AppHandle,
+  state: &AppState,
+  archive_path: &str,
+  audit_action: Option<&str>,
+) -> Result<(), AppError> {
   let _ = db::with_conn(&state.db, |conn| db::checkpoint(conn));
+  let local_sync_log = db::with_conn(&state.db, |conn| read_sync_log(conn)).unwrap_or_default();
   backup::restore_backup(archive_path, &state.db.db_path, &state.receipt_base)?;
-  db::reload_connection(&state.db)?;
 
-  db::with_conn(&state.db, |conn| {
-    fix_receipt_paths(conn, &state.receipt_base)?;
-    ensure_receipt_setting(conn, &state.receipt_base)?;
+  let applied = db::reload_connection(&state.db).and_then(|_| db::with_conn_retry(&state.db, |conn| {
+    let check = db::verify_restored_db(conn)?;
+    // One transaction per attempt so a DB_BUSY retry never replays half-applied writes.
+    let tx = conn.transaction()?;
+    db::seed_defaults(&tx, &state.receipt_base)?;
+    restore_sync_log(&tx, &local_sync_log)?;
+    fix_receipt_paths(&tx, &state.receipt_base)?;
+    ensure_receipt_setting(&tx, &state.receipt_base)?;
     if let Some(action) = audit_action {
       append_audit(
-        conn,
+        &tx,
         Some("sync".to_string()),
         action,
         "SYNC",
         None,
         None,
         "{}".to_string(),
-        Some("Restore via lokalem Sync".to_string()),
+        Some(format!("Restore via lokalem Sync, {} Buchungen", check.tx_count)),
       )?;
     }
+    tx.commit()?;
     Ok(())
-  })?;
+  }));
+  if let Err(err) = applied {
+    backup::rollback_restore(&state.db.db_path)?;
+    db::reload_connection(&state.db)?;
+    return Err(err);
+  }
 
+  events::emit_data_changed(handle, "ALL", None, None);
   Ok(())
 }
 
-fn merge_sync_backup(state: &AppState, archive_path: &str) -> Result<(), AppError> {
+fn merge_sync_backup(handle: &AppHandle, state: &AppState, archive_path: &str) -> Result<(), AppError> {
   let temp_dir = std::env::temp_dir().join(format!("pizza_damico_sync_merge_{}", Utc::now().timestamp()));
   fs::create_dir_all(&temp_dir)?;
   let temp_db = temp_dir.join("db.sqlite");
   let temp_receipts = temp_dir.join("receipts");
 
   backup::restore_backup(archive_path, &temp_db, &temp_receipts)?;
-  let remote_conn = Connection::open(&temp_db)?;
+  let mut remote_conn = Connection::open(&temp_db)?;
+  db::verify_restored_db(&mut remote_conn)?;
 
   copy_remote_receipts(&temp_receipts, &state.receipt_base)?;
 
-  db::with_conn(&state.db, |conn| {
-    merge_categories(conn, &remote_conn)?;
-    merge_transactions(conn, &remote_conn, &state.receipt_base)?;
-    merge_month_closing(conn, &remote_conn)?;
-    ensure_receipt_setting(conn, &state.receipt_base)?;
+  db::with_conn_retry(&state.db, |conn| {
+    let tx = conn.transaction()?;
+    merge_categories(&tx, &remote_conn)?;
+    merge_transactions(&tx, &remote_conn, &state.receipt_base)?;
+    merge_month_closing(&tx, &remote_conn)?;
+    merge_year_closing(&tx, &remote_conn)?;
+    ensure_receipt_setting(&tx, &state.receipt_base)?;
     append_audit(
-      conn,
+      &tx,
       Some("sync".to_string()),
       "SYNC_MERGE",
       "SYNC",
@@ -583,36 +1292,68 @@ fn merge_sync_backup(state: &AppState, archive_path: &str) -> Result<(), AppErro
       "{}".to_string(),
       Some("Merge via lokalem Sync".to_string()),
     )?;
+    tx.commit()?;
     Ok(())
   })?;
 
+  events::emit_data_changed(handle, "ALL", None, None);
   Ok(())
 }
 
-fn merge_categories(local: &Connection, remote: &Connection) -> Result<(), AppError> {
-  let mut stmt = remote.prepare("SELECT name, description, default_mwst_rate, is_active FROM categories")?;
+pub fn import_categories_from_backup(state: &AppState, archive_path: &str) -> Result<i64, AppError> {
+  let temp_dir = std::env::temp_dir().join(format!("pizza_damico_category_import_{}", Utc::now().timestamp()));
+  fs::create_dir_all(&temp_dir)?;
+  let temp_db = temp_dir.join("db.sqlite");
+  let temp_receipts = temp_dir.join("receipts");
+
+  let result = merge_categories_from_archive(state, archive_path, &temp_db, &temp_receipts);
+  let _ = fs::remove_dir_all(&temp_dir);
+  result
+}
+
+fn merge_categories_from_archive(
+  state: &AppState,
+  archive_path: &str,
+  temp_db: &Path,
+  temp_receipts: &Path,
+) -> Result<i64, AppError> {
+  backup::restore_backup(archive_path, temp_db, temp_receipts)?;
+  let mut remote_conn = Connection::open(temp_db)?;
+  db::verify_restored_db(&mut remote_conn)?;
+  db::with_conn(&state.db, |conn| merge_categories(conn, &remote_conn))
+}
+
+fn merge_categories(local: &Connection, remote: &Connection) -> Result<i64, AppError> {
+  let mut stmt = remote.prepare(
+    "SELECT name, description, default_mwst_rate, is_active, default_description, default_payment_method FROM categories",
+  )?;
   let rows = stmt.query_map([], |row| {
     Ok((
       row.get::<_, String>(0)?,
       row.get::<_, Option<String>>(1)?,
       row.get::<_, f64>(2)?,
       row.get::<_, i64>(3)?,
+      row.get::<_, Option<String>>(4)?,
+      row.get::<_, Option<String>>(5)?,
     ))
   })?;
 
+  let mut imported = 0_i64;
   for row in rows {
-    let (name, description, rate, is_active) = row?;
+    let (name, description, rate, is_active, default_description, default_payment_method) = row?;
     let existing: Option<i64> = local
       .query_row("SELECT id FROM categories WHERE name = ?1", params![name], |row| row.get(0))
       .optional()?;
     if existing.is_none() {
       local.execute(
-        "INSERT INTO categories (name, description, default_mwst_rate, is_active) VALUES (?1, ?2, ?3, ?4)",
-        params![name, description, rate, is_active],
+        "INSERT INTO categories (name, description, default_mwst_rate, is_active, default_description, default_payment_method)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![name, description, rate, is_active, default_description, default_payment_method],
       )?;
+      imported += 1;
     }
   }
-  Ok(())
+  Ok(imported)
 }
 
 fn merge_transactions(local: &Connection, remote: &Connection, receipt_base: &Path) -> Result<(), AppError> {
@@ -627,7 +1368,7 @@ fn merge_transactions(local: &Connection, remote: &Connection, receipt_base: &Pa
   let receipt_map = build_receipt_name_map(receipt_base);
 
   let mut stmt = remote.prepare(
-    "SELECT public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at\n     FROM transactions",
+    "SELECT public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at, created_by, updated_by,\n            receipt_not_required, is_demo\n     FROM transactions",
   )?;
   let rows = stmt.query_map([], |row| {
     Ok((
@@ -646,6 +1387,10 @@ fn merge_transactions(local: &Connection, remote: &Connection, receipt_base: &Pa
       row.get::<_, Option<String>>(12)?,
       row.get::<_, String>(13)?,
       row.get::<_, String>(14)?,
+      row.get::<_, Option<String>>(15)?,
+      row.get::<_, Option<String>>(16)?,
+      row.get::<_, i64>(17)?,
+      row.get::<_, i64>(18)?,
     ))
   })?;
 
@@ -666,6 +1411,10 @@ fn merge_transactions(local: &Connection, remote: &Connection, receipt_base: &Pa
       ref_public_id,
       created_at,
       updated_at,
+      created_by,
+      updated_by,
+      receipt_not_required,
+      is_demo,
     ) = row?;
 
     let category_name = match category_id {
@@ -692,7 +1441,7 @@ fn merge_transactions(local: &Connection, remote: &Connection, receipt_base: &Pa
       if is_after(&updated_at, &local_updated_at) {
         let receipt_value = mapped_receipt_path.or(existing_receipt_path);
         local.execute(
-          "UPDATE transactions SET date = ?2, year = ?3, month = ?4, type = ?5, payment_method = ?6, category_id = ?7, description = ?8,\n           amount_chf = ?9, mwst_rate = ?10, receipt_path = ?11, note = ?12, ref_public_id = ?13, created_at = ?14, updated_at = ?15 WHERE public_id = ?1",
+          "UPDATE transactions SET date = ?2, year = ?3, month = ?4, type = ?5, payment_method = ?6, category_id = ?7, description = ?8,\n           amount_chf = ?9, mwst_rate = ?10, receipt_path = ?11, note = ?12, ref_public_id = ?13, created_at = ?14, updated_at = ?15,\n           created_by = ?16, updated_by = ?17, receipt_not_required = ?18, is_demo = ?19 WHERE public_id = ?1",
           params![
             public_id,
             date,
@@ -709,12 +1458,16 @@ fn merge_transactions(local: &Connection, remote: &Connection, receipt_base: &Pa
             ref_public_id,
             created_at,
             updated_at,
+            created_by,
+            updated_by,
+            receipt_not_required,
+            is_demo,
           ],
         )?;
       }
     } else {
       local.execute(
-        "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at)\n         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        "INSERT INTO transactions (public_id, date, year, month, type, payment_method, category_id, description, amount_chf, mwst_rate, receipt_path, note, ref_public_id, created_at, updated_at, created_by, updated_by, receipt_not_required, is_demo)\n         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)",
         params![
           public_id,
           date,
@@ -731,6 +1484,10 @@ fn merge_transactions(local: &Connection, remote: &Connection, receipt_base: &Pa
           ref_public_id,
           created_at,
           updated_at,
+          created_by,
+          updated_by,
+          receipt_not_required,
+          is_demo,
         ],
       )?;
     }
@@ -740,7 +1497,7 @@ fn merge_transactions(local: &Connection, remote: &Connection, receipt_base: &Pa
 }
 
 fn merge_month_closing(local: &Connection, remote: &Connection) -> Result<(), AppError> {
-  let mut stmt = remote.prepare("SELECT year, month, is_closed, closed_at, closed_by FROM month_closing")?;
+  let mut stmt = remote.prepare("SELECT year, month, is_closed, closed_at, closed_by, comment FROM month_closing")?;
   let rows = stmt.query_map([], |row| {
     Ok((
       row.get::<_, i32>(0)?,
@@ -748,11 +1505,12 @@ fn merge_month_closing(local: &Connection, remote: &Connection) -> Result<(), Ap
       row.get::<_, i64>(2)?,
       row.get::<_, Option<String>>(3)?,
       row.get::<_, Option<String>>(4)?,
+      row.get::<_, Option<String>>(5)?,
     ))
   })?;
 
   for row in rows {
-    let (year, month, is_closed, closed_at, closed_by) = row?;
+    let (year, month, is_closed, closed_at, closed_by, comment) = row?;
     let existing: Option<(i64, Option<String>)> = local
       .query_row(
         "SELECT is_closed, closed_at FROM month_closing WHERE year = ?1 AND month = ?2",
@@ -765,24 +1523,24 @@ fn merge_month_closing(local: &Connection, remote: &Connection) -> Result<(), Ap
       Some((local_closed, local_closed_at)) => {
         if is_closed == 1 && local_closed == 0 {
           local.execute(
-            "UPDATE month_closing SET is_closed = 1, closed_at = ?3, closed_by = ?4 WHERE year = ?1 AND month = ?2",
-            params![year, month, closed_at, closed_by],
+            "UPDATE month_closing SET is_closed = 1, closed_at = ?3, closed_by = ?4, comment = ?5 WHERE year = ?1 AND month = ?2",
+            params![year, month, closed_at, closed_by, comment],
           )?;
         } else if is_closed == 1 && local_closed == 1 {
           let remote_time = closed_at.clone().unwrap_or_default();
           let local_time = local_closed_at.unwrap_or_default();
           if is_after(&remote_time, &local_time) {
             local.execute(
-              "UPDATE month_closing SET closed_at = ?3, closed_by = ?4 WHERE year = ?1 AND month = ?2",
-              params![year, month, closed_at, closed_by],
+              "UPDATE month_closing SET closed_at = ?3, closed_by = ?4, comment = ?5 WHERE year = ?1 AND month = ?2",
+              params![year, month, closed_at, closed_by, comment],
             )?;
           }
         }
       }
       None => {
         local.execute(
-          "INSERT INTO month_closing (year, month, is_closed, closed_at, closed_by) VALUES (?1, ?2, ?3, ?4, ?5)",
-          params![year, month, is_closed, closed_at, closed_by],
+          "INSERT INTO month_closing (year, month, is_closed, closed_at, closed_by, comment) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+          params![year, month, is_closed, closed_at, closed_by, comment],
         )?;
       }
     }
@@ -791,6 +1549,40 @@ fn merge_month_closing(local: &Connection, remote: &Connection) -> Result<(), Ap
   Ok(())
 }
 
+fn merge_year_closing(local: &Connection, remote: &Connection) -> Result<(), AppError> {
+  let has_table: i64 = remote.query_row(
+    "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'year_closing'",
+    [],
+    |row| row.get(0),
+  )?;
+  if has_table == 0 {
+    return Ok(());
+  }
+
+  let mut stmt = remote.prepare("SELECT year, is_closed, closed_at, closed_by FROM year_closing WHERE is_closed = 1")?;
+  let rows = stmt.query_map([], |row| {
+    Ok((
+      row.get::<_, i32>(0)?,
+      row.get::<_, Option<String>>(2)?,
+      row.get::<_, Option<String>>(3)?,
+    ))
+  })?;
+
+  for row in rows {
+    let (year, closed_at, closed_by) = row?;
+    local.execute(
+      "INSERT OR IGNORE INTO year_closing (year, is_closed, closed_at, closed_by) VALUES (?1, 0, NULL, NULL)",
+      params![year],
+    )?;
+    local.execute(
+      "UPDATE year_closing SET is_closed = 1, closed_at = ?2, closed_by = ?3 WHERE year = ?1 AND is_closed = 0",
+      params![year, closed_at, closed_by],
+    )?;
+  }
+
+  Ok(())
+}
+
 fn build_conflict_summary(db: &crate::db::Db) -> Result<SyncConflictSummary, AppError> {
   db::with_conn(db, |conn| build_summary_from_conn(conn))
 }
@@ -856,7 +1648,7 @@ fn build_remote_summary(path: &str) -> Result<Option<SyncConflictSummary>, AppEr
   Ok(Some(summary))
 }
 
-fn ensure_receipt_setting(conn: &Connection, receipt_base: &Path) -> Result<(), AppError> {
+pub(crate) fn ensure_receipt_setting(conn: &Connection, receipt_base: &Path) -> Result<(), AppError> {
   let value = receipt_base.to_string_lossy().to_string();
   conn.execute(
     "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
@@ -865,7 +1657,88 @@ fn ensure_receipt_setting(conn: &Connection, receipt_base: &Path) -> Result<(),
   Ok(())
 }
 
-fn fix_receipt_paths(conn: &Connection, receipt_base: &Path) -> Result<(), AppError> {
+pub(crate) fn relink_receipt_paths(
+  conn: &Connection,
+  old_base: &Path,
+  new_base: &Path,
+  move_files: bool,
+) -> Result<ReceiptRelinkSummary, AppError> {
+  let mut stmt = conn.prepare("SELECT public_id, receipt_path FROM transactions WHERE receipt_path IS NOT NULL")?;
+  let rows: Vec<(String, String)> = stmt
+    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+    .collect::<Result<_, _>>()?;
+  drop(stmt);
+
+  let mut summary = ReceiptRelinkSummary {
+    relinked: 0,
+    moved: 0,
+    broken: 0,
+    broken_public_ids: Vec::new(),
+  };
+  let mut unresolved = Vec::new();
+  for (public_id, receipt_path) in rows {
+    let current = Path::new(&receipt_path);
+    let Ok(relative) = current.strip_prefix(old_base) else {
+      if !current.exists() {
+        unresolved.push((public_id, receipt_path));
+      }
+      continue;
+    };
+    let target = new_base.join(relative);
+    if move_files && current.exists() && !target.exists() {
+      // One unreadable or locked file must not abort the relink of all others.
+      if move_receipt_file(current, &target).is_err() {
+        summary.broken += 1;
+        summary.broken_public_ids.push(public_id);
+        continue;
+      }
+      summary.moved += 1;
+    }
+    if target.exists() {
+      conn.execute(
+        "UPDATE transactions SET receipt_path = ?1 WHERE public_id = ?2",
+        params![target.to_string_lossy().to_string(), public_id],
+      )?;
+      summary.relinked += 1;
+    } else if !current.exists() {
+      unresolved.push((public_id, receipt_path));
+    }
+  }
+
+  if !unresolved.is_empty() {
+    let name_map = build_receipt_name_map(new_base);
+    for (public_id, receipt_path) in unresolved {
+      if let Some(mapped) = map_receipt_path(&receipt_path, new_base, &name_map) {
+        conn.execute(
+          "UPDATE transactions SET receipt_path = ?1 WHERE public_id = ?2",
+          params![mapped, public_id],
+        )?;
+        summary.relinked += 1;
+      } else {
+        summary.broken += 1;
+        summary.broken_public_ids.push(public_id);
+      }
+    }
+  }
+  Ok(summary)
+}
+
+fn move_receipt_file(current: &Path, target: &Path) -> std::io::Result<()> {
+  if let Some(parent) = target.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  // rename fails across drives, so fall back to copy and remove.
+  if fs::rename(current, target).is_err() {
+    if let Err(err) = fs::copy(current, target) {
+      let _ = fs::remove_file(target);
+      return Err(err);
+    }
+    let _ = fs::remove_file(current);
+  }
+  Ok(())
+}
+
+pub(crate) fn fix_receipt_paths(conn: &Connection, receipt_base: &Path) -> Result<(), AppError> {
   let receipt_map = build_receipt_name_map(receipt_base);
   let mut stmt = conn.prepare("SELECT public_id, receipt_path FROM transactions WHERE receipt_path IS NOT NULL")?;
   let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
@@ -971,6 +1844,48 @@ fn authorize_request(request: &Request, state: &AppState) -> Result<DeviceAuth,
   })
 }
 
+fn read_body_with_progress(request: &mut Request, handle: &AppHandle, device_name: &str) -> std::io::Result<Vec<u8>> {
+  let total = request.body_length().map(|length| length as u64);
+  let mut body = Vec::with_capacity(total.unwrap_or(0) as usize);
+  let mut buffer = [0_u8; 64 * 1024];
+  let mut last_emitted = 0_u64;
+  emit_progress(handle, DIRECTION_INCOMING, "TRANSFERRING", Some(device_name), 0, total);
+  loop {
+    let read = request.as_reader().read(&mut buffer)?;
+    if read == 0 {
+      break;
+    }
+    body.extend_from_slice(&buffer[..read]);
+    let done = body.len() as u64;
+    if done - last_emitted >= SYNC_PROGRESS_STEP {
+      last_emitted = done;
+      emit_progress(handle, DIRECTION_INCOMING, "TRANSFERRING", Some(device_name), done, total);
+    }
+  }
+  emit_progress(handle, DIRECTION_INCOMING, "TRANSFERRING", Some(device_name), body.len() as u64, total);
+  Ok(body)
+}
+
+fn emit_progress(
+  handle: &AppHandle,
+  direction: &str,
+  phase: &str,
+  device_name: Option<&str>,
+  bytes_done: u64,
+  bytes_total: Option<u64>,
+) {
+  let _ = handle.emit(
+    SYNC_PROGRESS_EVENT,
+    SyncProgress {
+      direction: direction.to_string(),
+      phase: phase.to_string(),
+      device_name: device_name.map(|name| name.to_string()),
+      bytes_done,
+      bytes_total,
+    },
+  );
+}
+
 fn read_remote_last_change(request: &Request) -> Result<String, Response<std::io::Cursor<Vec<u8>>>> {
   read_header(request, "X-Pizza-Remote-Last-Change")
     .ok_or_else(|| json_error(StatusCode(400), "SYNC_REMOTE_CHANGE", "Remote-Stand fehlt."))
@@ -1074,7 +1989,7 @@ fn save_store(path: &Path, store: &SyncStore) -> Result<(), AppError> {
 }
 
 fn store_conflict_archive(state: &AppState, device_id: &str, body: &[u8]) -> Option<String> {
-  let conflict_dir = state.app_dir.join("SyncConflicts");
+  let conflict_dir = state.app_dir.join(CONFLICT_ARCHIVE_DIR);
   if fs::create_dir_all(&conflict_dir).is_err() {
     return None;
   }
@@ -1085,9 +2000,72 @@ fn store_conflict_archive(state: &AppState, device_id: &str, body: &[u8]) -> Opt
   Some(filename.to_string_lossy().to_string())
 }
 
+fn cleanup_conflict_archives(state: &AppState) {
+  let conflict_dir = state.app_dir.join(CONFLICT_ARCHIVE_DIR);
+  let Ok(entries) = fs::read_dir(&conflict_dir) else {
+    return;
+  };
+  let pending_archive = state
+    .sync
+    .get_pending_conflict()
+    .ok()
+    .flatten()
+    .and_then(|conflict| conflict.archive_path)
+    .map(PathBuf::from);
+  for entry in entries.flatten() {
+    let path = entry.path();
+    if !path.is_file() || pending_archive.as_deref() == Some(path.as_path()) {
+      continue;
+    }
+    let expired = entry
+      .metadata()
+      .and_then(|meta| meta.modified())
+      .ok()
+      .and_then(|modified| modified.elapsed().ok())
+      .map(|age| age > CONFLICT_ARCHIVE_MAX_AGE)
+      .unwrap_or(false);
+    if expired {
+      let _ = fs::remove_file(&path);
+    }
+  }
+}
+
 fn schedule_cleanup(path: PathBuf) {
   std::thread::spawn(move || {
     std::thread::sleep(Duration::from_secs(90));
     let _ = fs::remove_file(path);
   });
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::net::TcpListener;
+
+  #[test]
+  fn audit_purge_does_not_move_last_change() {
+    let conn = crate::db::open_test_db();
+    conn
+      .execute(
+        "INSERT INTO audit_log (ts, actor, action, entity_type, payload_json) VALUES ('2024-05-10T00:00:00+00:00', NULL, 'CREATE', 'TRANSACTION', '{}')",
+        [],
+      )
+      .unwrap();
+    append_audit(&conn, None, "PURGE_AUDIT", "AUDIT_LOG", None, None, "{}".to_string(), None).unwrap();
+
+    assert_eq!(get_last_change(&conn).unwrap(), "2024-05-10T00:00:00+00:00");
+  }
+
+  #[test]
+  fn bind_falls_back_when_preferred_port_is_taken() {
+    let (blocker, preferred) = SYNC_PORT_RANGE
+      .filter_map(|port| TcpListener::bind(("0.0.0.0", port)).ok().map(|listener| (listener, port)))
+      .next()
+      .expect("no free port in sync range");
+
+    let (_server, port) = bind_sync_server(preferred).expect("fallback port");
+    assert_ne!(port, preferred);
+    assert!(SYNC_PORT_RANGE.contains(&port));
+    drop(blocker);
+  }
+}